@@ -0,0 +1,338 @@
+// Export d'un patch téléchargé en archive .tar.zst ou .zip, avec reprise après interruption
+use crate::downloader::{local_path, DownloadControl};
+use crate::rman::Manifest;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+pub const INDEX_FILE_NAME: &str = "index.json";
+
+// Un point de reprise est enregistré après ce nombre de fichiers ou d'octets
+const CHECKPOINT_FILES: usize = 64;
+const CHECKPOINT_BYTES: u64 = 256 * 1024 * 1024;
+const ZSTD_LEVEL: i32 = 3;
+
+// Distinct des fichiers temporaires du téléchargement pour survivre au nettoyage du démarrage
+const PARTIAL_ARCHIVE_SUFFIX: &str = ".export-partial";
+const CHECKPOINT_SUFFIX: &str = ".export-checkpoint.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArchiveFormat {
+    #[serde(rename = "tar.zst")]
+    TarZst,
+    #[serde(rename = "zip")]
+    Zip,
+}
+
+impl ArchiveFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::TarZst => "tar.zst",
+            ArchiveFormat::Zip => "zip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+// Contenu de index.json, ajouté en dernier dans l'archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveIndex {
+    pub version: String,
+    pub realm: String,
+    pub manifest_hash: String,
+    pub format: ArchiveFormat,
+    pub created_at: String,
+    pub files: Vec<ArchiveEntry>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveInfo {
+    pub version: String,
+    pub realm: String,
+    pub manifest_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub files: usize,
+    pub resumed_files: usize,
+    pub interrupted: bool,
+}
+
+// Point de reprise : taille valide de l'archive partielle et fichiers déjà ajoutés
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Checkpoint {
+    format: ArchiveFormat,
+    manifest_hash: String,
+    bytes: u64,
+    files: Vec<ArchiveEntry>,
+}
+
+fn with_suffix(destination: &Path, suffix: &str) -> PathBuf {
+    let mut name = destination.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn partial_path(destination: &Path) -> PathBuf {
+    with_suffix(destination, PARTIAL_ARCHIVE_SUFFIX)
+}
+
+fn checkpoint_path(destination: &Path) -> PathBuf {
+    with_suffix(destination, CHECKPOINT_SUFFIX)
+}
+
+fn load_checkpoint(destination: &Path, format: ArchiveFormat, manifest_hash: &str) -> Option<Checkpoint> {
+    let content = std::fs::read_to_string(checkpoint_path(destination)).ok()?;
+    let checkpoint: Checkpoint = serde_json::from_str(&content).ok()?;
+    let partial_len = std::fs::metadata(partial_path(destination)).ok()?.len();
+    (checkpoint.format == format && checkpoint.manifest_hash == manifest_hash && checkpoint.bytes <= partial_len)
+        .then_some(checkpoint)
+}
+
+fn save_checkpoint(destination: &Path, checkpoint: &Checkpoint) -> Result<(), String> {
+    let path = checkpoint_path(destination);
+    let temp_path = path.with_extension("tmp");
+    let content = serde_json::to_string(checkpoint).map_err(|e| e.to_string())?;
+    std::fs::write(&temp_path, content)
+        .and_then(|_| std::fs::rename(&temp_path, &path))
+        .map_err(|e| format!("Impossible d'enregistrer le point de reprise {}: {}", path.display(), e))
+}
+
+// Lecteur qui calcule le SHA-256 et compte les octets lus
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+    read: u64,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.hasher.update(&buf[..count]);
+        self.read += count as u64;
+        Ok(count)
+    }
+}
+
+// Écriture d'une entrée tar, avec un en-tête GNU LongLink pour les chemins de plus de 100 octets
+fn write_tar_entry(out: &mut impl Write, path: &str, size: u64, data: &mut impl Read) -> std::io::Result<()> {
+    let name = path.as_bytes();
+    if name.len() > 100 {
+        let mut long_name = name.to_vec();
+        long_name.push(0);
+        let mut header = tar::Header::new_gnu();
+        header.as_gnu_mut().unwrap().name[..13].copy_from_slice(b"././@LongLink");
+        header.set_entry_type(tar::EntryType::GNULongName);
+        header.set_mode(0o644);
+        header.set_size(long_name.len() as u64);
+        header.set_cksum();
+        out.write_all(header.as_bytes())?;
+        write_padded(out, &mut long_name.as_slice(), long_name.len() as u64)?;
+    }
+
+    let mut header = tar::Header::new_gnu();
+    let len = name.len().min(100);
+    header.as_gnu_mut().unwrap().name[..len].copy_from_slice(&name[..len]);
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_mode(0o644);
+    header.set_size(size);
+    header.set_mtime(0);
+    header.set_cksum();
+    out.write_all(header.as_bytes())?;
+    write_padded(out, data, size)
+}
+
+fn write_padded(out: &mut impl Write, data: &mut impl Read, size: u64) -> std::io::Result<()> {
+    let copied = std::io::copy(&mut data.take(size), out)?;
+    if copied != size {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            format!("{} octets lus au lieu de {}", copied, size),
+        ));
+    }
+    let padding = (512 - size % 512) % 512;
+    out.write_all(&vec![0u8; padding as usize])
+}
+
+// Fichiers du manifest présents dans le dossier de la tâche
+fn archived_files(manifest: &Manifest, source_dir: &Path) -> Result<Vec<(String, PathBuf, u64)>, String> {
+    let mut files = Vec::new();
+    for file in manifest.files.iter().filter(|f| f.link.is_empty()) {
+        let path = local_path(source_dir, &file.path)?;
+        if let Ok(metadata) = std::fs::metadata(&path) {
+            if metadata.len() == file.size {
+                files.push((file.path.clone(), path, file.size));
+            }
+        }
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+// Archive en cours d'écriture, fermée proprement à chaque point de reprise
+enum ArchiveWriter {
+    TarZst(zstd::Encoder<'static, File>),
+    Zip(Box<zip::ZipWriter<File>>),
+}
+
+impl ArchiveWriter {
+    fn open(format: ArchiveFormat, partial: &Path, bytes: u64) -> Result<Self, String> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(partial)
+            .map_err(|e| format!("Impossible d'ouvrir {}: {}", partial.display(), e))?;
+        file.set_len(bytes)
+            .and_then(|_| file.seek(SeekFrom::End(0)))
+            .map_err(|e| format!("Impossible de préparer {}: {}", partial.display(), e))?;
+        match format {
+            // Chaque lot est une trame zstd complète, les trames se lisent à la suite
+            ArchiveFormat::TarZst => zstd::Encoder::new(file, ZSTD_LEVEL)
+                .map(ArchiveWriter::TarZst)
+                .map_err(|e| format!("Erreur zstd: {}", e)),
+            ArchiveFormat::Zip if bytes == 0 => Ok(ArchiveWriter::Zip(Box::new(zip::ZipWriter::new(file)))),
+            ArchiveFormat::Zip => zip::ZipWriter::new_append(file)
+                .map(|writer| ArchiveWriter::Zip(Box::new(writer)))
+                .map_err(|e| format!("Archive partielle illisible {}: {}", partial.display(), e)),
+        }
+    }
+
+    fn add(&mut self, name: &str, size: u64, data: &mut impl Read) -> Result<(), String> {
+        match self {
+            ArchiveWriter::TarZst(encoder) => write_tar_entry(encoder, name, size, data).map_err(|e| e.to_string()),
+            ArchiveWriter::Zip(writer) => {
+                let options = zip::write::SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .large_file(size >= u32::MAX as u64);
+                writer.start_file(name, options).map_err(|e| e.to_string())?;
+                let copied = std::io::copy(&mut data.take(size), writer).map_err(|e| e.to_string())?;
+                if copied != size {
+                    return Err(format!("{} octets lus au lieu de {} pour {}", copied, size, name));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // Termine le lot en cours et renvoie la taille valide de l'archive
+    fn close(self, end_of_archive: bool) -> Result<u64, String> {
+        let mut file = match self {
+            ArchiveWriter::TarZst(mut encoder) => {
+                if end_of_archive {
+                    encoder.write_all(&[0u8; 1024]).map_err(|e| e.to_string())?;
+                }
+                encoder.finish().map_err(|e| format!("Erreur zstd: {}", e))?
+            }
+            ArchiveWriter::Zip(writer) => writer.finish().map_err(|e| e.to_string())?,
+        };
+        file.sync_all().map_err(|e| e.to_string())?;
+        file.stream_position().map_err(|e| e.to_string())
+    }
+}
+
+// Emballe le dossier d'une tâche terminée ; un appel après interruption reprend au dernier point de reprise
+pub fn export_archive(
+    manifest: &Manifest,
+    source_dir: &Path,
+    destination: &Path,
+    format: ArchiveFormat,
+    info: &ArchiveInfo,
+    control: &DownloadControl,
+) -> Result<ExportSummary, String> {
+    let files = archived_files(manifest, source_dir)?;
+    if files.is_empty() {
+        return Err(format!("Aucun fichier du manifest dans {}", source_dir.display()));
+    }
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+    }
+
+    let partial = partial_path(destination);
+    let mut checkpoint = load_checkpoint(destination, format, &info.manifest_hash).unwrap_or(Checkpoint {
+        format,
+        manifest_hash: info.manifest_hash.clone(),
+        bytes: 0,
+        files: Vec::new(),
+    });
+    let resumed_files = checkpoint.files.len();
+    let done: std::collections::HashSet<String> = checkpoint.files.iter().map(|e| e.path.clone()).collect();
+    let remaining: Vec<_> = files.into_iter().filter(|(name, _, _)| !done.contains(name)).collect();
+    control.reset_progress(remaining.iter().map(|(_, _, size)| size).sum());
+
+    let mut writer = ArchiveWriter::open(format, &partial, checkpoint.bytes)?;
+    let mut batch_files = 0;
+    let mut batch_bytes = 0;
+    for (name, path, size) in remaining {
+        if control.is_cancelled() {
+            checkpoint.bytes = writer.close(false)?;
+            save_checkpoint(destination, &checkpoint)?;
+            return Ok(ExportSummary {
+                path: destination.display().to_string(),
+                files: checkpoint.files.len(),
+                resumed_files,
+                interrupted: true,
+            });
+        }
+
+        let file = File::open(&path).map_err(|e| format!("Impossible d'ouvrir {}: {}", path.display(), e))?;
+        let mut reader = HashingReader {
+            inner: std::io::BufReader::new(file),
+            hasher: Sha256::new(),
+            read: 0,
+        };
+        writer.add(&name, size, &mut reader)?;
+        control.add_progress(reader.read);
+        checkpoint.files.push(ArchiveEntry {
+            path: name,
+            size,
+            sha256: format!("{:x}", reader.hasher.finalize()),
+        });
+
+        batch_files += 1;
+        batch_bytes += size;
+        if batch_files >= CHECKPOINT_FILES || batch_bytes >= CHECKPOINT_BYTES {
+            checkpoint.bytes = writer.close(false)?;
+            save_checkpoint(destination, &checkpoint)?;
+            writer = ArchiveWriter::open(format, &partial, checkpoint.bytes)?;
+            batch_files = 0;
+            batch_bytes = 0;
+        }
+    }
+
+    let index = ArchiveIndex {
+        version: info.version.clone(),
+        realm: info.realm.clone(),
+        manifest_hash: info.manifest_hash.clone(),
+        format,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        files: checkpoint.files.clone(),
+    };
+    let index = serde_json::to_vec_pretty(&index).map_err(|e| e.to_string())?;
+    writer.add(INDEX_FILE_NAME, index.len() as u64, &mut index.as_slice())?;
+    writer.close(true)?;
+
+    std::fs::rename(&partial, destination)
+        .map_err(|e| format!("Impossible de finaliser {}: {}", destination.display(), e))?;
+    let _ = std::fs::remove_file(checkpoint_path(destination));
+    Ok(ExportSummary {
+        path: destination.display().to_string(),
+        files: checkpoint.files.len(),
+        resumed_files,
+        interrupted: false,
+    })
+}
@@ -0,0 +1,242 @@
+// Banques audio Wwise : conteneurs .wpk (voix) et .bnk (effets) contenant des flux WEM
+use crate::hashes::HashTable;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub const WPK_MAGIC: &[u8; 4] = b"r3d2";
+pub const BNK_MAGIC: &[u8; 4] = b"BKHD";
+
+// Codecs WEM (champ format du chunk fmt)
+const WEM_PCM: u16 = 0x0001;
+const WEM_PCM_EXTENSIBLE: u16 = 0xFFFE;
+const WEM_VORBIS: u16 = 0xFFFF;
+const WEM_ADPCM: u16 = 0x0002;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioContainer {
+    Wpk,
+    Bnk,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioStream {
+    pub id: u32,
+    // Nom stocké dans le .wpk, ou trouvé dans la table de hash pour un .bnk
+    pub name: Option<String>,
+    pub offset: u32,
+    pub size: u32,
+    pub codec: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioBank {
+    pub container: AudioContainer,
+    pub streams: Vec<AudioStream>,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| "Banque audio tronquée".to_string())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, String> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| "Fichier WEM tronqué".to_string())
+}
+
+impl AudioBank {
+    pub fn parse(data: &[u8], table: &HashTable) -> Result<AudioBank, String> {
+        let mut bank = if data.starts_with(WPK_MAGIC) {
+            AudioBank::parse_wpk(data)?
+        } else if data.starts_with(BNK_MAGIC) {
+            AudioBank::parse_bnk(data, table)?
+        } else {
+            return Err("Banque audio inconnue : signature r3d2 (WPK) ou BKHD (BNK) attendue".to_string());
+        };
+        for stream in &mut bank.streams {
+            let codec = stream_data(data, stream).ok().and_then(wem_codec);
+            stream.codec = codec_name(codec).to_string();
+        }
+        Ok(bank)
+    }
+
+    // En-tête, nombre de fichiers puis une table de positions vers (offset, taille, nom UTF-16)
+    fn parse_wpk(data: &[u8]) -> Result<AudioBank, String> {
+        let count = read_u32(data, 8)? as usize;
+        // Pas de réservation d'après le nombre annoncé : la table de positions est lue avant tout
+        let mut streams = Vec::new();
+        for i in 0..count {
+            let header = read_u32(data, 12 + i * 4)? as usize;
+            if header == 0 {
+                continue;
+            }
+            let (offset, size) = (read_u32(data, header)?, read_u32(data, header + 4)?);
+            let length = read_u32(data, header + 8)? as usize;
+            let raw = data
+                .get(header + 12..header + 12 + length * 2)
+                .ok_or_else(|| "Nom de flux WPK tronqué".to_string())?;
+            let units: Vec<u16> = raw.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+            let name = String::from_utf16_lossy(&units);
+            // Les noms sont de la forme "<id>.wem"
+            let id = name.split('.').next().and_then(|id| id.parse().ok()).unwrap_or_default();
+            streams.push(AudioStream {
+                id,
+                name: Some(name),
+                offset,
+                size,
+                codec: String::new(),
+            });
+        }
+        Ok(AudioBank {
+            container: AudioContainer::Wpk,
+            streams,
+        })
+    }
+
+    // Sections (tag, taille) : DIDX indexe les flux, positionnés relativement au début de DATA
+    fn parse_bnk(data: &[u8], table: &HashTable) -> Result<AudioBank, String> {
+        let mut index = None;
+        let mut data_start = None;
+        let mut position = 0;
+        while position + 8 <= data.len() {
+            let size = read_u32(data, position + 4)? as usize;
+            let body = position + 8;
+            match &data[position..position + 4] {
+                b"DIDX" => index = Some((body, size)),
+                b"DATA" => data_start = Some(body),
+                _ => {}
+            }
+            position = body + size;
+        }
+
+        let mut streams = Vec::new();
+        if let Some((start, size)) = index {
+            let data_start = data_start.ok_or_else(|| "Section DATA absente de la banque".to_string())? as u32;
+            for record in (start..start + size).step_by(12) {
+                let id = read_u32(data, record)?;
+                let offset = data_start
+                    .checked_add(read_u32(data, record + 4)?)
+                    .ok_or_else(|| format!("Position du flux {} hors de la banque", id))?;
+                streams.push(AudioStream {
+                    id,
+                    name: table.get(id as u64).map(str::to_string),
+                    offset,
+                    size: read_u32(data, record + 8)?,
+                    codec: String::new(),
+                });
+            }
+        }
+        Ok(AudioBank {
+            container: AudioContainer::Bnk,
+            streams,
+        })
+    }
+}
+
+pub fn stream_data<'a>(data: &'a [u8], stream: &AudioStream) -> Result<&'a [u8], String> {
+    let start = stream.offset as usize;
+    data.get(start..start + stream.size as usize)
+        .ok_or_else(|| format!("Flux {} hors de la banque", stream.id))
+}
+
+// Parcourt les chunks RIFF d'un WEM
+fn riff_chunk<'a>(data: &'a [u8], id: &[u8; 4]) -> Option<&'a [u8]> {
+    if !data.starts_with(b"RIFF") || data.get(8..12) != Some(b"WAVE") {
+        return None;
+    }
+    let mut position = 12;
+    while position + 8 <= data.len() {
+        let size = u32::from_le_bytes(data[position + 4..position + 8].try_into().unwrap()) as usize;
+        let body = position + 8;
+        if &data[position..position + 4] == id {
+            return data.get(body..(body + size).min(data.len()));
+        }
+        position = body + size + (size & 1);
+    }
+    None
+}
+
+pub fn wem_codec(data: &[u8]) -> Option<u16> {
+    riff_chunk(data, b"fmt ").and_then(|fmt| read_u16(fmt, 0).ok())
+}
+
+pub fn codec_name(codec: Option<u16>) -> &'static str {
+    match codec {
+        Some(WEM_PCM) | Some(WEM_PCM_EXTENSIBLE) => "pcm",
+        Some(WEM_VORBIS) => "vorbis",
+        Some(WEM_ADPCM) => "adpcm",
+        Some(_) => "inconnu",
+        None => "invalide",
+    }
+}
+
+// Seuls les WEM PCM se convertissent directement : on réécrit un en-tête WAV standard.
+// Le Vorbis Wwise (paquets et codebooks propriétaires) reste en .wem.
+pub fn wem_to_wav(data: &[u8]) -> Result<Vec<u8>, String> {
+    let fmt = riff_chunk(data, b"fmt ").ok_or_else(|| "Chunk fmt absent du WEM".to_string())?;
+    let codec = read_u16(fmt, 0)?;
+    if codec != WEM_PCM && codec != WEM_PCM_EXTENSIBLE {
+        return Err(format!("Codec WEM non convertible : {} ({:#06x})", codec_name(Some(codec)), codec));
+    }
+    let samples = riff_chunk(data, b"data").ok_or_else(|| "Chunk data absent du WEM".to_string())?;
+    let channels = read_u16(fmt, 2)?;
+    let sample_rate = read_u32(fmt, 4)?;
+    let block_align = read_u16(fmt, 12)?;
+    let bits = read_u16(fmt, 14)?;
+    let byte_rate = sample_rate
+        .checked_mul(block_align as u32)
+        .ok_or_else(|| format!("En-tête WEM invalide : {} Hz × {} octets par bloc", sample_rate, block_align))?;
+    let riff_size = u32::try_from(samples.len())
+        .ok()
+        .and_then(|len| len.checked_add(36))
+        .ok_or_else(|| "Flux WEM trop volumineux pour un WAV".to_string())?;
+
+    let mut wav = Vec::with_capacity(44 + samples.len());
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&riff_size.to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&WEM_PCM.to_le_bytes());
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&byte_rate.to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&bits.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+    wav.extend_from_slice(samples);
+    Ok(wav)
+}
+
+// Extrait les flux demandés (tous si vide) en "<id>.wem", ou en "<id>.wav" quand la conversion est possible
+pub fn extract_streams(
+    data: &[u8],
+    bank: &AudioBank,
+    ids: &[u32],
+    output_dir: &Path,
+    convert: bool,
+) -> Result<Vec<PathBuf>, String> {
+    std::fs::create_dir_all(output_dir).map_err(|e| format!("Impossible de créer {}: {}", output_dir.display(), e))?;
+    let mut written = Vec::new();
+    for stream in bank.streams.iter().filter(|s| ids.is_empty() || ids.contains(&s.id)) {
+        let wem = stream_data(data, stream)?;
+        let stem = match &stream.name {
+            Some(name) => crate::downloader::local_path(output_dir, name)?.with_extension(""),
+            None => output_dir.join(stream.id.to_string()),
+        };
+        let (path, content) = match convert.then(|| wem_to_wav(wem)) {
+            Some(Ok(wav)) => (stem.with_extension("wav"), wav),
+            _ => (stem.with_extension("wem"), wem.to_vec()),
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&path, content).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))?;
+        written.push(path);
+    }
+    Ok(written)
+}
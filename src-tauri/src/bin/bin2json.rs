@@ -0,0 +1,63 @@
+// Conversion en ligne de commande d'un fichier .bin (PROP/PTCH) en JSON
+//
+//   bin2json <fichier.bin> [sortie.json] [--hashes <liste.txt>]...
+//   bin2json --wad <archive.wad.client> <entrée ou hash> [sortie.json] [--hashes <liste.txt>]...
+use app_lib::hashes::HashTable;
+use app_lib::prop_bin::PropBin;
+use app_lib::wad::{self, Wad};
+use std::path::Path;
+
+const USAGE: &str = "Usage: bin2json [--wad <archive.wad.client>] <fichier.bin | entrée> [sortie.json] [--hashes <liste.txt>]...";
+
+fn run() -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut hash_files = Vec::new();
+    let mut wad_path = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--hashes" => hash_files.push(args.next().ok_or(USAGE)?),
+            "--wad" => wad_path = Some(args.next().ok_or(USAGE)?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ => positional.push(arg),
+        }
+    }
+    let input = positional.first().ok_or(USAGE)?;
+
+    let mut table = HashTable::default();
+    for file in &hash_files {
+        let text = std::fs::read_to_string(file).map_err(|e| format!("Impossible de lire {}: {}", file, e))?;
+        table.extend(&text);
+    }
+
+    let data = match &wad_path {
+        Some(wad_path) => {
+            let wad = Wad::open(Path::new(wad_path))?;
+            let entry = wad
+                .entry(wad::entry_hash(input))
+                .ok_or_else(|| format!("Entrée {} absente de {}", input, wad_path))?;
+            wad.read(entry)?
+        }
+        None => std::fs::read(input).map_err(|e| format!("Impossible de lire {}: {}", input, e))?,
+    };
+
+    let json = PropBin::parse(&data)?.to_json(&table);
+    let content = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
+    match positional.get(1) {
+        Some(output) => std::fs::write(output, content).map_err(|e| format!("Impossible d'écrire {}: {}", output, e)),
+        None => {
+            println!("{}", content);
+            Ok(())
+        }
+    }
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,82 @@
+// Chargement du catalogue des manifestes (export CSV de la Google Sheet)
+use crate::products::{Channel, Product};
+use crate::realms::Realm;
+use serde::{Deserialize, Serialize};
+
+// Variable d'environnement permettant de pointer vers une autre source
+pub const CATALOGUE_URL_ENV: &str = "LOL_PATCH_CATALOGUE_URL";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogueEntry {
+    pub product: Product,
+    pub channel: Channel,
+    pub realm: String,
+    pub manifest_url: String,
+    pub date: String,
+    pub size_bytes: u64,
+}
+
+impl CatalogueEntry {
+    // Hash du manifest extrait de son URL (ex: "93A211A9D0F05050")
+    pub fn manifest_hash(&self) -> String {
+        manifest_hash_from_url(&self.manifest_url)
+    }
+}
+
+pub fn manifest_hash_from_url(url: &str) -> String {
+    url.rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(".manifest")
+        .to_string()
+}
+
+// Colonnes : produit, serveur, URL du manifest, date, (non utilisée), taille en octets
+// Les lignes d'un produit inconnu sont ignorées
+pub fn parse_catalogue(csv: &str) -> Vec<CatalogueEntry> {
+    csv.lines()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            let fields: Vec<String> = line
+                .split(',')
+                .map(|f| f.trim().trim_matches('"').to_string())
+                .collect();
+            if fields.len() < 6 {
+                return None;
+            }
+            let realm = Realm::parse(&fields[1]);
+            Some(CatalogueEntry {
+                product: fields[0].parse().ok()?,
+                channel: realm.kind,
+                realm: realm.id,
+                manifest_url: fields[2].clone(),
+                date: fields[3].clone(),
+                size_bytes: fields[5].parse().unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+pub async fn fetch_catalogue(url: &str) -> Result<Vec<CatalogueEntry>, String> {
+    let bytes = crate::downloader::fetch_bytes(url).await?;
+    Ok(parse_catalogue(&String::from_utf8_lossy(&bytes)))
+}
+
+// Même format d'affichage que le frontend ("2.1 GB", "350.0 MB"...)
+pub fn format_size(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+    if bytes == 0 {
+        "Unknown".to_string()
+    } else if bytes > GB {
+        format!("{:.1} GB", bytes as f64 / GB as f64)
+    } else if bytes > MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else if bytes > KB {
+        format!("{:.1} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
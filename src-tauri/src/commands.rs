@@ -1,9 +1,35 @@
+use crate::archive::{self, ArchiveFormat, ArchiveInfo};
+use crate::audio::{self, AudioBank};
+use crate::catalogue::{self, CatalogueEntry};
+use crate::diff::{self, ManifestDiff};
+use crate::disk_space::{self, DEFAULT_FREE_SPACE_RESERVE};
+use crate::downloader::{self, DownloadControl, DownloadOptions, DownloadSummary, VerifyReport};
+use crate::error::DownloadError;
+use crate::game_diff::{self, GameDiffFormat};
+use crate::game_version;
+use crate::hashes::{self, HashTable, UnknownHashReport};
+use crate::history::{self, HistoryQuery, ManifestHistory, ManifestRecord};
+use crate::journal::{self, FileJournal};
+use crate::model::{self, Skeleton, SkinnedMesh};
+use crate::output_template::{self, TemplateValues};
+use crate::patch_calendar::{self, PatchCalendar, PatchVersion};
+use crate::products::{Channel, Product};
+use crate::prop_bin::PropBin;
+use crate::realms::{self, PlatformRegion, Realm};
+use crate::rman::Manifest;
+use crate::settings::{AppSettings, SettingsStore};
+use crate::stall::{StallDetector, STALL_RESTART_DELAY};
+use crate::tasks::{self, CancelMode, DownloadTask, TaskPage, TaskQuery, TaskStatus};
+use crate::stringtable::{self, LanguageDiff, StringTableSummary};
+use crate::texture::{self, TextureBatchReport};
+use crate::wad::{self, NamedWadEntry, Wad};
+use crate::watcher::{self, WatchReport, WatchState, WatchedDownload};
+use crate::webhooks::{self, DeliveryOptions, DeliveryRecord, WebhookEvent, WebhookTarget};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
-use tauri::State;
-use tokio::sync::Mutex;
-use tokio::process::Command as TokioCommand;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::{watch, Mutex};
 use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -11,32 +37,56 @@ pub struct PatchManifest {
     pub version: String,
     pub date: String,
     pub size: String,
+    #[serde(default)]
+    pub size_bytes: u64,
     pub content: String,
     pub manifest: String,
     pub languages: Vec<String>,
     pub region: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct DownloadTask {
-    pub id: String,
-    pub manifest: String,
-    pub version: String,
-    pub status: String,
-    pub progress: f64,
-    pub speed: String,
-    pub eta: String,
-    pub error: Option<String>,
-    pub start_time: String,
-    pub end_time: Option<String>,
-    pub output_path: Option<String>,
+    // Version complète lue dans les fichiers du jeu une fois le patch téléchargé
+    #[serde(default)]
+    pub build_version: Option<String>,
+    // Version d'après le calendrier des patchs (ex: "24.17", "25.S1.3", "24.17.1" pour un micro-patch)
+    #[serde(default)]
+    pub patch_version: Option<PatchVersion>,
+    #[serde(default)]
+    pub product: Product,
+    #[serde(default)]
+    pub channel: Channel,
 }
 
 // État global pour stocker les données
 pub struct AppState {
     pub manifests: Mutex<Vec<PatchManifest>>,
     pub downloads: Mutex<HashMap<String, DownloadTask>>,
-    pub download_processes: Mutex<HashMap<String, Arc<Mutex<Option<tokio::process::Child>>>>>,
+    pub download_controls: Mutex<HashMap<String, Arc<DownloadControl>>>,
+    pub export_jobs: Mutex<HashMap<String, ExportJob>>,
+    pub hash_table: Mutex<Arc<HashTable>>,
+    // URL de manifest -> version du jeu détectée
+    pub game_versions: Mutex<HashMap<String, String>>,
+    pub patch_calendar: Mutex<Arc<PatchCalendar>>,
+    pub watch_state: Mutex<WatchState>,
+    // Ouverte au démarrage, absente si la base est inaccessible
+    pub history: Mutex<Option<ManifestHistory>>,
+    // Téléchargements en cours d'exécution -> sort de leurs fichiers à la fin (annulation pendant l'exécution)
+    pub running_downloads: Mutex<HashMap<String, RunningDownload>>,
+}
+
+// Exécution du moteur pour une tâche ; une reprise remplace l'entrée de l'exécution précédente
+#[derive(Debug, Clone)]
+pub struct RunningDownload {
+    pub control: Arc<DownloadControl>,
+    pub on_exit: CancelMode,
+    // Passe à true quand l'exécution est terminée (fichiers libérés)
+    pub exited: watch::Receiver<bool>,
+}
+
+// Paramètres d'un export d'archive, conservés pour pouvoir le reprendre
+#[derive(Debug, Clone)]
+pub struct ExportJob {
+    pub source_task_id: String,
+    pub format: ArchiveFormat,
+    pub destination: PathBuf,
 }
 
 impl Default for AppState {
@@ -44,7 +94,14 @@ impl Default for AppState {
         Self {
             manifests: Mutex::new(Vec::new()),
             downloads: Mutex::new(HashMap::new()),
-            download_processes: Mutex::new(HashMap::new()),
+            download_controls: Mutex::new(HashMap::new()),
+            export_jobs: Mutex::new(HashMap::new()),
+            hash_table: Mutex::new(Arc::new(HashTable::default())),
+            game_versions: Mutex::new(HashMap::new()),
+            patch_calendar: Mutex::new(Arc::new(PatchCalendar::default())),
+            watch_state: Mutex::new(WatchState::default()),
+            history: Mutex::new(None),
+            running_downloads: Mutex::new(HashMap::new()),
         }
     }
 }
@@ -56,621 +113,2182 @@ fn get_test_manifests() -> Vec<PatchManifest> {
             version: "14.17.1".to_string(),
             date: "2024-08-28".to_string(),
             size: "2.1 GB".to_string(),
+            size_bytes: 2_254_857_830,
             content: "assets".to_string(),
             manifest: "93A211A9D0F05050.manifest".to_string(),
             languages: vec!["en_us".to_string(), "fr_fr".to_string()],
-            region: "NA".to_string(),
+            region: "NA1".to_string(),
+            build_version: None,
+            patch_version: None,
+            product: Product::Lol,
+            channel: Channel::Live,
         },
         PatchManifest {
             version: "14.17.0".to_string(),
             date: "2024-08-21".to_string(),
             size: "1.8 GB".to_string(),
+            size_bytes: 1_932_735_283,
             content: "assets".to_string(),
             manifest: "8B2F119C0E04040.manifest".to_string(),
             languages: vec!["en_us".to_string(), "fr_fr".to_string(), "ja_jp".to_string()],
-            region: "EUW".to_string(),
+            region: "EUW1".to_string(),
+            build_version: None,
+            patch_version: None,
+            product: Product::Lol,
+            channel: Channel::Live,
         },
         PatchManifest {
             version: "14.16.1".to_string(),
             date: "2024-08-14".to_string(),
             size: "2.3 GB".to_string(),
+            size_bytes: 2_469_606_195,
             content: "sounds".to_string(),
             manifest: "7A1E008B0D03030.manifest".to_string(),
             languages: vec!["en_us".to_string(), "ko_kr".to_string()],
             region: "KR".to_string(),
+            build_version: None,
+            patch_version: None,
+            product: Product::Lol,
+            channel: Channel::Live,
         },
         PatchManifest {
             version: "14.16.0".to_string(),
             date: "2024-08-07".to_string(),
             size: "1.9 GB".to_string(),
+            size_bytes: 2_040_109_465,
             content: "assets".to_string(),
             manifest: "690FDD7A0C02020.manifest".to_string(),
             languages: vec!["en_us".to_string(), "fr_fr".to_string(), "zh_cn".to_string()],
-            region: "JP".to_string(),
+            region: "JP1".to_string(),
+            build_version: None,
+            patch_version: None,
+            product: Product::Lol,
+            channel: Channel::Live,
         },
         PatchManifest {
             version: "14.15.1".to_string(),
             date: "2024-07-31".to_string(),
             size: "2.0 GB".to_string(),
+            size_bytes: 2_147_483_648,
             content: "assets".to_string(),
             manifest: "580ECC690B01010.manifest".to_string(),
             languages: vec!["en_us".to_string()],
-            region: "NA".to_string(),
+            region: "NA1".to_string(),
+            build_version: None,
+            patch_version: None,
+            product: Product::Lol,
+            channel: Channel::Live,
         },
     ]
 }
 
+// Conversion d'une ligne du catalogue vers le format attendu par le frontend
+fn manifest_from_entry(entry: &CatalogueEntry) -> PatchManifest {
+    let version = if entry.channel != Channel::Live || !entry.product.uses_patch_calendar() {
+        format!("Version {}", entry.manifest_hash())
+    } else {
+        format!("Patch {}", entry.date)
+    };
+
+    PatchManifest {
+        version,
+        date: entry.date.clone(),
+        size: catalogue::format_size(entry.size_bytes),
+        size_bytes: entry.size_bytes,
+        content: "assets".to_string(),
+        manifest: entry.manifest_url.clone(),
+        languages: vec!["en_us".to_string()],
+        region: entry.realm.clone(),
+        build_version: None,
+        patch_version: None,
+        product: entry.product,
+        channel: entry.channel,
+    }
+}
+
+// Versions d'après le calendrier, produit par produit et serveur par serveur pour numéroter les micro-patchs
+fn apply_patch_calendar(manifests: &mut [PatchManifest], calendar: &PatchCalendar) {
+    let mut realms: HashMap<(Product, String), Vec<usize>> = HashMap::new();
+    for (i, manifest) in manifests.iter().enumerate() {
+        if manifest.channel == Channel::Live && manifest.product.uses_patch_calendar() {
+            realms.entry((manifest.product, manifest.region.clone())).or_default().push(i);
+        }
+    }
+    for indices in realms.values() {
+        let dates: Vec<&str> = indices.iter().map(|i| manifests[*i].date.as_str()).collect();
+        let versions = calendar.label_versions(&dates);
+        for (i, version) in indices.iter().zip(versions) {
+            if let Some(version) = version {
+                manifests[*i].version = format!("Patch {}", version);
+                manifests[*i].patch_version = Some(version);
+            }
+        }
+    }
+}
+
+// Remplace la version déduite du catalogue par celle lue dans un patch déjà téléchargé
+fn apply_game_version(manifest: &mut PatchManifest, build: &str, calendar: &PatchCalendar) {
+    if let Some(version) = calendar.from_build(build) {
+        manifest.version = format!("Patch {}", version);
+        manifest.patch_version = Some(version);
+    }
+    manifest.build_version = Some(build.to_string());
+}
+
+fn history_record(manifest: &PatchManifest) -> ManifestRecord {
+    ManifestRecord {
+        url: manifest.manifest.clone(),
+        realm: manifest.region.clone(),
+        hash: catalogue::manifest_hash_from_url(&manifest.manifest),
+        date: manifest.date.clone(),
+        size_bytes: manifest.size_bytes,
+        version: manifest.version.clone(),
+        patch_version: manifest.patch_version,
+        build_version: manifest.build_version.clone(),
+        first_seen: String::new(),
+        last_seen: String::new(),
+    }
+}
+
+// Calendrier modifiable dans le dossier de configuration, sinon celui fourni avec l'application
+async fn load_patch_calendar(app: &AppHandle) -> PatchCalendar {
+    let Ok(path) = app.path().app_config_dir().map(|dir| dir.join(patch_calendar::CALENDAR_FILE_NAME)) else {
+        return PatchCalendar::default();
+    };
+    if !path.exists() {
+        return PatchCalendar::default();
+    }
+    match tokio::task::spawn_blocking(move || PatchCalendar::load(&path)).await {
+        Ok(Ok(calendar)) => calendar,
+        Ok(Err(e)) => {
+            println!("⚠️ {}, calendrier par défaut utilisé", e);
+            PatchCalendar::default()
+        }
+        Err(e) => {
+            println!("⚠️ {}", e);
+            PatchCalendar::default()
+        }
+    }
+}
+
+// Adresse du catalogue : paramètres de surveillance, sinon variable d'environnement
+async fn catalogue_source(app: &AppHandle) -> Option<String> {
+    let configured = app.state::<SettingsStore>().get().await.watch.catalogue_url;
+    Some(configured.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .or_else(|| std::env::var(catalogue::CATALOGUE_URL_ENV).ok())
+        .filter(|url| !url.trim().is_empty())
+}
+
 #[tauri::command]
-pub async fn fetch_manifests(state: State<'_, AppState>) -> Result<Vec<PatchManifest>, String> {
+pub async fn fetch_manifests(
+    source_url: Option<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<PatchManifest>, String> {
     println!("🔍 Commande fetch_manifests appelée");
+    // Relu à chaque actualisation pour prendre en compte les modifications du fichier
+    let calendar = Arc::new(load_patch_calendar(&app).await);
+    *state.patch_calendar.lock().await = calendar.clone();
     let mut manifests = state.manifests.lock().await;
-    
-    // Utiliser les données de test
-    println!("🔄 Utilisation des données de test...");
-    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-    
-    let test_data = get_test_manifests();
-    println!("📦 {} manifestes de test chargés", test_data.len());
-    *manifests = test_data;
-    
+
+    let source_url = match source_url {
+        Some(url) => Some(url),
+        None => catalogue_source(&app).await,
+    };
+    match source_url {
+        Some(url) => {
+            println!("🔄 Chargement du catalogue depuis {}...", url);
+            let entries = catalogue::fetch_catalogue(&url).await?;
+            println!("📦 {} manifestes chargés depuis le catalogue", entries.len());
+            *manifests = entries.iter().map(manifest_from_entry).collect();
+        }
+        None if cfg!(debug_assertions) => {
+            // Utiliser les données de test (builds de développement uniquement)
+            println!("🔄 Utilisation des données de test...");
+            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+
+            let test_data = get_test_manifests();
+            println!("📦 {} manifestes de test chargés", test_data.len());
+            *manifests = test_data;
+        }
+        None => {
+            return Err("Aucun catalogue configuré : renseignez son adresse dans les paramètres de surveillance".to_string());
+        }
+    }
+
+    apply_patch_calendar(&mut manifests, &calendar);
+    let versions = state.game_versions.lock().await;
+    for manifest in manifests.iter_mut() {
+        if let Some(build) = versions.get(&manifest.manifest) {
+            apply_game_version(manifest, build, &calendar);
+        }
+    }
+
+    // Tous les manifests vus sont conservés dans l'historique
+    if let Some(history) = state.history.lock().await.as_mut() {
+        let records: Vec<ManifestRecord> = manifests.iter().map(history_record).collect();
+        match history.record(&records, &chrono::Utc::now().to_rfc3339()) {
+            Ok(added) if added > 0 => println!("🗄️ {} nouveaux manifests ajoutés à l'historique", added),
+            Ok(_) => {}
+            Err(e) => println!("⚠️ {}", e),
+        }
+    }
+
     Ok(manifests.clone())
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RealmOverview {
+    pub preferred: Option<Realm>,
+    pub groups: BTreeMap<PlatformRegion, Vec<Realm>>,
+}
+
+// Serveurs présents dans le catalogue (ou le registre s'il n'est pas encore chargé), par région
+#[tauri::command]
+pub async fn list_realms(app: AppHandle, state: State<'_, AppState>) -> Result<RealmOverview, String> {
+    let mut available: Vec<Realm> = state.manifests.lock().await.iter().map(|m| Realm::parse(&m.region)).collect();
+    if available.is_empty() {
+        available = realms::registry();
+    }
+    let groups = realms::group_by_region(&available);
+    let ordered: Vec<Realm> = groups.values().flatten().cloned().collect();
+    let preferred_realm = app.state::<SettingsStore>().get().await.preferred_realm;
+    Ok(RealmOverview {
+        preferred: realms::pick_preferred(&ordered, &preferred_realm),
+        groups,
+    })
+}
+
+// Manifests du catalogue filtrés par serveur, type de serveur, région ou produit
+#[tauri::command]
+pub async fn filter_manifests(
+    realm: Option<String>,
+    kind: Option<Channel>,
+    region: Option<PlatformRegion>,
+    product: Option<Product>,
+    state: State<'_, AppState>,
+) -> Result<Vec<PatchManifest>, String> {
+    let manifests = state.manifests.lock().await;
+    Ok(manifests
+        .iter()
+        .filter(|m| {
+            let info = Realm::parse(&m.region);
+            realm.as_deref().map_or(true, |r| realms::same_realm(r, &m.region))
+                && kind.map_or(true, |k| info.kind == k)
+                && region.map_or(true, |r| info.region == r)
+                && product.map_or(true, |p| m.product == p)
+        })
+        .cloned()
+        .collect())
+}
+
 #[tauri::command]
 pub async fn start_download(
     manifest: String,
     language: String,
     content: String,
     output_path: Option<String>,
+    ignore_disk_space: Option<bool>,
+    app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    let task_id = format!("task_{}", chrono::Utc::now().timestamp_millis());
+) -> Result<String, DownloadError> {
+    // Valeurs par défaut issues des paramètres
+    let settings = app.state::<SettingsStore>().get().await;
+    let patch = state.manifests.lock().await.iter().find(|m| m.manifest == manifest).cloned();
+    let product_language = patch.as_ref().and_then(|p| p.product.default_language());
+    let language = if !language.trim().is_empty() {
+        language
+    } else {
+        product_language.map(str::to_string).unwrap_or_else(|| settings.language.clone())
+    };
+    let content = if content.trim().is_empty() { settings.content_filter.clone() } else { content };
+    let output_path = output_path
+        .filter(|p| !p.trim().is_empty())
+        .or_else(|| Some(settings.download_path.clone()).filter(|p| !p.trim().is_empty()));
+    let check_space = !ignore_disk_space.unwrap_or(false);
+
+    // Dossier propre au patch, construit à partir du modèle et des informations du catalogue
+    let output_dir = patch_output_dir(&settings.output_template, output_path, &manifest, patch.as_ref(), &language, &content)?;
+    println!("📁 Dossier du patch: {}", output_dir.display());
+
+    // Premier contrôle avec la taille annoncée par le catalogue, avant de télécharger quoi que ce soit
+    let catalogue_size = patch.as_ref().map(|m| m.size_bytes).unwrap_or(0);
+    if catalogue_size > 0 {
+        match disk_space::check_disk_space(&output_dir, catalogue_size, DEFAULT_FREE_SPACE_RESERVE) {
+            Ok(_) => {}
+            Err(e) if check_space => {
+                println!("❌ {}", e);
+                return Err(e);
+            }
+            Err(e) => println!("⚠️ {} (contrôle ignoré)", e),
+        }
+    }
+
+    let task_id = tasks::new_task_id("task");
+    let output_path = Some(output_dir.to_string_lossy().to_string());
     
     let task = DownloadTask {
-        id: task_id.clone(),
-        manifest: manifest.clone(),
-        version: "Unknown".to_string(),
-        status: "pending".to_string(),
-        progress: 0.0,
-        speed: "0 MB/s".to_string(),
-        eta: "--".to_string(),
-        error: None,
-        start_time: chrono::Utc::now().to_rfc3339(),
-        end_time: None,
-        output_path: output_path.clone(),
+        language: language.clone(),
+        content_filter: content.clone(),
+        ..DownloadTask::new(
+            task_id.clone(),
+            manifest.clone(),
+            patch.as_ref().map(|p| p.version.clone()).unwrap_or_else(|| "Unknown".to_string()),
+            output_path.clone(),
+            patch.as_ref().map(|p| p.region.clone()),
+        )
     };
     
+    // Refuser deux tâches actives dans le même dossier (contrôle et insertion sous le même verrou)
     let mut downloads = state.downloads.lock().await;
+    let active = downloads
+        .values()
+        .filter(|t| t.is_active())
+        .filter_map(|t| t.output_path.as_deref().map(|p| (t.id.as_str(), Path::new(p))));
+    if let Some(other) = output_template::find_collision(active, &output_dir) {
+        let error = DownloadError::OutputDirectoryInUse {
+            path: output_dir.display().to_string(),
+            task_id: other.to_string(),
+        };
+        println!("❌ {}", error);
+        return Err(error);
+    }
     downloads.insert(task_id.clone(), task);
+    let (control, exited) = register_run(&state, &task_id).await;
     
-    // Démarrer le vrai téléchargement avec rman-dl.exe
-    let task_id_clone = task_id.clone();
-    let manifest_clone = manifest.clone();
-    let language_clone = language.clone();
-    let content_clone = content.clone();
-    let output_path_clone = output_path.clone();
-    
-    tokio::spawn(async move {
-        execute_real_download(task_id_clone, manifest_clone, language_clone, content_clone, output_path_clone).await;
-    });
+    // Démarrer le téléchargement avec le moteur natif
+    let run = DownloadRun {
+        task_id: task_id.clone(),
+        manifest_url: manifest,
+        language,
+        content,
+        output_path,
+        check_space,
+        control,
+    };
+    tokio::spawn(execute_real_download(app, run, exited));
     
     Ok(task_id)
 }
 
 #[tauri::command]
 pub async fn pause_download(task_id: String, state: State<'_, AppState>) -> Result<(), String> {
-    let downloads = state.downloads.lock().await;
-    if let Some(_task) = downloads.get(&task_id) {
-        // Note: En production, on mettrait à jour via un mécanisme plus robuste
+    let mut downloads = state.downloads.lock().await;
+    if let Some(task) = downloads.get_mut(&task_id) {
+        task.transition(TaskStatus::Paused, None)?;
+        keep_partial_files(&task_id, true);
         println!("📊 Task {} mise en pause", task_id);
     }
 
-    // Arrêter le processus si en cours
-    let processes = state.download_processes.lock().await;
-    if let Some(process_arc) = processes.get(&task_id) {
-        if let Some(child) = &mut *process_arc.lock().await {
-            let _ = child.kill().await;
-        }
+    // Interrompre le moteur, les fichiers déjà écrits sont conservés pour la reprise
+    let controls = state.download_controls.lock().await;
+    if let Some(control) = controls.get(&task_id) {
+        control.cancel();
     }
     
     Ok(())
 }
 
 #[tauri::command]
-pub async fn resume_download(task_id: String, state: State<'_, AppState>) -> Result<(), String> {
-    let downloads = state.downloads.lock().await;
-    if let Some(task) = downloads.get(&task_id) {
-        
-        // Redémarrer le téléchargement
-        let task_id_clone = task_id.clone();
-        let manifest_clone = task.manifest.clone();
-        let language = "en_us".to_string(); // Par défaut
-        let content = "".to_string(); // Par défaut
-        let output_path = task.output_path.clone();
-        
-        tokio::spawn(async move {
-            execute_real_download(task_id_clone, manifest_clone, language, content, output_path).await;
-        });
+pub async fn resume_download(
+    task_id: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+    store: State<'_, SettingsStore>,
+) -> Result<(), String> {
+    if let Some(job) = state.export_jobs.lock().await.get(&task_id).cloned() {
+        let mut downloads = state.downloads.lock().await;
+        if let Some(task) = downloads.get_mut(&task_id) {
+            task.transition(TaskStatus::Queued, None)?;
+            task.error = None;
+            tokio::spawn(execute_export(app, task_id, job));
+        }
+        return Ok(());
+    }
+
+    // Le moteur mis en pause termine ses écritures en cours : attendre sa sortie
+    // pour que deux exécutions n'écrivent jamais dans les mêmes fichiers
+    if let Some(task) = state.downloads.lock().await.get(&task_id) {
+        task.clone().transition(TaskStatus::Queued, None)?;
+    }
+    let previous = state.running_downloads.lock().await.get(&task_id).map(|r| r.exited.clone());
+    if let Some(mut exited) = previous {
+        println!("⏳ Attente de la fin de l'exécution précédente - Task: {}", task_id);
+        // Erreur = exécution disparue sans signaler sa fin, elle est donc terminée aussi
+        let _ = exited.wait_for(|done| *done).await;
+    }
+
+    let settings = store.get().await;
+    let mut downloads = state.downloads.lock().await;
+    if let Some(task) = downloads.get_mut(&task_id) {
+        // Déjà relancée par une autre reprise pendant l'attente
+        if state.running_downloads.lock().await.contains_key(&task_id) {
+            return Ok(());
+        }
+        task.transition(TaskStatus::Queued, None)?;
+        task.error = None;
+        keep_partial_files(&task_id, false);
+
+        // Redémarrer le téléchargement (les fichiers déjà vérifiés sont ignorés)
+        let (control, exited) = register_run(&state, &task_id).await;
+        let run = DownloadRun {
+            task_id: task_id.clone(),
+            manifest_url: task.manifest.clone(),
+            language: Some(task.language.clone()).filter(|l| !l.is_empty()).unwrap_or(settings.language),
+            content: task.content_filter.clone(),
+            output_path: task.output_path.clone(),
+            check_space: true,
+            control,
+        };
+        tokio::spawn(execute_real_download(app, run, exited));
     }
     Ok(())
 }
 
+// Annuler une tâche en conservant ses fichiers (repris par un nouveau téléchargement du même patch)
+// ou en supprimant ceux qu'elle a créés ; les fichiers déjà présents ne sont jamais supprimés
 #[tauri::command]
-pub async fn cancel_download(task_id: String, state: State<'_, AppState>) -> Result<(), String> {
+pub async fn cancel_download(task_id: String, mode: Option<CancelMode>, state: State<'_, AppState>) -> Result<(), String> {
+    let mode = mode.unwrap_or_default();
     let mut downloads = state.downloads.lock().await;
     if let Some(task) = downloads.get_mut(&task_id) {
-        task.status = "error".to_string();
-        task.error = Some("Téléchargement annulé".to_string());
+        let reason = (mode == CancelMode::Delete).then(|| "Fichiers de la tâche supprimés".to_string());
+        task.transition(TaskStatus::Cancelled, reason)?;
+        keep_partial_files(&task_id, mode == CancelMode::Keep);
     }
     
-    // Arrêter le processus
-    let processes = state.download_processes.lock().await;
-    if let Some(process_arc) = processes.get(&task_id) {
-        if let Some(child) = &mut *process_arc.lock().await {
-            let _ = child.kill().await;
+    // Arrêter le moteur
+    let controls = state.download_controls.lock().await;
+    if let Some(control) = controls.get(&task_id) {
+        control.cancel();
+    }
+    drop(controls);
+
+    // Suppression immédiate si rien ne tourne, sinon à la fin de l'exécution pour ne pas courir après le moteur
+    if mode == CancelMode::Delete {
+        let mut running = state.running_downloads.lock().await;
+        match running.get_mut(&task_id) {
+            Some(run) => run.on_exit = CancelMode::Delete,
+            None => {
+                drop(running);
+                drop(downloads);
+                remove_task_files(task_id).await;
+            }
         }
     }
     
     Ok(())
 }
 
+// Supprime les fichiers créés par une tâche d'après son journal, puis son manifest local
+async fn remove_task_files(task_id: String) {
+    match delete_task_files(&task_id).await {
+        Ok(report) => {
+            println!("🧹 Task {} : {} fichier(s) et {} dossier(s) supprimé(s)", task_id, report.removed_files, report.removed_dirs);
+            for kept in report.kept {
+                println!("⚠️ Conservé (non vide ou verrouillé) : {}", kept);
+            }
+        }
+        Err(e) => println!("❌ Nettoyage de la tâche {} : {}", task_id, e),
+    }
+}
+
+// Autre tâche dont le journal note des fichiers dans ce dossier, y compris une tâche lancée lors d'une session précédente
+fn journal_collision(task_id: &str, dir: &Path) -> Option<String> {
+    let entries = std::fs::read_dir(task_files_dir()).ok()?;
+    entries.flatten().map(|entry| entry.path()).find_map(|path| {
+        let name = path.file_name()?.to_string_lossy().to_string();
+        let other = name.strip_prefix("journal_")?.strip_suffix(".jsonl")?;
+        if other == task_id {
+            return None;
+        }
+        let recorded = journal::read_journal(&path).ok()?;
+        recorded.iter().any(|p| p.starts_with(dir)).then(|| other.to_string())
+    })
+}
+
+async fn delete_task_files(task_id: &str) -> Result<journal::CleanupReport, String> {
+    let journal_path = task_journal_path(task_id);
+    let manifest_path = manifest_file_path(task_id);
+    tokio::task::spawn_blocking(move || {
+        let report = journal::remove_created(&journal_path)?;
+        match std::fs::remove_file(&manifest_path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Impossible de supprimer {}: {}", manifest_path.display(), e))
+            }
+            _ => Ok(report),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 pub async fn get_download_progress(task_id: String, state: State<'_, AppState>) -> Result<Option<DownloadTask>, String> {
     let downloads = state.downloads.lock().await;
     Ok(downloads.get(&task_id).cloned())
 }
 
-// Nouvelle commande pour sélectionner le dossier de destination
+// Liste filtrée et paginée des tâches, les plus récentes d'abord
 #[tauri::command]
-pub async fn select_download_folder() -> Result<Option<String>, String> {
-    println!("📁 Ouverture de la boîte de dialogue de sélection de dossier...");
-
-    // Ouvrir la boîte de dialogue native pour sélectionner un dossier
-    let dialog_result = rfd::FileDialog::new()
-        .set_title("Sélectionner le dossier de destination pour les patches LoL")
-        .set_directory(dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("C:\\")))
-        .pick_folder();
-
-    match dialog_result {
-        Some(selected_path) => {
-            println!("📁 Dossier sélectionné: {}", selected_path.display());
-
-            // Créer le dossier s'il n'existe pas
-            if !selected_path.exists() {
-                std::fs::create_dir_all(&selected_path).map_err(|e| {
-                    println!("⚠️ Impossible de créer le dossier: {}", e);
-                    format!("Impossible de créer le dossier: {}", e)
-                })?;
-            }
-
-            Ok(Some(selected_path.to_string_lossy().to_string()))
-        }
+pub async fn list_downloads(query: Option<TaskQuery>, state: State<'_, AppState>) -> Result<TaskPage, String> {
+    let downloads = state.downloads.lock().await;
+    Ok(query.unwrap_or_default().page(downloads.values()))
+}
 
-        None => {
-            println!("❌ Aucun dossier sélectionné par l'utilisateur");
-            Ok(None)
+// Retire une tâche terminée de la liste ; les fichiers téléchargés sont conservés
+#[tauri::command]
+pub async fn remove_download(task_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let mut downloads = state.downloads.lock().await;
+    match downloads.get(&task_id) {
+        None => return Err(format!("Tâche {} introuvable", task_id)),
+        Some(task) if task.is_active() => {
+            return Err(format!("La tâche {} est en cours, annulez-la avant de la retirer", task_id));
         }
+        Some(_) => {}
     }
+    downloads.remove(&task_id);
+    drop(downloads);
+    forget_task(&state, &task_id).await;
+    println!("🗑️ Tâche {} retirée", task_id);
+    Ok(())
 }
 
-// Fonction pour mettre à jour le statut d'un téléchargement
-async fn update_download_status(task_id: String, status: String, progress: f64, error: String) {
-    // Note: Dans un vrai environnement, on utiliserait l'état global pour mettre à jour le statut
-    // Pour l'instant, on se contente d'un log
-    println!("📊 Mise à jour statut - Task: {}, Status: {}, Progress: {}%, Error: {}", task_id, status, progress, error);
-}
-
-// Fonction pour simuler la progression du téléchargement
-async fn simulate_download_progress(task_id: String) {
-    let mut progress = 0.0;
-
-    // Mettre à jour le statut initial
-    update_download_status(task_id.clone(), "downloading".to_string(), progress, String::new()).await;
-
-    // Simuler la progression
-    while progress < 95.0 {
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-
-        // Progression non linéaire (plus lente au début et à la fin)
-        let increment = if progress < 10.0 {
-            2.0 + rand::random::<f64>() * 3.0
-        } else if progress > 80.0 {
-            0.5 + rand::random::<f64>() * 1.5
-        } else {
-            3.0 + rand::random::<f64>() * 5.0
-        };
-
-        progress = (progress + increment).min(95.0);
-
-        // Simuler la vitesse et l'ETA
-        let speed = format!("{:.1} MB/s", 1.0 + rand::random::<f64>() * 2.0);
-        let remaining = ((100.0 - progress) / 10.0).round() as i32;
-        let eta = if remaining > 60 {
-            format!("{}m {}s", remaining / 60, remaining % 60)
-        } else {
-            format!("{}s", remaining * 6)
-        };
-
-        println!("📊 Progression simulée - Task: {}, Progress: {:.1}%, Speed: {}, ETA: {}",
-                task_id, progress, speed, eta);
-
-        update_download_status(task_id.clone(), "downloading".to_string(), progress, String::new()).await;
+// Retire toutes les tâches terminées ou en erreur, renvoie leurs identifiants
+#[tauri::command]
+pub async fn clear_finished(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let mut downloads = state.downloads.lock().await;
+    let finished: Vec<String> = downloads.values().filter(|t| !t.is_active()).map(|t| t.id.clone()).collect();
+    for task_id in &finished {
+        downloads.remove(task_id);
     }
-
-    // Attendre un peu avant de marquer comme terminé
-    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+    drop(downloads);
+    for task_id in &finished {
+        forget_task(&state, task_id).await;
+    }
+    println!("🗑️ {} tâche(s) terminée(s) retirée(s)", finished.len());
+    Ok(finished)
 }
 
-// Exécuter un vrai téléchargement avec rman-dl.exe
-async fn execute_real_download(
-    task_id: String, 
-    manifest_url: String, 
-    language: String, 
-    content: String,
-    output_path: Option<String>
-) {
-    println!("🚀 Démarrage du téléchargement réel pour task: {}", task_id);
-    println!("📄 Manifest URL: {}", manifest_url);
-    println!("🌍 Langue: {}", language);
-    println!("📦 Contenu: {}", content);
-    println!("📁 Dossier de sortie: {:?}", output_path);
-    
-    // 1. Télécharger le manifest en binaire
-    println!("📥 Téléchargement du manifest...");
-    let manifest_content = match reqwest::get(&manifest_url).await {
-        Ok(response) => {
-            println!("📡 Status HTTP: {}", response.status());
-            
-            if response.status().is_success() {
-                match response.bytes().await {
-                    Ok(bytes) => {
-                        println!("✅ Manifest téléchargé ({} bytes)", bytes.len());
-                        bytes
-                    }
-                    Err(e) => {
-                        println!("❌ Erreur lors de la lecture du manifest: {}", e);
-                        return;
-                    }
-                }
-            } else {
-                println!("❌ Erreur HTTP: {}", response.status());
-                return;
-            }
-        }
-        Err(e) => {
-            println!("❌ Erreur lors du téléchargement du manifest: {}", e);
-            return;
-        }
-    };
-    
-    // 2. Sauvegarder le manifest localement
-    let manifest_filename = format!("manifest_{}.manifest", task_id);
-    let manifest_path = if cfg!(debug_assertions) {
-        std::env::current_dir()
-            .unwrap()
-            .parent()
-            .unwrap()
-            .join("downloads")
-            .join(&manifest_filename)
-    } else {
-        std::env::current_dir()
-            .unwrap()
-            .join("downloads")
-            .join(&manifest_filename)
-    };
-    
-    // Créer le dossier downloads s'il n'existe pas
-    if let Some(parent) = manifest_path.parent() {
-        if !parent.exists() {
-            std::fs::create_dir_all(parent).unwrap_or_else(|e| {
-                println!("❌ Erreur lors de la création du dossier downloads: {}", e);
-                return;
-            });
+// Données annexes d'une tâche retirée : contrôle du moteur, export et manifest local
+async fn forget_task(state: &AppState, task_id: &str) {
+    state.download_controls.lock().await.remove(task_id);
+    let mut export_jobs = state.export_jobs.lock().await;
+    export_jobs.remove(task_id);
+    // Le manifest reste nécessaire tant qu'un export de la tâche est listé
+    let exported = export_jobs.values().any(|job| job.source_task_id == task_id);
+    drop(export_jobs);
+    // Sans son journal, les fichiers d'une tâche retirée ne seront plus jamais supprimés automatiquement :
+    // ses fichiers temporaires partent avec lui, sauf s'ils ont été conservés (pause, annulation sans suppression)
+    let journal_path = task_journal_path(task_id);
+    if !partial_files_kept(task_id) {
+        if let Err(e) = journal::remove_recorded_partials(&journal_path) {
+            println!("⚠️ {}", e);
         }
     }
-    
-    // Sauvegarder le manifest en binaire
-    match std::fs::write(&manifest_path, &manifest_content) {
-        Ok(_) => println!("✅ Manifest sauvegardé: {} ({} bytes)", manifest_path.display(), manifest_content.len()),
-        Err(e) => {
-            println!("❌ Erreur lors de la sauvegarde du manifest: {}", e);
-            return;
+    let _ = std::fs::remove_file(journal_path);
+    keep_partial_files(task_id, false);
+    let manifest_path = manifest_file_path(task_id);
+    if !exported && manifest_path.exists() {
+        if let Err(e) = std::fs::remove_file(&manifest_path) {
+            println!("⚠️ Impossible de supprimer {}: {}", manifest_path.display(), e);
         }
     }
-    
-    // 3. Chemin vers l'exécutable rman-dl.exe
-    let exe_path = if cfg!(debug_assertions) {
-        // En mode debug, chercher dans le répertoire assets du projet
-        let current_dir = std::env::current_dir().unwrap();
-        let project_root = current_dir.parent().unwrap().parent().unwrap();
-        let assets_path = project_root.join("assets").join("rman-dl.exe");
-
-        println!("🔍 Recherche dans: {}", assets_path.display());
-        if assets_path.exists() {
-            assets_path.to_string_lossy().to_string()
-        } else {
-            println!("❌ rman-dl.exe non trouvé dans {}", assets_path.display());
-            return;
-        }
-    } else {
-        // Essayer plusieurs chemins possibles pour l'exécutable en production
-        let current_dir = std::env::current_dir().unwrap();
-        let exe_dir = std::env::current_exe().unwrap().parent().unwrap().to_path_buf();
-
-        let possible_paths = vec![
-            "rman-dl.exe".to_string(),
-            "./rman-dl.exe".to_string(),
-            current_dir.join("rman-dl.exe").to_string_lossy().to_string(),
-            // Chercher dans le répertoire d'installation de l'app
-            exe_dir.join("rman-dl.exe").to_string_lossy().to_string(),
-            // Chercher dans le dossier assets (pour les builds Tauri)
-            exe_dir.join("assets").join("rman-dl.exe").to_string_lossy().to_string(),
-            // Chercher dans le dossier parent assets (pour les builds de développement)
-            exe_dir.parent().unwrap().join("assets").join("rman-dl.exe").to_string_lossy().to_string(),
-            // Chercher dans le dossier resources de Tauri (pour les builds finaux)
-            exe_dir.join("resources").join("rman-dl.exe").to_string_lossy().to_string(),
-            // Chercher dans le dossier assets local (pour les builds de développement)
-            current_dir.join("assets").join("rman-dl.exe").to_string_lossy().to_string(),
-        ];
-
-        let mut found_path = None;
-        for path in &possible_paths {
-            println!("🔍 Vérification: {}", path);
-            if Path::new(path).exists() {
-                println!("✅ Trouvé: {}", path);
-                found_path = Some(path.clone());
-                break;
-            }
-        }
+}
 
-        match found_path {
-            Some(path) => path,
-            None => {
-                println!("❌ Erreur: rman-dl.exe non trouvé dans les chemins suivants:");
-                for path in &possible_paths {
-                    println!("  - {}", path);
-                }
-                return;
-            }
-        }
+#[tauri::command]
+pub async fn get_settings(store: State<'_, SettingsStore>) -> Result<AppSettings, String> {
+    Ok(store.get().await)
+}
+
+#[tauri::command]
+pub async fn save_settings(settings: AppSettings, store: State<'_, SettingsStore>) -> Result<(), String> {
+    store.save(settings).await?;
+    println!("💾 Paramètres enregistrés dans {}", store.path().display());
+    Ok(())
+}
+
+// Vérifier les fichiers d'une tâche par rapport à son manifest
+#[tauri::command]
+pub async fn verify_download(
+    task_id: String,
+    language: Option<String>,
+    content: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<VerifyReport, String> {
+    // Par défaut, la langue et le filtre du téléchargement lui-même
+    let (output_path, task_language, task_content) = {
+        let downloads = state.downloads.lock().await;
+        let task = downloads.get(&task_id).ok_or("Tâche introuvable")?;
+        let output_path = task.output_path.clone().ok_or("Aucun dossier de sortie pour cette tâche")?;
+        (output_path, task.language.clone(), task.content_filter.clone())
     };
 
-    // Vérifier que l'exécutable existe
-    println!("🔍 Chemin vers rman-dl.exe: {}", exe_path);
-    if !Path::new(&exe_path).exists() {
-        println!("❌ Erreur: rman-dl.exe non trouvé dans {}", exe_path);
-        return;
-    }
-    println!("✅ rman-dl.exe trouvé !");
-    
-    // 4. Créer le dossier de sortie pour les fichiers téléchargés
-    let current_dir = std::env::current_dir().unwrap();
-    let project_root = if cfg!(debug_assertions) {
-        current_dir.parent().unwrap().parent().unwrap()
-    } else {
-        &current_dir
+    let manifest_bytes = std::fs::read(manifest_file_path(&task_id))
+        .map_err(|e| format!("Manifest local introuvable: {}", e))?;
+    let manifest = Manifest::parse(&manifest_bytes)?;
+    let options = DownloadOptions {
+        language: language
+            .or(Some(task_language))
+            .filter(|l| !l.trim().is_empty())
+            .unwrap_or_else(|| DownloadOptions::default().language),
+        content_filter: content.unwrap_or(task_content),
+        ..DownloadOptions::default()
     };
 
-    let output_dir = if let Some(custom_path) = output_path {
-        let path = std::path::PathBuf::from(custom_path);
-        if path.is_relative() && cfg!(debug_assertions) {
-            // En mode debug, résoudre le chemin relatif depuis le répertoire du projet
-            project_root.join(path)
-        } else {
-            path
+    // Une tâche terminée ou en échec passe par la vérification, et en ressort terminée ou en échec selon le résultat
+    let tracked = {
+        let mut downloads = state.downloads.lock().await;
+        match downloads.get_mut(&task_id) {
+            Some(task) if task.status.is_finished() => task.transition(TaskStatus::Verifying, None).is_ok(),
+            _ => false,
         }
-    } else if cfg!(debug_assertions) {
-        project_root.join("downloads").join("files")
-    } else {
-        current_dir.join("downloads").join("files")
     };
 
-    // Créer le dossier de sortie s'il n'existe pas
-    if !output_dir.exists() {
-        std::fs::create_dir_all(&output_dir).unwrap_or_else(|e| {
-            println!("❌ Erreur lors de la création du dossier de sortie: {}", e);
-            return;
-        });
+    println!("🔍 Vérification de {} dans {}", task_id, output_path);
+    let report = downloader::verify_files(&manifest, &PathBuf::from(output_path), &options);
+    if tracked {
+        let (status, error) = match &report {
+            Ok(report) if report.is_ok() => (TaskStatus::Completed, None),
+            Ok(report) => (
+                TaskStatus::Failed,
+                Some(format!("{} fichier(s) manquant(s), {} corrompu(s)", report.missing.len(), report.corrupted.len())),
+            ),
+            Err(e) => (TaskStatus::Failed, Some(e.clone())),
+        };
+        if let Some(task) = state.downloads.lock().await.get_mut(&task_id) {
+            if task.transition(status, error.clone()).is_ok() {
+                task.error = error;
+            }
+        }
     }
+    report
+}
 
-    println!("📁 Dossier de sortie: {}", output_dir.display());
-    println!("📁 Dossier de sortie existe: {}", output_dir.exists());
-    println!("📁 Dossier de sortie est un répertoire: {}", output_dir.is_dir());
-
-    // Vérifier que le manifest existe avant de continuer
-    println!("🔍 Vérification du manifest: {}", manifest_path.display());
-    println!("📄 Manifest existe: {}", manifest_path.exists());
-    if let Ok(metadata) = std::fs::metadata(&manifest_path) {
-        println!("📄 Taille du manifest: {} bytes", metadata.len());
+// Exporter le dossier d'un téléchargement terminé en archive, suivi comme une tâche
+#[tauri::command]
+pub async fn export_patch_archive(
+    task_id: String,
+    format: ArchiveFormat,
+    destination: Option<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let source = state
+        .downloads
+        .lock()
+        .await
+        .get(&task_id)
+        .cloned()
+        .ok_or_else(|| format!("Tâche {} introuvable", task_id))?;
+    if source.status != TaskStatus::Completed {
+        return Err(format!("Le téléchargement {} n'est pas terminé", task_id));
     }
+    let output_path = source.output_path.ok_or("Aucun dossier de sortie pour cette tâche")?;
 
-    // 5. Construire la commande rman-dl avec le fichier manifest local
-    let mut cmd = TokioCommand::new(&exe_path);
+    // Par défaut l'archive est placée à côté du dossier du patch
+    let destination = destination
+        .filter(|p| !p.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(format!("{}.{}", output_path.trim_end_matches(['/', '\\']), format.extension())));
 
-    // Note: On ne change pas le répertoire de travail car on passe le chemin complet en argument
+    let export_id = tasks::new_task_id("export");
+    let task = DownloadTask::new(
+        export_id.clone(),
+        source.manifest.clone(),
+        source.version.clone(),
+        Some(destination.to_string_lossy().to_string()),
+        source.realm.clone(),
+    );
+    let job = ExportJob {
+        source_task_id: task_id,
+        format,
+        destination,
+    };
+    state.downloads.lock().await.insert(export_id.clone(), task);
+    state.export_jobs.lock().await.insert(export_id.clone(), job.clone());
 
-    // Arguments selon la syntaxe correcte : rman-dl [options] manifest output
-    // Options d'abord - commencer simple pour diagnostiquer
-    cmd.arg("--no-progress"); // Désactiver la barre de progression
-    cmd.arg("--no-verify");   // Ne pas vérifier les fichiers existants
+    tokio::spawn(execute_export(app, export_id.clone(), job));
+    Ok(export_id)
+}
 
-    // Test avec filtre de langue simple
-    cmd.arg("-l");
-    cmd.arg("none"); // Commencer avec "none" seulement pour les fichiers internationaux
-    println!("🌍 Filtre de langue: none (fichiers internationaux seulement)");
+// Ouvre un WAD du dossier d'une tâche, avec sa table de sous-chunks si elle existe
+async fn open_task_wad(state: &AppState, task_id: &str, wad_path: &str) -> Result<(Wad, PathBuf), String> {
+    let output_path = state
+        .downloads
+        .lock()
+        .await
+        .get(task_id)
+        .and_then(|task| task.output_path.clone())
+        .ok_or_else(|| format!("Aucun dossier de sortie pour la tâche {}", task_id))?;
+    let output_dir = PathBuf::from(output_path);
+    let wad_path = wad_path.to_string();
+    tokio::task::spawn_blocking(move || {
+        let mut wad = Wad::open(&downloader::local_path(&output_dir, &wad_path)?)?;
+        wad.load_subchunk_toc(&wad_path)?;
+        Ok((wad, output_dir))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    cmd.arg("--cdn");
-    cmd.arg("http://lol.secure.dyn.riotcdn.net/channels/public");
+#[tauri::command]
+pub async fn list_wad_entries(task_id: String, wad_path: String, state: State<'_, AppState>) -> Result<Vec<NamedWadEntry>, String> {
+    let (wad, _) = open_task_wad(&state, &task_id, &wad_path).await?;
+    println!("📚 {}: WAD v{}.{}, {} entrées", wad_path, wad.version.0, wad.version.1, wad.entries.len());
+    let table = state.hash_table.lock().await.clone();
+    tokio::task::spawn_blocking(move || wad.named_entries(&table))
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    // Limiter le téléchargement pour les tests (optionnel)
-    cmd.arg("--cdn-workers");
-    cmd.arg("4"); // Utiliser seulement 4 workers pour éviter la surcharge
+#[tauri::command]
+pub async fn extract_wad_entries(
+    task_id: String,
+    wad_path: String,
+    hashes: Option<Vec<String>>,
+    output_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let (wad, task_dir) = open_task_wad(&state, &task_id, &wad_path).await?;
+    let hashes = hashes
+        .unwrap_or_default()
+        .iter()
+        .map(|h| wad::parse_hash(h))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Par défaut : <dossier de la tâche>/_extracted/<nom du WAD>
+    let output_dir = output_path.filter(|p| !p.trim().is_empty()).map(PathBuf::from).unwrap_or_else(|| {
+        let name = Path::new(&wad_path).file_name().map(|n| n.to_os_string()).unwrap_or_default();
+        task_dir.join("_extracted").join(name)
+    });
 
-    // Filtre de contenu si spécifié
-    if !content.trim().is_empty() && content.trim() != "" {
-        cmd.arg("-p");
-        cmd.arg(&content);
-        println!("📋 Filtre de contenu appliqué: {}", content);
-    } else {
-        println!("📋 Aucun filtre de contenu - téléchargement de tous les fichiers");
-    }
-
-    // Ensuite le manifest
-    cmd.arg(manifest_path.to_string_lossy().to_string());
-
-    // Enfin le dossier de sortie (output directory)
-    cmd.arg(output_dir.to_string_lossy().to_string());
-
-    // Rediriger stdout et stderr vers des fichiers pour le débogage
-    let stdout_file = output_dir.join("rman-dl.stdout.log");
-    let stderr_file = output_dir.join("rman-dl.stderr.log");
-    cmd.stdout(std::process::Stdio::piped());
-    cmd.stderr(std::process::Stdio::piped());
-
-    println!("🔧 Commande exécutée: {:?}", cmd);
-    println!("🔧 Arguments de la commande:");
-    println!("  - Exécutable: {}", exe_path);
-    println!("  - Options:");
-    println!("    - Langue: none|windows|{}", language);
-    println!("    - CDN: http://lol.secure.dyn.riotcdn.net/channels/public");
-    println!("    - Filtre contenu: {}", if content.trim().is_empty() { "aucun" } else { &content });
-    println!("  - Manifest: {}", manifest_path.display());
-    println!("  - Dossier de sortie: {}", output_dir.display());
-
-    // Tester si rman-dl.exe peut être exécuté avec --help
-    println!("🧪 Test de rman-dl.exe...");
-    match TokioCommand::new(&exe_path)
-        .arg("--help")
-        .output()
-        .await {
-        Ok(output) => {
-            println!("✅ rman-dl.exe répond:");
-            if let Ok(stdout) = String::from_utf8(output.stdout) {
-                println!("📄 Sortie: {}", stdout.lines().take(5).collect::<Vec<&str>>().join("\n"));
+    let table = state.hash_table.lock().await.clone();
+    let written = tokio::task::spawn_blocking(move || wad::extract_entries(&wad, &hashes, &output_dir, &table))
+        .await
+        .map_err(|e| e.to_string())??;
+    println!("📤 {} entrées extraites de {}", written.len(), wad_path);
+    Ok(written.iter().map(|p| p.to_string_lossy().to_string()).collect())
+}
+
+// Convertit un fichier .bin extrait en JSON, écrit aussi sur disque si un chemin de sortie est donné
+#[tauri::command]
+pub async fn convert_bin_to_json(
+    input_path: String,
+    output_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let table = state.hash_table.lock().await.clone();
+    tokio::task::spawn_blocking(move || {
+        let data = std::fs::read(&input_path).map_err(|e| format!("Impossible de lire {}: {}", input_path, e))?;
+        let json = PropBin::parse(&data)?.to_json(&table);
+        if let Some(output_path) = output_path.filter(|p| !p.trim().is_empty()) {
+            let content = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
+            std::fs::write(&output_path, content).map_err(|e| format!("Impossible d'écrire {}: {}", output_path, e))?;
+            println!("📝 {} converti vers {}", input_path, output_path);
+        }
+        Ok(json)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Convertit directement un .bin contenu dans un WAD d'une tâche (chemin ou hash de l'entrée)
+#[tauri::command]
+pub async fn convert_wad_bin_to_json(
+    task_id: String,
+    wad_path: String,
+    entry: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let (wad, _) = open_task_wad(&state, &task_id, &wad_path).await?;
+    let hash = wad::entry_hash(&entry);
+    let wad_entry = wad
+        .entry(hash)
+        .cloned()
+        .ok_or_else(|| format!("Entrée {} absente de {}", entry, wad_path))?;
+    let table = state.hash_table.lock().await.clone();
+    tokio::task::spawn_blocking(move || Ok(PropBin::parse(&wad.read(&wad_entry)?)?.to_json(&table)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// Convertit une texture .tex/.dds en PNG, par défaut à côté du fichier d'origine
+#[tauri::command]
+pub async fn convert_texture_to_png(input_path: String, output_path: Option<String>, mip: Option<u32>) -> Result<String, String> {
+    let input = PathBuf::from(&input_path);
+    let output = output_path
+        .filter(|p| !p.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| input.with_extension("png"));
+    let written = output.clone();
+    tokio::task::spawn_blocking(move || texture::convert_file(&input, &output, mip.unwrap_or(0)))
+        .await
+        .map_err(|e| e.to_string())??;
+    println!("🖼️ {} converti en PNG", input_path);
+    Ok(written.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn convert_wad_texture_to_png(
+    task_id: String,
+    wad_path: String,
+    entry: String,
+    output_path: Option<String>,
+    mip: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let (wad, task_dir) = open_task_wad(&state, &task_id, &wad_path).await?;
+    let hash = wad::entry_hash(&entry);
+    let wad_entry = wad
+        .entry(hash)
+        .cloned()
+        .ok_or_else(|| format!("Entrée {} absente de {}", entry, wad_path))?;
+    // Par défaut : <dossier de la tâche>/_png/<chemin de l'entrée>.png
+    let output = match output_path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => match state.hash_table.lock().await.get(hash) {
+            Some(name) => downloader::local_path(&task_dir.join("_png"), name)?.with_extension("png"),
+            None => task_dir.join("_png").join(format!("{:016x}.png", hash)),
+        },
+    };
+
+    let written = output.clone();
+    tokio::task::spawn_blocking(move || -> Result<(), String> {
+        let png = texture::texture_to_png(&wad.read(&wad_entry)?, mip.unwrap_or(0))?;
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&output, png).map_err(|e| format!("Impossible d'écrire {}: {}", output.display(), e))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    println!("🖼️ {} ({}) converti en PNG", entry, wad_path);
+    Ok(written.to_string_lossy().to_string())
+}
+
+// Conversion par lot de toutes les textures d'un sous-dossier de la tâche (fichiers déjà extraits)
+#[tauri::command]
+pub async fn convert_task_textures(
+    task_id: String,
+    subtree: Option<String>,
+    output_path: Option<String>,
+    mip: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<TextureBatchReport, String> {
+    let (_, task_dir) = task_output(&state, &task_id).await?;
+    let subtree = subtree.filter(|s| !s.trim().is_empty());
+    let source = match &subtree {
+        Some(subtree) => downloader::local_path(&task_dir, subtree)?,
+        None => task_dir.clone(),
+    };
+    // Par défaut : <dossier de la tâche>/_png/<sous-dossier>
+    let output_dir = match output_path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => match &subtree {
+            Some(subtree) => downloader::local_path(&task_dir.join("_png"), subtree)?,
+            None => task_dir.join("_png"),
+        },
+    };
+
+    let report = tokio::task::spawn_blocking(move || texture::convert_tree(&source, &output_dir, mip.unwrap_or(0)))
+        .await
+        .map_err(|e| e.to_string())??;
+    println!("🖼️ {} textures converties, {} en échec", report.converted.len(), report.failed.len());
+    Ok(report)
+}
+
+// Lit une banque audio (.wpk/.bnk) stockée dans un WAD de la tâche
+async fn read_task_audio_bank(
+    state: &State<'_, AppState>,
+    task_id: &str,
+    wad_path: &str,
+    entry: &str,
+) -> Result<(Vec<u8>, AudioBank, PathBuf), String> {
+    let (wad, task_dir) = open_task_wad(state, task_id, wad_path).await?;
+    let wad_entry = wad
+        .entry(wad::entry_hash(entry))
+        .cloned()
+        .ok_or_else(|| format!("Entrée {} absente de {}", entry, wad_path))?;
+    let data = wad.read(&wad_entry)?;
+    let table = state.hash_table.lock().await.clone();
+    let bank = AudioBank::parse(&data, &table)?;
+    Ok((data, bank, task_dir))
+}
+
+#[tauri::command]
+pub async fn list_audio_streams(
+    task_id: String,
+    wad_path: String,
+    entry: String,
+    state: State<'_, AppState>,
+) -> Result<AudioBank, String> {
+    let (_, bank, _) = read_task_audio_bank(&state, &task_id, &wad_path, &entry).await?;
+    println!("🔊 {}: {} flux audio", entry, bank.streams.len());
+    Ok(bank)
+}
+
+// Extrait les flux WEM d'une banque ; avec convert, les flux PCM sont écrits en WAV
+#[tauri::command]
+pub async fn extract_audio_streams(
+    task_id: String,
+    wad_path: String,
+    entry: String,
+    ids: Option<Vec<u32>>,
+    output_path: Option<String>,
+    convert: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let (data, bank, task_dir) = read_task_audio_bank(&state, &task_id, &wad_path, &entry).await?;
+    // Par défaut : <dossier de la tâche>/_audio/<nom de la banque>
+    let output_dir = output_path.filter(|p| !p.trim().is_empty()).map(PathBuf::from).unwrap_or_else(|| {
+        let name = Path::new(&entry).file_stem().map(|n| n.to_os_string()).unwrap_or_default();
+        task_dir.join("_audio").join(name)
+    });
+
+    let ids = ids.unwrap_or_default();
+    let written = tokio::task::spawn_blocking(move || {
+        audio::extract_streams(&data, &bank, &ids, &output_dir, convert.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    println!("🔊 {} flux extraits de {}", written.len(), entry);
+    Ok(written.iter().map(|p| p.to_string_lossy().to_string()).collect())
+}
+
+// Exporte un maillage .skn d'un WAD en .glb. Sans précision, le squelette (.skl du même nom)
+// et la texture "_tx_cm" du même dossier sont recherchés dans le WAD. Les animations .anm ne sont pas exportées.
+#[tauri::command]
+pub async fn export_model_gltf(
+    task_id: String,
+    wad_path: String,
+    mesh_entry: String,
+    skeleton_entry: Option<String>,
+    texture_entry: Option<String>,
+    output_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let (wad, task_dir) = open_task_wad(&state, &task_id, &wad_path).await?;
+    let table = state.hash_table.lock().await.clone();
+    let mesh_hash = wad::entry_hash(&mesh_entry);
+    let mesh_name = table.get(mesh_hash).unwrap_or(&mesh_entry).to_string();
+
+    let skeleton_entry = skeleton_entry
+        .filter(|s| !s.trim().is_empty())
+        .or_else(|| model::skeleton_path(&mesh_name).filter(|path| wad.entry(wad::path_hash(path)).is_some()));
+    let texture_entry = texture_entry.filter(|t| !t.trim().is_empty()).or_else(|| {
+        let names = wad.entries.iter().filter_map(|e| table.get(e.path_hash));
+        model::matching_texture(&mesh_name, names).map(str::to_string)
+    });
+
+    // Par défaut : <dossier de la tâche>/_gltf/<chemin du maillage>.glb
+    let output = match output_path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => match table.get(mesh_hash) {
+            Some(name) => downloader::local_path(&task_dir.join("_gltf"), name)?.with_extension("glb"),
+            None => task_dir.join("_gltf").join(format!("{:016x}.glb", mesh_hash)),
+        },
+    };
+
+    let written = output.clone();
+    let read_entry = move |name: &str| -> Result<Vec<u8>, String> {
+        let entry = wad
+            .entry(wad::entry_hash(name))
+            .ok_or_else(|| format!("Entrée {} absente de {}", name, wad_path))?;
+        wad.read(entry)
+    };
+    tokio::task::spawn_blocking(move || -> Result<(), String> {
+        let mesh = SkinnedMesh::parse(&read_entry(&mesh_entry)?)?;
+        let skeleton = match &skeleton_entry {
+            Some(entry) => Some(Skeleton::parse(&read_entry(entry)?)?),
+            None => None,
+        };
+        let texture = match &texture_entry {
+            Some(entry) => Some(texture::texture_to_png(&read_entry(entry)?, 0)?),
+            None => None,
+        };
+        let glb = model::to_glb(&mesh, skeleton.as_ref(), texture.as_deref())?;
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&output, glb).map_err(|e| format!("Impossible d'écrire {}: {}", output.display(), e))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    println!("🧊 {} exporté en glTF", mesh_name);
+    Ok(written.to_string_lossy().to_string())
+}
+
+// Emplacement de la table de hash téléchargée
+fn hash_cache_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| e.to_string())?
+        .join(hashes::HASH_CACHE_FILE_NAME))
+}
+
+// Charge au démarrage la dernière table de hash téléchargée
+pub async fn load_cached_hash_table(app: AppHandle) {
+    let Ok(path) = hash_cache_path(&app) else {
+        return;
+    };
+    if !path.exists() {
+        return;
+    }
+    match tokio::task::spawn_blocking(move || HashTable::load_file(&path)).await {
+        Ok(Ok(table)) => {
+            println!("🔑 {} hash chargés depuis le cache", table.len());
+            *app.state::<AppState>().hash_table.lock().await = Arc::new(table);
+        }
+        Ok(Err(e)) => println!("⚠️ {}", e),
+        Err(e) => println!("⚠️ {}", e),
+    }
+}
+
+// Emplacement des versions du jeu détectées
+fn game_version_cache_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(game_version::VERSION_CACHE_FILE_NAME))
+}
+
+// Charge au démarrage les versions détectées lors des sessions précédentes
+pub async fn load_cached_game_versions(app: AppHandle) {
+    let Ok(path) = game_version_cache_path(&app) else {
+        return;
+    };
+    match tokio::task::spawn_blocking(move || game_version::load_versions(&path)).await {
+        Ok(versions) => {
+            if !versions.is_empty() {
+                println!("🏷️ {} versions du jeu chargées depuis le cache", versions.len());
             }
-            if let Ok(stderr) = String::from_utf8(output.stderr) {
-                if !stderr.is_empty() {
-                    println!("⚠️ Erreurs: {}", stderr.lines().take(3).collect::<Vec<&str>>().join("\n"));
+            *app.state::<AppState>().game_versions.lock().await = versions;
+        }
+        Err(e) => println!("⚠️ {}", e),
+    }
+}
+
+// Lit la version du jeu dans le dossier d'une tâche et la reporte sur la tâche et le catalogue
+async fn record_game_version(app: &AppHandle, task_id: &str, output_dir: PathBuf) -> Result<String, String> {
+    let build = tokio::task::spawn_blocking(move || game_version::detect_game_version(&output_dir))
+        .await
+        .map_err(|e| e.to_string())??;
+    println!("🏷️ Version du jeu détectée pour {} : {}", task_id, build);
+
+    let state = app.state::<AppState>();
+    let manifest_url = {
+        let mut downloads = state.downloads.lock().await;
+        let task = downloads.get_mut(task_id).ok_or("Tâche non trouvée")?;
+        task.version = build.clone();
+        task.manifest.clone()
+    };
+    let calendar = state.patch_calendar.lock().await.clone();
+    for manifest in state.manifests.lock().await.iter_mut().filter(|m| m.manifest == manifest_url) {
+        apply_game_version(manifest, &build, &calendar);
+    }
+    if let Some(history) = state.history.lock().await.as_ref() {
+        history.set_build_version(&manifest_url, &build, calendar.from_build(&build))?;
+    }
+    let versions = {
+        let mut versions = state.game_versions.lock().await;
+        versions.insert(manifest_url, build.clone());
+        versions.clone()
+    };
+    let path = game_version_cache_path(app)?;
+    tokio::task::spawn_blocking(move || game_version::save_versions(&path, &versions))
+        .await
+        .map_err(|e| e.to_string())??;
+    Ok(build)
+}
+
+// Détection manuelle, pour les patchs téléchargés avant la prise en charge
+#[tauri::command]
+pub async fn detect_game_version(task_id: String, app: AppHandle, state: State<'_, AppState>) -> Result<String, String> {
+    let output_dir = {
+        let downloads = state.downloads.lock().await;
+        let task = downloads.get(&task_id).ok_or("Tâche non trouvée")?;
+        task.output_path.clone().ok_or("Dossier de la tâche inconnu")?
+    };
+    record_game_version(&app, &task_id, PathBuf::from(output_dir)).await
+}
+
+// Emplacement de l'état de la surveillance (manifests vus, téléchargements lancés)
+fn watch_state_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(watcher::WATCH_STATE_FILE_NAME))
+}
+
+// Un passage de la surveillance : suivi des tâches lancées, nouveaux patchs, puis rétention
+async fn run_watch_cycle(app: &AppHandle) -> Result<WatchReport, String> {
+    let watch = app.state::<SettingsStore>().get().await.watch;
+    let state = app.state::<AppState>();
+    let mut watch_state = state.watch_state.lock().await;
+    let mut report = WatchReport::default();
+
+    let tasks = state.downloads.lock().await.clone();
+    for download in watch_state.downloads.clone().into_iter().filter(|d| !d.completed) {
+        match tasks.get(&download.task_id) {
+            Some(task) if task.status == TaskStatus::Completed => {
+                if let Some(watched) = watch_state.downloads.iter_mut().find(|d| d.task_id == download.task_id) {
+                    watched.completed = true;
+                    watched.output_path = task.output_path.clone();
                 }
             }
+            // Annulé par l'utilisateur : on ne le relance pas
+            Some(task) if task.status == TaskStatus::Cancelled => watch_state.forget(&download.task_id),
+            // En échec, ou perdu à la fermeture de l'application
+            Some(task) if task.status == TaskStatus::Failed => watch_state.retry(&download.task_id),
+            None => watch_state.retry(&download.task_id),
+            Some(_) => {}
         }
-        Err(e) => {
-            println!("❌ Impossible de tester rman-dl.exe: {}", e);
-        }
-    }
-
-    // 6. Exécuter la commande de manière non-bloquante
-    println!("🚀 Lancement de rman-dl.exe avec le manifest...");
-
-    // Créer un fichier de log pour capturer la sortie
-    let log_file = output_dir.join("rman-dl.log");
-    println!("📝 Logs rman-dl disponibles dans: {}", log_file.display());
-
-    match cmd.spawn() {
-        Ok(mut child) => {
-            println!("✅ rman-dl.exe lancé avec PID: {:?}", child.id());
-
-            // Capturer stdout et stderr
-            let stdout_task = if let Some(stdout) = child.stdout.take() {
-                let stdout_file_clone = stdout_file.clone();
-                Some(tokio::spawn(async move {
-                    use tokio::io::AsyncReadExt;
-                    let mut reader = tokio::io::BufReader::new(stdout);
-                    let mut buffer = Vec::new();
-                    if let Err(e) = reader.read_to_end(&mut buffer).await {
-                        println!("❌ Erreur lecture stdout: {}", e);
-                    } else if let Ok(content) = String::from_utf8(buffer) {
-                        if let Err(e) = std::fs::write(&stdout_file_clone, &content) {
-                            println!("❌ Erreur sauvegarde stdout: {}", e);
-                        } else {
-                            println!("📄 Sortie stdout sauvegardée dans: {}", stdout_file_clone.display());
-                        }
-                    }
-                }))
-            } else {
-                None
-            };
+    }
 
-            let stderr_task = if let Some(stderr) = child.stderr.take() {
-                let stderr_file_clone = stderr_file.clone();
-                Some(tokio::spawn(async move {
-                    use tokio::io::AsyncReadExt;
-                    let mut reader = tokio::io::BufReader::new(stderr);
-                    let mut buffer = Vec::new();
-                    if let Err(e) = reader.read_to_end(&mut buffer).await {
-                        println!("❌ Erreur lecture stderr: {}", e);
-                    } else if let Ok(content) = String::from_utf8(buffer) {
-                        if let Err(e) = std::fs::write(&stderr_file_clone, &content) {
-                            println!("❌ Erreur sauvegarde stderr: {}", e);
-                        } else {
-                            println!("📄 Sortie stderr sauvegardée dans: {}", stderr_file_clone.display());
-                        }
-                    }
-                }))
-            } else {
-                None
-            };
+    let source = catalogue_source(app).await.ok_or("Aucun catalogue à surveiller")?;
+    let manifests = fetch_manifests(Some(source), app.clone(), app.state()).await?;
+    let entries: Vec<CatalogueEntry> = manifests
+        .iter()
+        .map(|m| CatalogueEntry {
+            product: m.product,
+            channel: m.channel,
+            realm: m.region.clone(),
+            manifest_url: m.manifest.clone(),
+            date: m.date.clone(),
+            size_bytes: m.size_bytes,
+        })
+        .collect();
+    report.checked = entries.len();
 
-            // Simuler la progression pendant que le processus tourne
-            let task_id_clone = task_id.clone();
-            tokio::spawn(async move {
-                simulate_download_progress(task_id_clone).await;
-            });
-
-            // Attendre la fin avec timeout en utilisant tokio
-            let timeout_duration = tokio::time::Duration::from_secs(1800); // 30 minutes max pour les gros téléchargements
-
-            println!("⏳ Attente de la fin de rman-dl.exe (timeout: 30 minutes)...");
-            println!("📝 Logs disponibles:");
-            println!("   - stdout: {}", stdout_file.display());
-            println!("   - stderr: {}", stderr_file.display());
-            println!("   - général: {}", log_file.display());
-
-            match tokio::time::timeout(timeout_duration, child.wait()).await {
-                Ok(result) => {
-                    // Attendre que les tâches de capture se terminent
-                    if let Some(task) = stdout_task {
-                        let _ = task.await;
-                    }
-                    if let Some(task) = stderr_task {
-                        let _ = task.await;
-                    }
+    for entry in watch_state.new_manifests(&entries, &watch) {
+        println!("👀 Nouveau patch {} ({}) : {}", entry.realm, entry.date, entry.manifest_url);
+        let version = manifests
+            .iter()
+            .find(|m| m.manifest == entry.manifest_url)
+            .map(|m| m.version.clone())
+            .unwrap_or_default();
+        notify_webhooks(app, WebhookEvent::new_manifest(&entry.realm, &entry.manifest_url, &entry.date, &version)).await;
+        let started = start_download(
+            entry.manifest_url.clone(),
+            watch.language.clone(),
+            watch.content_filter.clone(),
+            None,
+            None,
+            app.clone(),
+            app.state(),
+        )
+        .await;
+        match started {
+            Ok(task_id) => {
+                let output_path = state.downloads.lock().await.get(&task_id).and_then(|t| t.output_path.clone());
+                watch_state.downloads.push(WatchedDownload {
+                    manifest: entry.manifest_url.clone(),
+                    realm: entry.realm.clone(),
+                    date: entry.date.clone(),
+                    task_id: task_id.clone(),
+                    output_path,
+                    completed: false,
+                });
+                report.enqueued.push(task_id);
+            }
+            Err(e) => {
+                println!("❌ {} : {}", entry.realm, e);
+                watch_state.seen.remove(&entry.manifest_url);
+                report.errors.push(format!("{}: {}", entry.realm, e));
+            }
+        }
+    }
 
-                    match result {
-                        Ok(status) => {
-                            println!("🏁 rman-dl.exe terminé - Code de sortie: {}", status.code().unwrap_or(-1));
-                            if status.success() {
-                                println!("✅ rman-dl.exe terminé avec succès");
-                                update_download_status(task_id.clone(), "completed".to_string(), 100.0, "Terminé avec succès".to_string()).await;
-                            } else {
-                                println!("❌ rman-dl.exe terminé avec erreur: {:?}", status);
-                                update_download_status(task_id.clone(), "error".to_string(), 0.0, format!("Erreur rman-dl (code {}): {:?}", status.code().unwrap_or(-1), status)).await;
-                            }
-                        }
-                        Err(e) => {
-                            println!("❌ Erreur lors de l'attente de rman-dl.exe: {}", e);
-                            update_download_status(task_id.clone(), "error".to_string(), 0.0, format!("Erreur système: {}", e)).await;
-                        }
+    // Seuls les fichiers notés dans le journal de la tâche sont supprimés : un modèle comme `{root}/{realm}`
+    // fait partager leur dossier à plusieurs patchs, et le dossier racine n'appartient à aucun
+    let tasks = state.downloads.lock().await.clone();
+    for download in watch_state.expired(watch.keep_latest) {
+        if let Some(path) = &download.output_path {
+            let kept = watch_state
+                .downloads
+                .iter()
+                .filter(|d| d.task_id != download.task_id)
+                .filter_map(|d| d.output_path.as_deref().map(|p| (d.task_id.as_str(), Path::new(p))))
+                .chain(
+                    tasks
+                        .values()
+                        .filter(|t| t.id != download.task_id)
+                        .filter_map(|t| t.output_path.as_deref().map(|p| (t.id.as_str(), Path::new(p)))),
+                );
+            let shared = output_template::find_collision(kept, Path::new(path)).map(str::to_string);
+            let (task_id, dir) = (download.task_id.clone(), PathBuf::from(path));
+            let (recorded, shared) = tokio::task::spawn_blocking(move || {
+                let recorded = journal::read_journal(&task_journal_path(&task_id)).map(|paths| !paths.is_empty());
+                (recorded, shared.or_else(|| journal_collision(&task_id, &dir)))
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+            let reason = match (recorded, shared) {
+                (Err(e), _) => Some(e),
+                (_, Some(other)) => Some(format!("dossier partagé avec la tâche {}", other)),
+                // Tâche retirée de la liste : son journal a disparu, on ne sait plus quels fichiers lui appartiennent
+                (Ok(false), None) => Some("journal de la tâche absent".to_string()),
+                (Ok(true), None) => None,
+            };
+            if let Some(reason) = reason {
+                println!("⚠️ {} conservé : {}", path, reason);
+                report.kept.push(format!("{} ({})", path, reason));
+                watch_state.forget(&download.task_id);
+                continue;
+            }
+            match delete_task_files(&download.task_id).await {
+                Ok(cleanup) => {
+                    println!("🗑️ Ancien patch supprimé : {} ({} fichier(s))", path, cleanup.removed_files);
+                    for kept in cleanup.kept {
+                        println!("⚠️ Conservé (non vide ou verrouillé) : {}", kept);
                     }
                 }
-                Err(_) => {
-                    // Attendre que les tâches de capture se terminent avant de tuer
-                    if let Some(task) = stdout_task {
-                        let _ = task.await;
-                    }
-                    if let Some(task) = stderr_task {
-                        let _ = task.await;
+                Err(e) => {
+                    report.errors.push(format!("Impossible de supprimer {}: {}", path, e));
+                    continue;
+                }
+            }
+            report.removed.push(path.clone());
+        }
+        watch_state.forget(&download.task_id);
+    }
+
+    let path = watch_state_path(app)?;
+    let snapshot = watch_state.clone();
+    tokio::task::spawn_blocking(move || snapshot.save(&path))
+        .await
+        .map_err(|e| e.to_string())??;
+    Ok(report)
+}
+
+// Boucle de fond : un passage dès que l'intervalle configuré est écoulé
+pub async fn run_watcher(app: AppHandle) {
+    if let Ok(path) = watch_state_path(&app) {
+        if let Ok(state) = tokio::task::spawn_blocking(move || WatchState::load(&path)).await {
+            *app.state::<AppState>().watch_state.lock().await = state;
+        }
+    }
+    let mut last_run: Option<tokio::time::Instant> = None;
+    let mut ticker = tokio::time::interval(tokio::time::Duration::from_secs(60));
+    loop {
+        ticker.tick().await;
+        let watch = app.state::<SettingsStore>().get().await.watch;
+        if !watch.enabled {
+            continue;
+        }
+        let interval = tokio::time::Duration::from_secs(watch.interval_minutes * 60);
+        if last_run.is_some_and(|at| at.elapsed() < interval) {
+            continue;
+        }
+        last_run = Some(tokio::time::Instant::now());
+        match run_watch_cycle(&app).await {
+            Ok(report) => println!(
+                "👀 Surveillance : {} manifests, {} lancés, {} supprimés",
+                report.checked,
+                report.enqueued.len(),
+                report.removed.len()
+            ),
+            Err(e) => println!("⚠️ Surveillance : {}", e),
+        }
+    }
+}
+
+// Passage immédiat, sans attendre l'intervalle
+#[tauri::command]
+pub async fn run_watch_now(app: AppHandle) -> Result<WatchReport, String> {
+    run_watch_cycle(&app).await
+}
+
+// Journal des envois de webhooks
+fn webhook_log_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join(webhooks::WEBHOOK_LOG_FILE_NAME))
+}
+
+async fn deliver_and_log(app: &AppHandle, target: &WebhookTarget, event: &WebhookEvent) -> DeliveryRecord {
+    let record = webhooks::deliver(&reqwest::Client::new(), target, event, &DeliveryOptions::default()).await;
+    if record.success {
+        println!("📨 Webhook envoyé à {} ({} tentative(s))", record.url, record.attempts);
+    } else {
+        println!("⚠️ Webhook {} en échec : {:?}", record.url, record.error);
+    }
+    if let Ok(path) = webhook_log_path(app) {
+        let logged = record.clone();
+        let written = tokio::task::spawn_blocking(move || webhooks::append_log(&path, &logged)).await;
+        if let Ok(Err(e)) = written {
+            println!("⚠️ {}", e);
+        }
+    }
+    record
+}
+
+// Envoie l'événement aux webhooks configurés, en tâche de fond
+async fn notify_webhooks(app: &AppHandle, event: WebhookEvent) {
+    let targets: Vec<WebhookTarget> = app
+        .state::<SettingsStore>()
+        .get()
+        .await
+        .webhooks
+        .into_iter()
+        .filter(|target| target.accepts(event.kind))
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    tokio::spawn(async move {
+        for target in &targets {
+            deliver_and_log(&app, target, &event).await;
+        }
+    });
+}
+
+// Envoie un événement de test à une cible, même si elle n'est pas encore enregistrée
+#[tauri::command]
+pub async fn test_webhook(target: WebhookTarget, app: AppHandle) -> Result<DeliveryRecord, String> {
+    target.validate()?;
+    Ok(deliver_and_log(&app, &target, &WebhookEvent::test()).await)
+}
+
+#[tauri::command]
+pub async fn get_webhook_log(limit: Option<usize>, app: AppHandle) -> Result<Vec<DeliveryRecord>, String> {
+    let path = webhook_log_path(&app)?;
+    let limit = limit.unwrap_or(100);
+    tokio::task::spawn_blocking(move || webhooks::read_log(&path, limit))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Ouvre la base d'historique des manifests dans le dossier de données
+pub async fn open_manifest_history(app: AppHandle) {
+    let path = match app.path().app_data_dir() {
+        Ok(dir) => dir.join(history::HISTORY_DB_FILE_NAME),
+        Err(e) => {
+            println!("⚠️ {}", e);
+            return;
+        }
+    };
+    match tokio::task::spawn_blocking(move || ManifestHistory::open(&path)).await {
+        Ok(Ok(history)) => {
+            if let Ok(count) = history.len() {
+                println!("🗄️ Historique : {} manifests connus", count);
+            }
+            *app.state::<AppState>().history.lock().await = Some(history);
+        }
+        Ok(Err(e)) => println!("⚠️ {}", e),
+        Err(e) => println!("⚠️ {}", e),
+    }
+}
+
+#[tauri::command]
+pub async fn search_manifest_history(query: HistoryQuery, state: State<'_, AppState>) -> Result<Vec<ManifestRecord>, String> {
+    let history = state.history.lock().await;
+    history.as_ref().ok_or("Historique des manifests indisponible")?.search(&query)
+}
+
+// Manifest en jeu sur un serveur à une date donnée
+#[tauri::command]
+pub async fn manifest_live_at(realm: String, date: String, state: State<'_, AppState>) -> Result<Option<ManifestRecord>, String> {
+    let history = state.history.lock().await;
+    history.as_ref().ok_or("Historique des manifests indisponible")?.live_at(&realm, &date)
+}
+
+// Met à jour la table depuis une URL ou un fichier local (par défaut les sources des paramètres)
+#[tauri::command]
+pub async fn update_hash_table(source: Option<String>, app: AppHandle, state: State<'_, AppState>) -> Result<usize, String> {
+    let sources = match source.filter(|s| !s.trim().is_empty()) {
+        Some(source) => vec![source],
+        None => app.state::<SettingsStore>().get().await.hash_list_urls,
+    };
+    if sources.is_empty() {
+        return Err("Aucune source de hash configurée".to_string());
+    }
+
+    let mut text = String::new();
+    for source in &sources {
+        println!("🔑 Chargement des hash depuis {}...", source);
+        text.push_str(&hashes::fetch_hash_list(source).await?);
+        text.push('\n');
+    }
+
+    let cache_path = hash_cache_path(&app)?;
+    let table = tokio::task::spawn_blocking(move || -> Result<HashTable, String> {
+        if let Some(parent) = cache_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+        }
+        let temp_path = cache_path.with_extension("txt.tmp");
+        std::fs::write(&temp_path, &text)
+            .and_then(|_| std::fs::rename(&temp_path, &cache_path))
+            .map_err(|e| format!("Impossible d'enregistrer {}: {}", cache_path.display(), e))?;
+        Ok(HashTable::parse(&text))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let count = table.len();
+    println!("✅ {} hash disponibles", count);
+    *state.hash_table.lock().await = Arc::new(table);
+    Ok(count)
+}
+
+// Hash sans nom connu dans les WAD d'un téléchargement
+#[tauri::command]
+pub async fn unknown_wad_hashes(task_id: String, state: State<'_, AppState>) -> Result<UnknownHashReport, String> {
+    let output_path = state
+        .downloads
+        .lock()
+        .await
+        .get(&task_id)
+        .and_then(|task| task.output_path.clone())
+        .ok_or_else(|| format!("Aucun dossier de sortie pour la tâche {}", task_id))?;
+    let table = state.hash_table.lock().await.clone();
+    let report = tokio::task::spawn_blocking(move || hashes::unknown_hashes(Path::new(&output_path), &table))
+        .await
+        .map_err(|e| e.to_string())??;
+    println!("🔑 {} hash inconnus sur {} entrées", report.unknown, report.entries);
+    Ok(report)
+}
+
+// Version et dossier de sortie d'une tâche téléchargée
+async fn task_output(state: &State<'_, AppState>, task_id: &str) -> Result<(String, PathBuf), String> {
+    let downloads = state.downloads.lock().await;
+    let task = downloads
+        .get(task_id)
+        .ok_or_else(|| format!("Tâche {} introuvable", task_id))?;
+    let output_path = task
+        .output_path
+        .clone()
+        .ok_or_else(|| format!("Aucun dossier de sortie pour la tâche {}", task_id))?;
+    Ok((task.version.clone(), PathBuf::from(output_path)))
+}
+
+#[tauri::command]
+pub async fn diff_game_data(
+    old_task_id: String,
+    new_task_id: String,
+    format: Option<GameDiffFormat>,
+    scopes: Option<Vec<String>>,
+    output_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let (old_label, old_dir) = task_output(&state, &old_task_id).await?;
+    let (new_label, new_dir) = task_output(&state, &new_task_id).await?;
+    let scopes = scopes.unwrap_or_else(|| game_diff::DEFAULT_GAME_DATA_SCOPES.iter().map(|s| s.to_string()).collect());
+    let table = state.hash_table.lock().await.clone();
+    println!("🎮 Comparaison des données de jeu {} → {}", old_label, new_label);
+
+    let mut diff = tokio::task::spawn_blocking(move || game_diff::diff_game_data(&old_dir, &new_dir, &scopes, &table))
+        .await
+        .map_err(|e| e.to_string())??;
+    diff.old_label = old_label;
+    diff.new_label = new_label;
+    println!("🎮 {} entrées modifiées dans {} fichiers .bin", diff.changed.len(), diff.bins_compared);
+
+    let content = match format.unwrap_or(GameDiffFormat::Json) {
+        GameDiffFormat::Json => serde_json::to_string_pretty(&diff).map_err(|e| e.to_string())?,
+        GameDiffFormat::Markdown => game_diff::to_markdown(&diff),
+    };
+    if let Some(path) = output_path {
+        std::fs::write(&path, &content).map_err(|e| format!("Impossible d'écrire {}: {}", path, e))?;
+    }
+    Ok(content)
+}
+
+fn string_table_scopes(scopes: Option<Vec<String>>) -> Vec<String> {
+    scopes.unwrap_or_else(|| stringtable::DEFAULT_STRING_TABLE_SCOPES.iter().map(|s| s.to_string()).collect())
+}
+
+// Exporte les textes de chaque langue du patch en <langue>.json
+#[tauri::command]
+pub async fn extract_string_tables(
+    task_id: String,
+    languages: Option<Vec<String>>,
+    scopes: Option<Vec<String>>,
+    output_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<StringTableSummary>, String> {
+    let (_, task_dir) = task_output(&state, &task_id).await?;
+    // Par défaut : <dossier de la tâche>/_strings
+    let output_dir = output_path
+        .filter(|p| !p.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| task_dir.join("_strings"));
+    let scopes = string_table_scopes(scopes);
+    let languages = languages.unwrap_or_default();
+    let table = state.hash_table.lock().await.clone();
+
+    let summaries = tokio::task::spawn_blocking(move || {
+        let strings = stringtable::load_languages(&task_dir, &scopes, &languages, &table)?;
+        stringtable::write_languages(&strings, &output_dir)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    println!("🌐 Textes exportés pour {} langues", summaries.len());
+    Ok(summaries)
+}
+
+#[tauri::command]
+pub async fn diff_string_tables(
+    old_task_id: String,
+    new_task_id: String,
+    languages: Option<Vec<String>>,
+    scopes: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<Vec<LanguageDiff>, String> {
+    let (old_label, old_dir) = task_output(&state, &old_task_id).await?;
+    let (new_label, new_dir) = task_output(&state, &new_task_id).await?;
+    let scopes = string_table_scopes(scopes);
+    let languages = languages.unwrap_or_default();
+    let table = state.hash_table.lock().await.clone();
+    println!("🌐 Comparaison des textes {} → {}", old_label, new_label);
+
+    let diffs = tokio::task::spawn_blocking(move || -> Result<Vec<LanguageDiff>, String> {
+        let old = stringtable::load_languages(&old_dir, &scopes, &languages, &table)?;
+        let new = stringtable::load_languages(&new_dir, &scopes, &languages, &table)?;
+        Ok(stringtable::diff_languages(&old, &new))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    for diff in &diffs {
+        println!(
+            "🌐 {}: {} ajoutés, {} supprimés, {} reformulés",
+            diff.language,
+            diff.added.len(),
+            diff.removed.len(),
+            diff.reworded.len()
+        );
+    }
+    Ok(diffs)
+}
+
+// Comparer les fichiers de deux manifestes
+#[tauri::command]
+pub async fn diff_manifests(old_manifest: String, new_manifest: String) -> Result<ManifestDiff, String> {
+    println!("🔍 Comparaison de {} et {}", old_manifest, new_manifest);
+    let old = downloader::fetch_manifest(&old_manifest).await?;
+    let new = downloader::fetch_manifest(&new_manifest).await?;
+    Ok(diff::diff_manifests(&old, &new))
+}
+
+// Nouvelle commande pour sélectionner le dossier de destination
+#[tauri::command]
+pub async fn select_download_folder() -> Result<Option<String>, String> {
+    println!("📁 Ouverture de la boîte de dialogue de sélection de dossier...");
+
+    // Ouvrir la boîte de dialogue native pour sélectionner un dossier
+    let dialog_result = rfd::FileDialog::new()
+        .set_title("Sélectionner le dossier de destination pour les patches LoL")
+        .set_directory(dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("C:\\")))
+        .pick_folder();
+
+    match dialog_result {
+        Some(selected_path) => {
+            println!("📁 Dossier sélectionné: {}", selected_path.display());
+
+            // Créer le dossier s'il n'existe pas
+            if !selected_path.exists() {
+                std::fs::create_dir_all(&selected_path).map_err(|e| {
+                    println!("⚠️ Impossible de créer le dossier: {}", e);
+                    format!("Impossible de créer le dossier: {}", e)
+                })?;
+            }
+
+            Ok(Some(selected_path.to_string_lossy().to_string()))
+        }
+
+        None => {
+            println!("❌ Aucun dossier sélectionné par l'utilisateur");
+            Ok(None)
+        }
+    }
+}
+
+// Fonction pour mettre à jour le statut d'un téléchargement, renvoie faux si la transition est refusée
+async fn update_download_status(app: &AppHandle, task_id: &str, status: TaskStatus, progress: f64, error: Option<String>) -> bool {
+    println!("📊 Mise à jour statut - Task: {}, Status: {}, Progress: {:.1}%, Error: {:?}", task_id, status, progress, error);
+
+    let state = app.state::<AppState>();
+    let mut downloads = state.downloads.lock().await;
+    let Some(task) = downloads.get_mut(task_id) else {
+        return false;
+    };
+    // Une tâche mise en pause ou annulée garde son statut
+    if let Err(e) = task.transition(status, error.clone()) {
+        println!("⚠️ {}", e);
+        return false;
+    }
+    task.progress = progress;
+    task.error = error;
+    true
+}
+
+// Passe un téléchargement en erreur et prévient les webhooks
+async fn fail_download(app: &AppHandle, task_id: &str, error: String) {
+    if !update_download_status(app, task_id, TaskStatus::Failed, 0.0, Some(error.clone())).await {
+        return;
+    }
+    let manifest = app.state::<AppState>().downloads.lock().await.get(task_id).map(|t| t.manifest.clone());
+    notify_webhooks(app, WebhookEvent::download_failed(task_id, &manifest.unwrap_or_default(), &error)).await;
+}
+
+// Mettre à jour la progression, la vitesse et l'ETA à partir des compteurs du moteur
+async fn update_download_progress(app: &AppHandle, task_id: &str, control: &DownloadControl, bytes_per_second: f64) {
+    let total = control.total_bytes();
+    let downloaded = control.downloaded_bytes();
+    let progress = if total > 0 { downloaded as f64 / total as f64 * 100.0 } else { 0.0 };
+
+    let speed = format!("{:.1} MB/s", bytes_per_second / (1024.0 * 1024.0));
+    let eta = if bytes_per_second > 0.0 {
+        let remaining = (total.saturating_sub(downloaded) as f64 / bytes_per_second).round() as u64;
+        if remaining > 60 {
+            format!("{}m {}s", remaining / 60, remaining % 60)
+        } else {
+            format!("{}s", remaining)
+        }
+    } else {
+        "--".to_string()
+    };
+
+    let state = app.state::<AppState>();
+    let mut downloads = state.downloads.lock().await;
+    if let Some(task) = downloads.get_mut(task_id) {
+        if task.status == TaskStatus::Downloading {
+            task.progress = progress.min(100.0);
+            task.speed = speed;
+            task.eta = eta;
+        }
+    }
+}
+
+// Attendre qu'un emplacement se libère selon le nombre de téléchargements simultanés autorisé
+async fn wait_for_download_slot(app: &AppHandle, task_id: &str) -> bool {
+    loop {
+        let max_concurrent = app.state::<SettingsStore>().get().await.max_concurrent_downloads as usize;
+        {
+            let state = app.state::<AppState>();
+            let mut downloads = state.downloads.lock().await;
+            let active = downloads
+                .values()
+                .filter(|t| t.id != task_id && t.status == TaskStatus::Downloading)
+                .count();
+            match downloads.get_mut(task_id) {
+                Some(task) if task.status != TaskStatus::Queued => return false,
+                Some(task) if active < max_concurrent => {
+                    return task.transition(TaskStatus::Downloading, None).is_ok();
+                }
+                Some(_) => {}
+                None => return false,
+            }
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    }
+}
+
+// Une tentative du moteur, suivie chaque seconde ; None si le téléchargement est bloqué.
+// Le moteur est alors abandonné, même s'il attend une réponse réseau qui n'arrivera jamais.
+async fn drive_download(
+    app: &AppHandle,
+    task_id: &str,
+    manifest: &Manifest,
+    output_dir: &Path,
+    options: &DownloadOptions,
+    control: &Arc<DownloadControl>,
+    stall: &mut StallDetector,
+) -> Option<Result<DownloadSummary, String>> {
+    let download = downloader::download_manifest(manifest, output_dir, options, control.clone());
+    tokio::pin!(download);
+    let mut ticker = tokio::time::interval(tokio::time::Duration::from_secs(1));
+    let mut last_bytes = 0u64;
+    loop {
+        tokio::select! {
+            result = &mut download => return Some(result),
+            _ = ticker.tick() => {
+                let bytes = control.downloaded_bytes();
+                update_download_progress(app, task_id, control, bytes.saturating_sub(last_bytes) as f64).await;
+                last_bytes = bytes;
+                // La fenêtre ne démarre qu'avec les transferts (vérification des fichiers d'une reprise)
+                if control.is_preparing() {
+                    stall.reset();
+                    continue;
+                }
+                if stall.observe(std::time::Instant::now(), control.received_bytes()) {
+                    // Pause ou annulation pendant le blocage : interruption normale, sans redémarrage
+                    if control.is_cancelled() {
+                        return Some(Ok(DownloadSummary {
+                            files: downloader::select_files(manifest, options).len(),
+                            skipped_files: 0,
+                            bytes_written: control.downloaded_bytes(),
+                            interrupted: true,
+                            disk_full: control.is_disk_full(),
+                        }));
                     }
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+// Résoudre le dossier de sortie d'une tâche
+fn resolve_output_dir(output_path: Option<String>) -> PathBuf {
+    let current_dir = std::env::current_dir().unwrap();
+    let project_root = if cfg!(debug_assertions) {
+        current_dir.parent().unwrap().parent().unwrap()
+    } else {
+        &current_dir
+    };
+
+    if let Some(custom_path) = output_path {
+        let path = PathBuf::from(custom_path);
+        if path.is_relative() && cfg!(debug_assertions) {
+            // En mode debug, résoudre le chemin relatif depuis le répertoire du projet
+            project_root.join(path)
+        } else {
+            path
+        }
+    } else if cfg!(debug_assertions) {
+        project_root.join("downloads").join("files")
+    } else {
+        current_dir.join("downloads").join("files")
+    }
+}
 
-                    println!("⏰ Timeout après 30 minutes, arrêt forcé de rman-dl.exe...");
-                    let _ = child.kill().await;
-                    update_download_status(task_id.clone(), "error".to_string(), 0.0, "Timeout après 30 minutes".to_string()).await;
+// Dossier de sortie d'un patch : modèle des paramètres appliqué au dossier racine
+fn patch_output_dir(
+    template: &str,
+    root: Option<String>,
+    manifest_url: &str,
+    patch: Option<&PatchManifest>,
+    language: &str,
+    content: &str,
+) -> Result<PathBuf, String> {
+    let values = TemplateValues {
+        root: resolve_output_dir(root),
+        product: patch.map(|p| p.product.to_string()).unwrap_or_default(),
+        channel: patch.map(|p| p.channel.to_string()).unwrap_or_default(),
+        realm: patch.map(|p| p.region.clone()).unwrap_or_default(),
+        version: patch.map(|p| p.version.clone()).unwrap_or_default(),
+        manifest_hash: catalogue::manifest_hash_from_url(manifest_url),
+        date: patch.map(|p| p.date.clone()).unwrap_or_default(),
+        language: language.to_string(),
+        content: content.to_string(),
+    };
+    output_template::expand_template(template, &values)
+}
+
+// Exporter le dossier d'une tâche terminée, avec le même suivi qu'un téléchargement
+async fn execute_export(app: AppHandle, export_id: String, job: ExportJob) {
+    println!("📦 Export de {} vers {}", job.source_task_id, job.destination.display());
+
+    let (source_dir, manifest_url) = {
+        let state = app.state::<AppState>();
+        let downloads = state.downloads.lock().await;
+        match downloads.get(&job.source_task_id) {
+            Some(task) => (task.output_path.clone().map(PathBuf::from), task.manifest.clone()),
+            None => (None, String::new()),
+        }
+    };
+    let Some(source_dir) = source_dir else {
+        update_download_status(&app, &export_id, TaskStatus::Failed, 0.0, Some("Téléchargement source introuvable".to_string())).await;
+        return;
+    };
+    let manifest = match std::fs::read(manifest_file_path(&job.source_task_id))
+        .map_err(|e| format!("Manifest de la tâche illisible: {}", e))
+        .and_then(|bytes| Manifest::parse(&bytes))
+    {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            println!("❌ {}", e);
+            update_download_status(&app, &export_id, TaskStatus::Failed, 0.0, Some(e)).await;
+            return;
+        }
+    };
+
+    let patch = app
+        .state::<AppState>()
+        .manifests
+        .lock()
+        .await
+        .iter()
+        .find(|m| m.manifest == manifest_url)
+        .cloned();
+    let info = ArchiveInfo {
+        version: patch.as_ref().map(|p| p.version.clone()).unwrap_or_else(|| "Unknown".to_string()),
+        realm: patch.as_ref().map(|p| p.region.clone()).unwrap_or_default(),
+        manifest_hash: format!("{:016X}", manifest.id),
+    };
+
+    let control = Arc::new(DownloadControl::default());
+    app.state::<AppState>().download_controls.lock().await.insert(export_id.clone(), control.clone());
+    if !wait_for_download_slot(&app, &export_id).await {
+        app.state::<AppState>().download_controls.lock().await.remove(&export_id);
+        return;
+    }
+
+    let export_control = control.clone();
+    let export = tokio::task::spawn_blocking(move || {
+        archive::export_archive(&manifest, &source_dir, &job.destination, job.format, &info, &export_control)
+    });
+    tokio::pin!(export);
+    let mut ticker = tokio::time::interval(tokio::time::Duration::from_secs(1));
+    let mut last_bytes = 0u64;
+    let result = loop {
+        tokio::select! {
+            result = &mut export => break result.map_err(|e| e.to_string()).and_then(|r| r),
+            _ = ticker.tick() => {
+                let bytes = control.downloaded_bytes();
+                update_download_progress(&app, &export_id, &control, bytes.saturating_sub(last_bytes) as f64).await;
+                last_bytes = bytes;
+            }
+        }
+    };
+    app.state::<AppState>().download_controls.lock().await.remove(&export_id);
+
+    match result {
+        Ok(summary) if summary.interrupted => {
+            println!("⏸️ Export interrompu - {} fichiers archivés", summary.files);
+        }
+        Ok(summary) => {
+            println!("✅ Archive créée: {} ({} fichiers, {} repris)", summary.path, summary.files, summary.resumed_files);
+            update_download_status(&app, &export_id, TaskStatus::Completed, 100.0, None).await;
+        }
+        Err(e) => {
+            println!("❌ Erreur lors de l'export: {}", e);
+            update_download_status(&app, &export_id, TaskStatus::Failed, 0.0, Some(e)).await;
+        }
+    }
+}
+
+// Supprime au démarrage les fichiers temporaires notés dans les journaux des tâches d'une session interrompue
+// (fermeture, plantage). Seuls les fichiers créés par l'application sont concernés, et ceux conservés
+// volontairement (pause, annulation sans suppression) restent disponibles pour un nouveau téléchargement du patch.
+pub fn sweep_partial_downloads() {
+    let Ok(entries) = std::fs::read_dir(task_files_dir()) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let Some(task_id) = name.strip_prefix("journal_").and_then(|n| n.strip_suffix(".jsonl")) else {
+            continue;
+        };
+        if partial_files_kept(task_id) {
+            continue;
+        }
+        match journal::remove_recorded_partials(&path) {
+            Ok(0) => {}
+            Ok(count) => println!("🧹 {} fichiers temporaires supprimés d'après {}", count, path.display()),
+            Err(e) => println!("⚠️ Nettoyage d'après {} impossible: {}", path.display(), e),
+        }
+    }
+}
+
+// Marque les fichiers temporaires d'une tâche comme conservés, ou retire la marque
+fn keep_partial_files(task_id: &str, kept: bool) {
+    let marker = task_file_path(&format!("keep_{}", task_id));
+    let result = if kept {
+        std::fs::create_dir_all(task_files_dir()).and_then(|_| std::fs::write(&marker, b""))
+    } else {
+        match std::fs::remove_file(&marker) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    };
+    if let Err(e) = result {
+        println!("⚠️ Marque de conservation {} : {}", marker.display(), e);
+    }
+}
+
+fn partial_files_kept(task_id: &str) -> bool {
+    task_file_path(&format!("keep_{}", task_id)).exists()
+}
+
+// Emplacement du manifest sauvegardé pour une tâche
+fn manifest_file_path(task_id: &str) -> PathBuf {
+    task_file_path(&format!("manifest_{}.manifest", task_id))
+}
+
+// Journal des fichiers créés par la tâche, à côté de son manifest
+fn task_journal_path(task_id: &str) -> PathBuf {
+    task_file_path(&format!("journal_{}.jsonl", task_id))
+}
+
+fn task_file_path(file_name: &str) -> PathBuf {
+    task_files_dir().join(file_name)
+}
+
+// Dossier des manifests et journaux des tâches
+fn task_files_dir() -> PathBuf {
+    if cfg!(debug_assertions) {
+        std::env::current_dir().unwrap().parent().unwrap().join("downloads")
+    } else {
+        std::env::current_dir().unwrap().join("downloads")
+    }
+}
+
+// Paramètres d'une exécution du moteur, avec le contrôle qui lui est propre
+struct DownloadRun {
+    task_id: String,
+    manifest_url: String,
+    language: String,
+    content: String,
+    output_path: Option<String>,
+    check_space: bool,
+    control: Arc<DownloadControl>,
+}
+
+// Enregistre le contrôle d'une nouvelle exécution ; pause et annulation agissent dessus dès maintenant
+async fn register_run(state: &AppState, task_id: &str) -> (Arc<DownloadControl>, watch::Sender<bool>) {
+    let control = Arc::new(DownloadControl::default());
+    let (exited, exit_signal) = watch::channel(false);
+    state.download_controls.lock().await.insert(task_id.to_string(), control.clone());
+    state.running_downloads.lock().await.insert(
+        task_id.to_string(),
+        RunningDownload {
+            control: control.clone(),
+            on_exit: CancelMode::Keep,
+            exited: exit_signal,
+        },
+    );
+    (control, exited)
+}
+
+// Retire le contrôle d'une exécution, sauf s'il a déjà été remplacé par celui d'une reprise
+async fn release_control(app: &AppHandle, task_id: &str, control: &Arc<DownloadControl>) {
+    let state = app.state::<AppState>();
+    let mut controls = state.download_controls.lock().await;
+    if controls.get(task_id).is_some_and(|current| Arc::ptr_eq(current, control)) {
+        controls.remove(task_id);
+    }
+}
+
+// Exécute un téléchargement puis, s'il a été annulé entre-temps avec suppression, retire ses fichiers ;
+// la fin n'est signalée (`exited`) qu'une fois l'exécution et ses fichiers libérés
+async fn execute_real_download(app: AppHandle, run: DownloadRun, exited: watch::Sender<bool>) {
+    let task_id = run.task_id.clone();
+    let control = run.control.clone();
+    run_download(app.clone(), run).await;
+    release_control(&app, &task_id, &control).await;
+
+    // L'entrée d'une reprise lancée entre-temps appartient à cette reprise
+    let state = app.state::<AppState>();
+    let mut running = state.running_downloads.lock().await;
+    let on_exit = match running.get(&task_id) {
+        Some(current) if Arc::ptr_eq(&current.control, &control) => running.remove(&task_id).map(|r| r.on_exit),
+        _ => None,
+    };
+    drop(running);
+    if on_exit == Some(CancelMode::Delete) {
+        remove_task_files(task_id).await;
+    }
+    exited.send_replace(true);
+}
+
+async fn run_download(app: AppHandle, run: DownloadRun) {
+    let DownloadRun {
+        task_id,
+        manifest_url,
+        language,
+        content,
+        output_path,
+        check_space,
+        control,
+    } = run;
+    println!("🚀 Démarrage du téléchargement réel pour task: {}", task_id);
+    println!("📄 Manifest URL: {}", manifest_url);
+    println!("🌍 Langue: {}", language);
+    println!("📦 Contenu: {}", content);
+    println!("📁 Dossier de sortie: {:?}", output_path);
+    
+    // 1. Télécharger le manifest en binaire
+    println!("📥 Téléchargement du manifest...");
+    let manifest_content = match downloader::fetch_bytes(&manifest_url).await {
+        Ok(bytes) => {
+            println!("✅ Manifest téléchargé ({} bytes)", bytes.len());
+            bytes
+        }
+        Err(e) => {
+            println!("❌ {}", e);
+            fail_download(&app, &task_id, e).await;
+            return;
+        }
+    };
+    
+    // 2. Sauvegarder le manifest localement
+    let manifest_path = manifest_file_path(&task_id);
+    
+    // Créer le dossier downloads s'il n'existe pas
+    if let Some(parent) = manifest_path.parent() {
+        if let Err(e) = std::fs::create_dir_all(parent) {
+            println!("❌ Erreur lors de la création du dossier downloads: {}", e);
+            fail_download(&app, &task_id, format!("Impossible de créer le dossier {}: {}", parent.display(), e)).await;
+            return;
+        }
+    }
+    
+    // Sauvegarder le manifest en binaire
+    match std::fs::write(&manifest_path, &manifest_content) {
+        Ok(_) => println!("✅ Manifest sauvegardé: {} ({} bytes)", manifest_path.display(), manifest_content.len()),
+        Err(e) => {
+            println!("❌ Erreur lors de la sauvegarde du manifest: {}", e);
+            fail_download(&app, &task_id, format!("Impossible d'enregistrer le manifest {}: {}", manifest_path.display(), e)).await;
+            return;
+        }
+    }
+    
+    // 3. Lire le manifest
+    let manifest = match Manifest::parse(&manifest_content) {
+        Ok(manifest) => {
+            println!("✅ Manifest {:016X}: {} fichiers, {} chunks", manifest.id, manifest.files.len(), manifest.chunks.len());
+            manifest
+        }
+        Err(e) => {
+            println!("❌ {}", e);
+            fail_download(&app, &task_id, e).await;
+            return;
+        }
+    };
+    
+    // 4. Créer le dossier de sortie pour les fichiers téléchargés
+    let output_dir = resolve_output_dir(output_path);
+
+    // Créer le dossier de sortie s'il n'existe pas, en notant dans le journal de la tâche tout ce qu'elle crée
+    let journal = match FileJournal::open(&task_journal_path(&task_id)) {
+        Ok(journal) => Arc::new(journal),
+        Err(e) => {
+            println!("❌ {}", e);
+            fail_download(&app, &task_id, e).await;
+            return;
+        }
+    };
+    if let Err(e) = journal.create_dir_all(&output_dir) {
+        println!("❌ Erreur lors de la création du dossier de sortie: {}", e);
+        fail_download(&app, &task_id, format!("Impossible de créer le dossier de sortie: {}", e)).await;
+        return;
+    }
+    println!("📁 Dossier de sortie: {}", output_dir.display());
+
+    // 5. Enregistrer le dossier résolu
+    if let Some(task) = app.state::<AppState>().downloads.lock().await.get_mut(&task_id) {
+        task.output_path = Some(output_dir.to_string_lossy().to_string());
+    }
+
+    // CDN et valeurs par défaut du produit, d'après le catalogue
+    let product = app
+        .state::<AppState>()
+        .manifests
+        .lock()
+        .await
+        .iter()
+        .find(|m| m.manifest == manifest_url)
+        .map(|m| m.product)
+        .unwrap_or_default();
+    let settings = app.state::<SettingsStore>().get().await;
+    let options = DownloadOptions {
+        cdn_url: product.cdn_url().to_string(),
+        language,
+        content_filter: content,
+        speed_limit: settings.speed_limit_bytes(),
+        min_free_space: DEFAULT_FREE_SPACE_RESERVE,
+        journal: Some(journal),
+        ..DownloadOptions::default()
+    };
+
+    // Contrôle précis de l'espace disque à partir du manifest (hachage des fichiers existants)
+    let required = {
+        let (manifest, output_dir, options) = (manifest.clone(), output_dir.clone(), options.clone());
+        tokio::task::spawn_blocking(move || disk_space::required_space(&manifest, &output_dir, &options))
+            .await
+            .unwrap_or_else(|e| Err(format!("Calcul de l'espace nécessaire interrompu: {}", e)))
+    };
+    let space_check = required
+        .map_err(DownloadError::from)
+        .and_then(|required| {
+            println!("💽 Espace nécessaire: {}", catalogue::format_size(required));
+            disk_space::check_disk_space(&output_dir, required, DEFAULT_FREE_SPACE_RESERVE)
+        });
+    if let Err(e) = space_check {
+        if check_space {
+            println!("❌ {}", e);
+            fail_download(&app, &task_id, e.to_string()).await;
+            return;
+        }
+        println!("⚠️ {} (contrôle ignoré)", e);
+    }
+    println!("🔧 Options: CDN {}, langue {}, filtre \"{}\", {} workers, limite {} Ko/s", options.cdn_url, options.language, options.content_filter, options.workers, settings.download_speed);
+
+    // 6. Attendre un emplacement libre puis lancer le moteur en suivant sa progression chaque seconde
+    if !wait_for_download_slot(&app, &task_id).await {
+        println!("⏸️ Task {} retirée de la file d'attente", task_id);
+        return;
+    }
+    // Un téléchargement bloqué est relancé depuis son point de reprise (fichiers temporaires conservés)
+    let mut stall = StallDetector::new(&settings.stall, options.speed_limit);
+    let mut restarts = 0;
+    let result = loop {
+        match drive_download(&app, &task_id, &manifest, &output_dir, &options, &control, &mut stall).await {
+            Some(result) => break result,
+            None if restarts < settings.stall.max_restarts => {
+                restarts += 1;
+                println!(
+                    "🐢 Téléchargement bloqué (moins de {} octets en {} s) - Task: {}, redémarrage {}/{}",
+                    settings.stall.min_bytes, settings.stall.window_seconds, task_id, restarts, settings.stall.max_restarts
+                );
+                if let Some(task) = app.state::<AppState>().downloads.lock().await.get_mut(&task_id) {
+                    task.stall_restarts += 1;
                 }
+                tokio::time::sleep(STALL_RESTART_DELAY).await;
+                stall.reset();
+            }
+            None => {
+                break Err(format!(
+                    "Téléchargement bloqué : moins de {} octets reçus en {} s, abandon après {} redémarrage(s)",
+                    settings.stall.min_bytes, settings.stall.window_seconds, restarts
+                ))
+            }
+        }
+    };
+
+    match result {
+        Ok(summary) if summary.disk_full => {
+            println!("💽 Disque plein, téléchargement mis en pause - Task: {}", task_id);
+            let state = app.state::<AppState>();
+            let mut downloads = state.downloads.lock().await;
+            if let Some(task) = downloads.get_mut(&task_id) {
+                let error = "Espace disque insuffisant, libérez de la place puis reprenez le téléchargement".to_string();
+                if task.transition(TaskStatus::Paused, Some(error.clone())).is_ok() {
+                    task.error = Some(error);
+                    keep_partial_files(&task_id, true);
+                }
+            }
+        }
+        Ok(summary) if summary.interrupted => {
+            println!("⏸️ Téléchargement interrompu - Task: {} ({} octets écrits)", task_id, summary.bytes_written);
+        }
+        Ok(summary) => {
+            println!("✅ Téléchargement terminé - {} fichiers ({} déjà présents), {} octets écrits", summary.files, summary.skipped_files, summary.bytes_written);
+            if !update_download_status(&app, &task_id, TaskStatus::Completed, 100.0, None).await {
+                return;
+            }
+            if let Err(e) = record_game_version(&app, &task_id, output_dir.clone()).await {
+                println!("⚠️ Version du jeu non détectée : {}", e);
+            }
+            let task = app.state::<AppState>().downloads.lock().await.get(&task_id).cloned();
+            if let Some(task) = task {
+                let output = task.output_path.unwrap_or_default();
+                notify_webhooks(&app, WebhookEvent::download_completed(&task.id, &task.manifest, &task.version, &output)).await;
             }
         }
         Err(e) => {
-            println!("❌ Erreur lors du lancement de rman-dl.exe: {}", e);
-            update_download_status(task_id.clone(), "error".to_string(), 0.0, format!("Erreur de lancement: {}", e)).await;
+            println!("❌ Erreur lors du téléchargement: {}", e);
+            fail_download(&app, &task_id, e).await;
         }
     }
 }
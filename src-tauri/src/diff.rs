@@ -0,0 +1,55 @@
+// Comparaison des fichiers de deux manifestes
+use crate::rman::Manifest;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffEntry {
+    pub path: String,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ManifestDiff {
+    pub added: Vec<DiffEntry>,
+    pub removed: Vec<DiffEntry>,
+    pub changed: Vec<DiffEntry>,
+    pub unchanged: usize,
+}
+
+pub fn diff_manifests(old: &Manifest, new: &Manifest) -> ManifestDiff {
+    let old_files: HashMap<&str, _> = old.files.iter().map(|f| (f.path.as_str(), f)).collect();
+    let new_files: HashMap<&str, _> = new.files.iter().map(|f| (f.path.as_str(), f)).collect();
+    let mut diff = ManifestDiff::default();
+
+    for file in &new.files {
+        match old_files.get(file.path.as_str()) {
+            None => diff.added.push(DiffEntry {
+                path: file.path.clone(),
+                old_size: None,
+                new_size: Some(file.size),
+            }),
+            Some(previous) if previous.size != file.size || previous.chunk_ids != file.chunk_ids => {
+                diff.changed.push(DiffEntry {
+                    path: file.path.clone(),
+                    old_size: Some(previous.size),
+                    new_size: Some(file.size),
+                })
+            }
+            Some(_) => diff.unchanged += 1,
+        }
+    }
+
+    for file in &old.files {
+        if !new_files.contains_key(file.path.as_str()) {
+            diff.removed.push(DiffEntry {
+                path: file.path.clone(),
+                old_size: Some(file.size),
+                new_size: None,
+            });
+        }
+    }
+
+    diff
+}
@@ -0,0 +1,52 @@
+// Contrôle de l'espace disque disponible avant et pendant un téléchargement
+use crate::downloader::{local_path, partial_path, select_files, verify_file, DownloadOptions};
+use crate::error::DownloadError;
+use crate::rman::Manifest;
+use std::path::Path;
+
+// Marge conservée libre sur le volume cible
+pub const DEFAULT_FREE_SPACE_RESERVE: u64 = 512 * 1024 * 1024;
+
+// Espace libre du volume contenant `path` (le dossier n'a pas besoin d'exister encore)
+pub fn available_space(path: &Path) -> std::io::Result<u64> {
+    let mut current = Some(path);
+    while let Some(candidate) = current {
+        if candidate.exists() {
+            return fs4::available_space(candidate);
+        }
+        current = candidate.parent();
+    }
+    fs4::available_space(std::env::current_dir()?)
+}
+
+// Octets encore à allouer : les fichiers valides sont conservés, les autres sont réécrits
+// dans un fichier temporaire de pleine taille (dont la part déjà allouée est déduite)
+pub fn required_space(manifest: &Manifest, output_dir: &Path, options: &DownloadOptions) -> Result<u64, String> {
+    let mut required = 0u64;
+    for file in select_files(manifest, options) {
+        let path = local_path(output_dir, &file.path)?;
+        let complete = std::fs::metadata(&path).is_ok_and(|m| m.len() == file.size)
+            && verify_file(manifest, file, &path)?;
+        if complete {
+            continue;
+        }
+        let allocated = std::fs::metadata(partial_path(&path)).map(|m| m.len()).unwrap_or(0);
+        required += file.size.saturating_sub(allocated);
+    }
+    Ok(required)
+}
+
+pub fn check_disk_space(path: &Path, required: u64, reserve: u64) -> Result<u64, DownloadError> {
+    let available = available_space(path).map_err(|e| DownloadError::DiskSpaceUnavailable {
+        path: path.display().to_string(),
+        message: e.to_string(),
+    })?;
+    if available < required.saturating_add(reserve) {
+        return Err(DownloadError::InsufficientDiskSpace {
+            path: path.display().to_string(),
+            required: required.saturating_add(reserve),
+            available,
+        });
+    }
+    Ok(available)
+}
@@ -0,0 +1,599 @@
+// Moteur de téléchargement natif : lit un manifest RMAN et récupère les chunks depuis le CDN
+use crate::journal::FileJournal;
+use crate::rman::{chunk_hash, Chunk, Manifest, ManifestFile};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+pub const DEFAULT_CDN_URL: &str = "http://lol.secure.dyn.riotcdn.net/channels/public";
+
+// Suffixe des fichiers en cours d'écriture, renommés une fois complets
+pub const PARTIAL_SUFFIX: &str = ".partial";
+
+// Écart maximal entre deux chunks d'un bundle pour les récupérer dans la même requête
+const MAX_RANGE_GAP: u64 = 64 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    pub cdn_url: String,
+    pub language: String,
+    pub content_filter: String,
+    pub workers: usize,
+    // Débit maximal en octets par seconde, 0 = illimité
+    pub speed_limit: u64,
+    // Espace à conserver libre : le téléchargement s'interrompt en dessous
+    pub min_free_space: u64,
+    // Journal des fichiers et dossiers créés, pour l'annulation avec suppression
+    pub journal: Option<Arc<FileJournal>>,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            cdn_url: DEFAULT_CDN_URL.to_string(),
+            language: "en_us".to_string(),
+            content_filter: String::new(),
+            workers: 4,
+            speed_limit: 0,
+            min_free_space: 0,
+            journal: None,
+        }
+    }
+}
+
+// Partagé entre le moteur et les commandes pour suivre et interrompre un téléchargement
+#[derive(Debug, Default)]
+pub struct DownloadControl {
+    cancelled: AtomicBool,
+    disk_full: AtomicBool,
+    downloaded_bytes: AtomicU64,
+    total_bytes: AtomicU64,
+    // Octets reçus du réseau, avant décompression et sans remise à zéro entre les tentatives
+    received_bytes: AtomicU64,
+    // Vérification des fichiers existants en cours, avant tout transfert
+    preparing: AtomicBool,
+}
+
+impl DownloadControl {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    // Interruption propre quand le disque est plein, les fichiers sont conservés
+    fn stop_disk_full(&self) {
+        self.disk_full.store(true, Ordering::SeqCst);
+        self.cancel();
+    }
+
+    pub fn is_disk_full(&self) -> bool {
+        self.disk_full.load(Ordering::SeqCst)
+    }
+
+    pub fn downloaded_bytes(&self) -> u64 {
+        self.downloaded_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn received_bytes(&self) -> u64 {
+        self.received_bytes.load(Ordering::Relaxed)
+    }
+
+    // Rien n'est reçu pendant la préparation : la détection des blocages doit l'ignorer
+    pub fn is_preparing(&self) -> bool {
+        self.preparing.load(Ordering::SeqCst)
+    }
+
+    // Compteurs partagés avec les autres opérations longues (export d'archive)
+    pub(crate) fn reset_progress(&self, total: u64) {
+        self.total_bytes.store(total, Ordering::Relaxed);
+        self.downloaded_bytes.store(0, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_progress(&self, bytes: u64) {
+        self.downloaded_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+// Limiteur de débit partagé par tous les workers d'un téléchargement
+#[derive(Debug)]
+struct RateLimiter {
+    bytes_per_second: u64,
+    state: tokio::sync::Mutex<(Instant, u64)>,
+}
+
+impl RateLimiter {
+    fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second,
+            state: tokio::sync::Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    async fn consume(&self, bytes: usize) {
+        if self.bytes_per_second == 0 {
+            return;
+        }
+        let wait = {
+            let mut state = self.state.lock().await;
+            state.1 += bytes as u64;
+            let expected = Duration::from_secs_f64(state.1 as f64 / self.bytes_per_second as f64);
+            expected.saturating_sub(state.0.elapsed())
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadSummary {
+    pub files: usize,
+    pub skipped_files: usize,
+    pub bytes_written: u64,
+    pub interrupted: bool,
+    pub disk_full: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    pub checked: usize,
+    pub missing: Vec<String>,
+    pub corrupted: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.corrupted.is_empty()
+    }
+}
+
+// Fichiers du manifest retenus selon la langue et le filtre de contenu
+pub fn select_files<'a>(manifest: &'a Manifest, options: &DownloadOptions) -> Vec<&'a ManifestFile> {
+    let filter = options.content_filter.trim().to_lowercase();
+    manifest
+        .files
+        .iter()
+        .filter(|f| f.link.is_empty())
+        .filter(|f| f.matches_language(&options.language))
+        .filter(|f| filter.is_empty() || f.path.to_lowercase().contains(&filter))
+        .collect()
+}
+
+// Chemin local d'un fichier du manifest, en refusant toute sortie du dossier cible
+pub fn local_path(output_dir: &Path, manifest_path: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(manifest_path);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Err(format!("Chemin invalide dans le manifest: {}", manifest_path));
+    }
+    Ok(output_dir.join(relative))
+}
+
+// Fichier temporaire dans lequel le moteur écrit avant le renommage final
+pub fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(PARTIAL_SUFFIX);
+    PathBuf::from(name)
+}
+
+pub async fn fetch_bytes(url: &str) -> Result<Vec<u8>, String> {
+    let response = reqwest::get(url)
+        .await
+        .map_err(|e| format!("Erreur lors du téléchargement de {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Erreur HTTP {} pour {}", response.status(), url));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Erreur lors de la lecture de {}: {}", url, e))?;
+    Ok(bytes.to_vec())
+}
+
+pub async fn fetch_manifest(url: &str) -> Result<Manifest, String> {
+    Manifest::parse(&fetch_bytes(url).await?)
+}
+
+// Vérifie un fichier sur disque chunk par chunk
+pub fn verify_file(manifest: &Manifest, file: &ManifestFile, path: &Path) -> Result<bool, String> {
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(false),
+    };
+    if metadata.len() != file.size {
+        return Ok(false);
+    }
+
+    let mut reader = std::io::BufReader::new(
+        std::fs::File::open(path).map_err(|e| format!("Impossible d'ouvrir {}: {}", path.display(), e))?,
+    );
+    let mut buffer = Vec::new();
+    for chunk_id in &file.chunk_ids {
+        let chunk = manifest
+            .chunks
+            .get(chunk_id)
+            .ok_or_else(|| format!("Chunk {:016X} absent du manifest", chunk_id))?;
+        buffer.resize(chunk.uncompressed_size as usize, 0);
+        if reader.read_exact(&mut buffer).is_err() {
+            return Ok(false);
+        }
+        if let Some(hash) = chunk_hash(&buffer, file.hash_type) {
+            if hash != *chunk_id {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
+
+pub fn verify_files(manifest: &Manifest, output_dir: &Path, options: &DownloadOptions) -> Result<VerifyReport, String> {
+    let mut report = VerifyReport::default();
+    for file in select_files(manifest, options) {
+        let path = local_path(output_dir, &file.path)?;
+        report.checked += 1;
+        if !path.exists() {
+            report.missing.push(file.path.clone());
+        } else if !verify_file(manifest, file, &path)? {
+            report.corrupted.push(file.path.clone());
+        }
+    }
+    Ok(report)
+}
+
+// Emplacement d'un chunk dans un fichier de sortie
+#[derive(Debug, Clone)]
+struct ChunkTarget {
+    file: usize,
+    offset: u64,
+    hash_type: u8,
+}
+
+// Fichier en cours d'écriture : renommé vers sa destination quand tous ses chunks sont écrits
+#[derive(Debug)]
+struct PendingFile {
+    path: PathBuf,
+    partial: PathBuf,
+    remaining: AtomicUsize,
+}
+
+impl PendingFile {
+    fn finish(&self) -> Result<(), String> {
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&self.partial)
+            .and_then(|file| file.sync_all())
+            .and_then(|_| std::fs::rename(&self.partial, &self.path))
+            .map_err(|e| format!("Impossible de finaliser {}: {}", self.path.display(), e))
+    }
+}
+
+// Chunks déjà présents et valides dans un fichier temporaire conservé par une pause
+fn valid_partial_chunks(manifest: &Manifest, file: &ManifestFile, partial: &Path) -> HashSet<u64> {
+    let mut valid = HashSet::new();
+    let Ok(handle) = std::fs::File::open(partial) else {
+        return valid;
+    };
+    if handle.metadata().map(|m| m.len()).unwrap_or(0) != file.size {
+        return valid;
+    }
+    let mut reader = std::io::BufReader::new(handle);
+    let mut buffer = Vec::new();
+    for (chunk_id, offset) in file.chunk_offsets(manifest) {
+        let Some(chunk) = manifest.chunks.get(&chunk_id) else {
+            continue;
+        };
+        buffer.resize(chunk.uncompressed_size as usize, 0);
+        let read = reader
+            .seek(SeekFrom::Start(offset))
+            .and_then(|_| reader.read_exact(&mut buffer));
+        if read.is_ok() && chunk_hash(&buffer, file.hash_type) == Some(chunk_id) {
+            valid.insert(offset);
+        }
+    }
+    valid
+}
+
+// Fichiers à compléter et chunks à écrire, après vérification de ceux déjà présents
+struct Prepared {
+    targets: HashMap<u64, Vec<ChunkTarget>>,
+    pending: Vec<PendingFile>,
+    total_bytes: u64,
+    files: usize,
+    skipped_files: usize,
+}
+
+fn prepare_files(manifest: &Manifest, output_dir: &Path, options: &DownloadOptions) -> Result<Prepared, String> {
+    let files = select_files(manifest, options);
+    let mut skipped_files = 0;
+    let mut targets: HashMap<u64, Vec<ChunkTarget>> = HashMap::new();
+    let mut pending = Vec::new();
+    let mut total_bytes = 0u64;
+    for file in &files {
+        let path = local_path(output_dir, &file.path)?;
+        if path.exists() && verify_file(manifest, file, &path)? {
+            skipped_files += 1;
+            continue;
+        }
+        let partial = partial_path(&path);
+        if let Some(parent) = path.parent() {
+            match &options.journal {
+                Some(journal) => journal.create_dir_all(parent)?,
+                None => std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?,
+            }
+        }
+        // Un fichier déjà présent mais invalide sera remplacé sans être noté : il n'appartient pas à la tâche
+        if let Some(journal) = &options.journal {
+            journal.record_new(&path)?;
+            journal.record_new(&partial)?;
+        }
+        let already_written = valid_partial_chunks(manifest, file, &partial);
+        if already_written.is_empty() {
+            let handle = std::fs::File::create(&partial)
+                .map_err(|e| format!("Impossible de créer {}: {}", partial.display(), e))?;
+            handle
+                .set_len(file.size)
+                .map_err(|e| format!("Impossible de dimensionner {}: {}", partial.display(), e))?;
+        }
+
+        let index = pending.len();
+        let mut remaining = 0;
+        for (chunk_id, offset) in file.chunk_offsets(manifest) {
+            if already_written.contains(&offset) {
+                continue;
+            }
+            if let Some(chunk) = manifest.chunks.get(&chunk_id) {
+                total_bytes += chunk.uncompressed_size as u64;
+            }
+            remaining += 1;
+            targets.entry(chunk_id).or_default().push(ChunkTarget {
+                file: index,
+                offset,
+                hash_type: file.hash_type,
+            });
+        }
+        let pending_file = PendingFile {
+            path,
+            partial,
+            remaining: AtomicUsize::new(remaining),
+        };
+        // Fichier vide ou entièrement présent dans le fichier temporaire
+        if remaining == 0 {
+            pending_file.finish()?;
+        }
+        pending.push(pending_file);
+    }
+    Ok(Prepared {
+        targets,
+        pending,
+        total_bytes,
+        files: files.len(),
+        skipped_files,
+    })
+}
+
+pub async fn download_manifest(
+    manifest: &Manifest,
+    output_dir: &Path,
+    options: &DownloadOptions,
+    control: Arc<DownloadControl>,
+) -> Result<DownloadSummary, String> {
+    // 1. Préparer les fichiers temporaires et repérer ceux déjà complets (reprise).
+    // Lectures et hachages sur disque, hors des threads du runtime
+    control.preparing.store(true, Ordering::SeqCst);
+    let prepared = {
+        let manifest = manifest.clone();
+        let output_dir = output_dir.to_path_buf();
+        let options = options.clone();
+        tokio::task::spawn_blocking(move || prepare_files(&manifest, &output_dir, &options)).await
+    };
+    control.preparing.store(false, Ordering::SeqCst);
+    let Prepared {
+        targets,
+        pending,
+        total_bytes,
+        files,
+        skipped_files,
+    } = prepared.map_err(|e| format!("Préparation des fichiers interrompue: {}", e))??;
+    let mut summary = DownloadSummary {
+        files,
+        skipped_files,
+        bytes_written: 0,
+        interrupted: false,
+        disk_full: false,
+    };
+    control.reset_progress(total_bytes);
+
+    // 2. Regrouper les chunks nécessaires par bundle
+    let mut bundles: HashMap<u64, Vec<Chunk>> = HashMap::new();
+    for chunk_id in targets.keys() {
+        let chunk = manifest
+            .chunks
+            .get(chunk_id)
+            .ok_or_else(|| format!("Chunk {:016X} absent du manifest", chunk_id))?;
+        bundles.entry(chunk.bundle_id).or_default().push(chunk.clone());
+    }
+
+    // 3. Télécharger les bundles en parallèle
+    let client = reqwest::Client::new();
+    let semaphore = Arc::new(Semaphore::new(options.workers.max(1)));
+    let limiter = Arc::new(RateLimiter::new(options.speed_limit));
+    let targets = Arc::new(targets);
+    let pending = Arc::new(pending);
+    let mut tasks = JoinSet::new();
+    for (bundle_id, mut chunks) in bundles {
+        chunks.sort_by_key(|c| c.bundle_offset);
+        let url = format!("{}/bundles/{:016X}.bundle", options.cdn_url.trim_end_matches('/'), bundle_id);
+        let client = client.clone();
+        let semaphore = semaphore.clone();
+        let targets = targets.clone();
+        let pending = pending.clone();
+        let control = control.clone();
+        let limiter = limiter.clone();
+        let output_dir = output_dir.to_path_buf();
+        let min_free_space = options.min_free_space;
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.map_err(|e| e.to_string())?;
+            if min_free_space > 0 && has_low_disk_space(&output_dir, min_free_space) {
+                control.stop_disk_full();
+            }
+            download_bundle(&client, &url, &chunks, &targets, &pending, &control, &limiter).await
+        });
+    }
+
+    while let Some(result) = tasks.join_next().await {
+        let outcome = result
+            .map_err(|e| format!("Tâche de téléchargement interrompue: {}", e))
+            .and_then(|r| r);
+        if let Err(e) = outcome {
+            control.cancel();
+            tasks.abort_all();
+            return Err(e);
+        }
+    }
+
+    summary.bytes_written = control.downloaded_bytes();
+    summary.interrupted = control.is_cancelled();
+    summary.disk_full = control.is_disk_full();
+    Ok(summary)
+}
+
+async fn download_bundle(
+    client: &reqwest::Client,
+    url: &str,
+    chunks: &[Chunk],
+    targets: &HashMap<u64, Vec<ChunkTarget>>,
+    pending: &[PendingFile],
+    control: &DownloadControl,
+    limiter: &RateLimiter,
+) -> Result<(), String> {
+    for span in merge_ranges(chunks) {
+        if control.is_cancelled() {
+            return Ok(());
+        }
+        let start = span[0].bundle_offset;
+        let last = span[span.len() - 1];
+        let end = last.bundle_offset + last.compressed_size as u64;
+
+        let mut response = client
+            .get(url)
+            .header(reqwest::header::RANGE, format!("bytes={}-{}", start, end - 1))
+            .send()
+            .await
+            .map_err(|e| format!("Erreur réseau pour {}: {}", url, e))?;
+        if !response.status().is_success() {
+            return Err(format!("Erreur HTTP {} pour {}", response.status(), url));
+        }
+        let partial = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+        let mut data = Vec::with_capacity((end - start) as usize);
+        while let Some(piece) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Erreur de lecture pour {}: {}", url, e))?
+        {
+            control.received_bytes.fetch_add(piece.len() as u64, Ordering::Relaxed);
+            limiter.consume(piece.len()).await;
+            data.extend_from_slice(&piece);
+        }
+        // Certains serveurs ignorent l'en-tête Range et renvoient le bundle complet
+        let base = if partial { start } else { 0 };
+
+        for chunk in span {
+            let from = (chunk.bundle_offset - base) as usize;
+            let compressed = data
+                .get(from..from + chunk.compressed_size as usize)
+                .ok_or_else(|| format!("Réponse tronquée pour le chunk {:016X}", chunk.id))?;
+            let decompressed = zstd::bulk::decompress(compressed, chunk.uncompressed_size as usize)
+                .map_err(|e| format!("Erreur de décompression du chunk {:016X}: {}", chunk.id, e))?;
+            let chunk_targets = targets.get(&chunk.id).map(Vec::as_slice).unwrap_or(&[]);
+            write_chunk(chunk, &decompressed, chunk_targets, pending, control)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_chunk(
+    chunk: &Chunk,
+    data: &[u8],
+    targets: &[ChunkTarget],
+    pending: &[PendingFile],
+    control: &DownloadControl,
+) -> Result<(), String> {
+    let mut verified = HashSet::new();
+    for target in targets {
+        if verified.insert(target.hash_type) {
+            if let Some(hash) = chunk_hash(data, target.hash_type) {
+                if hash != chunk.id {
+                    return Err(format!("Chunk {:016X} corrompu (hash {:016X})", chunk.id, hash));
+                }
+            }
+        }
+        let pending_file = &pending[target.file];
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&pending_file.partial)
+            .map_err(|e| format!("Impossible d'ouvrir {}: {}", pending_file.partial.display(), e))?;
+        if let Err(e) = file.seek(SeekFrom::Start(target.offset)).and_then(|_| file.write_all(data)) {
+            if is_disk_full_error(&e) {
+                control.stop_disk_full();
+                return Ok(());
+            }
+            return Err(format!("Erreur d'écriture dans {}: {}", pending_file.partial.display(), e));
+        }
+        drop(file);
+        control.add_progress(data.len() as u64);
+        if pending_file.remaining.fetch_sub(1, Ordering::SeqCst) == 1 {
+            pending_file.finish()?;
+        }
+    }
+    Ok(())
+}
+
+fn has_low_disk_space(output_dir: &Path, min_free_space: u64) -> bool {
+    crate::disk_space::available_space(output_dir)
+        .map(|available| available < min_free_space)
+        .unwrap_or(false)
+}
+
+// ENOSPC sous Unix, ERROR_DISK_FULL / ERROR_HANDLE_DISK_FULL sous Windows
+fn is_disk_full_error(error: &std::io::Error) -> bool {
+    match error.raw_os_error() {
+        Some(code) if cfg!(windows) => code == 112 || code == 39,
+        Some(code) => code == 28,
+        None => false,
+    }
+}
+
+// Fusionne les chunks proches pour limiter le nombre de requêtes
+fn merge_ranges(chunks: &[Chunk]) -> Vec<Vec<&Chunk>> {
+    let mut spans: Vec<Vec<&Chunk>> = Vec::new();
+    for chunk in chunks {
+        match spans.last_mut() {
+            Some(span) => {
+                let last = span[span.len() - 1];
+                if chunk.bundle_offset <= last.bundle_offset + last.compressed_size as u64 + MAX_RANGE_GAP {
+                    span.push(chunk);
+                } else {
+                    spans.push(vec![chunk]);
+                }
+            }
+            None => spans.push(vec![chunk]),
+        }
+    }
+    spans
+}
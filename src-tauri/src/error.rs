@@ -0,0 +1,48 @@
+// Erreurs typées renvoyées au frontend par les commandes de téléchargement
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DownloadError {
+    #[serde(rename_all = "camelCase")]
+    InsufficientDiskSpace {
+        path: String,
+        required: u64,
+        available: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    DiskSpaceUnavailable { path: String, message: String },
+    #[serde(rename_all = "camelCase")]
+    OutputDirectoryInUse { path: String, task_id: String },
+    Other { message: String },
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadError::InsufficientDiskSpace { path, required, available } => write!(
+                f,
+                "Espace disque insuffisant sur {} : {} nécessaires, {} disponibles",
+                path,
+                crate::catalogue::format_size(*required),
+                crate::catalogue::format_size(*available)
+            ),
+            DownloadError::DiskSpaceUnavailable { path, message } => {
+                write!(f, "Impossible de lire l'espace libre de {} : {}", path, message)
+            }
+            DownloadError::OutputDirectoryInUse { path, task_id } => write!(
+                f,
+                "Le dossier {} est déjà utilisé par le téléchargement {}",
+                path, task_id
+            ),
+            DownloadError::Other { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for DownloadError {
+    fn from(message: String) -> Self {
+        DownloadError::Other { message }
+    }
+}
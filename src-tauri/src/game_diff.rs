@@ -0,0 +1,291 @@
+// Différences des données de jeu (.bin des champions et objets) entre deux patchs téléchargés
+use crate::hashes::HashTable;
+use crate::prop_bin::{PropBin, PROP_MAGIC, PTCH_MAGIC};
+use crate::wad::{self, Wad, WadEntry};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+// WAD comparés par défaut : champions et données globales (objets, runes)
+pub const DEFAULT_GAME_DATA_SCOPES: &[&str] = &["DATA/FINAL/Champions/", "DATA/FINAL/Global"];
+
+// Champs qui identifient un élément de liste, plus stables que sa position
+const LIST_KEY_FIELDS: &[&str] = &["mName", "name"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GameDiffFormat {
+    Json,
+    Markdown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PropertyChange {
+    pub property: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryDiff {
+    pub entry: String,
+    pub class: String,
+    pub bin_file: String,
+    pub changes: Vec<PropertyChange>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameDataDiff {
+    pub old_label: String,
+    pub new_label: String,
+    pub bins_compared: usize,
+    pub changed: Vec<EntryDiff>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+// Aplatit un objet JSON en "chemin de propriété -> valeur"
+pub fn flatten(value: &Value, prefix: &str, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, child) in object {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(child, &path, out);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => match list_keys(items) {
+            Some(keys) => {
+                for (key, item) in keys.iter().zip(items) {
+                    flatten(item, &format!("{}[{}]", prefix, key), out);
+                }
+            }
+            None => {
+                for (i, item) in items.iter().enumerate() {
+                    flatten(item, &format!("{}[{}]", prefix, i), out);
+                }
+            }
+        },
+        _ => {
+            out.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+// Noms des éléments d'une liste d'objets s'ils sont tous nommés et uniques
+fn list_keys(items: &[Value]) -> Option<Vec<String>> {
+    let keys: Vec<String> = items
+        .iter()
+        .map(|item| {
+            LIST_KEY_FIELDS
+                .iter()
+                .find_map(|field| item.get(field).and_then(Value::as_str))
+                .map(str::to_string)
+        })
+        .collect::<Option<_>>()?;
+    let unique: BTreeSet<&String> = keys.iter().collect();
+    (unique.len() == keys.len()).then_some(keys)
+}
+
+fn diff_properties(old: &BTreeMap<String, Value>, new: &BTreeMap<String, Value>) -> Vec<PropertyChange> {
+    let properties: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    properties
+        .into_iter()
+        .filter_map(|property| {
+            let (before, after) = (old.get(property), new.get(property));
+            (before != after).then(|| PropertyChange {
+                property: property.clone(),
+                old: before.cloned(),
+                new: after.cloned(),
+            })
+        })
+        .collect()
+}
+
+fn bin_entries(bin: Option<&PropBin>, table: &HashTable) -> BTreeMap<String, Value> {
+    match bin.map(|bin| bin.to_json(table)) {
+        Some(Value::Object(mut json)) => match json.remove("entries") {
+            Some(Value::Object(entries)) => entries.into_iter().collect(),
+            _ => BTreeMap::new(),
+        },
+        _ => BTreeMap::new(),
+    }
+}
+
+// Compare les entrées de deux versions d'un même fichier .bin
+pub fn diff_bins(bin_file: &str, old: Option<&PropBin>, new: Option<&PropBin>, table: &HashTable, diff: &mut GameDataDiff) {
+    let old_entries = bin_entries(old, table);
+    let new_entries = bin_entries(new, table);
+
+    for (name, value) in &new_entries {
+        let Some(previous) = old_entries.get(name) else {
+            diff.added.push(name.clone());
+            continue;
+        };
+        if previous == value {
+            continue;
+        }
+        let (mut before, mut after) = (BTreeMap::new(), BTreeMap::new());
+        flatten(previous, "", &mut before);
+        flatten(value, "", &mut after);
+        diff.changed.push(EntryDiff {
+            entry: name.clone(),
+            class: value["__type"].as_str().unwrap_or_default().to_string(),
+            bin_file: bin_file.to_string(),
+            changes: diff_properties(&before, &after),
+        });
+    }
+    diff.removed
+        .extend(old_entries.keys().filter(|name| !new_entries.contains_key(*name)).cloned());
+}
+
+fn relative_path(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn scoped_wads(dir: &Path, scopes: &[String]) -> Result<BTreeMap<String, PathBuf>, String> {
+    Ok(wad::find_wads(dir)?
+        .into_iter()
+        .map(|path| (relative_path(&path, dir), path))
+        // Les WAD localisés ne contiennent que des voix et textures traduites
+        .filter(|(relative, _)| wad::wad_locale(relative).is_none())
+        .filter(|(relative, _)| {
+            let lower = relative.to_lowercase();
+            scopes.is_empty() || scopes.iter().any(|scope| lower.starts_with(&scope.to_lowercase()))
+        })
+        .collect())
+}
+
+fn open_wad(path: Option<&PathBuf>, relative: &str) -> Result<Option<Wad>, String> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let mut wad = Wad::open(path)?;
+    wad.load_subchunk_toc(relative)?;
+    Ok(Some(wad))
+}
+
+// Lit une entrée si c'est un fichier de propriétés, les fichiers illisibles sont ignorés
+fn load_bin(wad: &Wad, entry: &WadEntry, bin_file: &str) -> Result<Option<PropBin>, String> {
+    let data = wad.read(entry)?;
+    if !data.starts_with(PROP_MAGIC) && !data.starts_with(PTCH_MAGIC) {
+        return Ok(None);
+    }
+    match PropBin::parse(&data) {
+        Ok(bin) => Ok(Some(bin)),
+        Err(e) => {
+            println!("⚠️ {} ignoré : {}", bin_file, e);
+            Ok(None)
+        }
+    }
+}
+
+// Compare les .bin des WAD retenus dans les dossiers de deux patchs
+pub fn diff_game_data(
+    old_dir: &Path,
+    new_dir: &Path,
+    scopes: &[String],
+    table: &HashTable,
+) -> Result<GameDataDiff, String> {
+    let old_wads = scoped_wads(old_dir, scopes)?;
+    let new_wads = scoped_wads(new_dir, scopes)?;
+    let wad_paths: BTreeSet<&String> = old_wads.keys().chain(new_wads.keys()).collect();
+
+    let mut diff = GameDataDiff::default();
+    for relative in wad_paths {
+        let old_wad = open_wad(old_wads.get(relative), relative)?;
+        let new_wad = open_wad(new_wads.get(relative), relative)?;
+
+        let mut pairs: BTreeMap<u64, (Option<&WadEntry>, Option<&WadEntry>)> = BTreeMap::new();
+        for entry in old_wad.iter().flat_map(|wad| &wad.entries) {
+            pairs.entry(entry.path_hash).or_default().0 = Some(entry);
+        }
+        for entry in new_wad.iter().flat_map(|wad| &wad.entries) {
+            pairs.entry(entry.path_hash).or_default().1 = Some(entry);
+        }
+
+        for (hash, (old_entry, new_entry)) in pairs {
+            // Contenu identique d'après la somme de contrôle du WAD : rien à décompresser
+            if let (Some(a), Some(b)) = (old_entry, new_entry) {
+                if a.checksum != 0 && a.checksum == b.checksum && a.size == b.size {
+                    continue;
+                }
+            }
+            let name = table.get(hash);
+            if name.is_some_and(|name| !name.to_lowercase().ends_with(".bin")) {
+                continue;
+            }
+            let bin_file = name.map(str::to_string).unwrap_or_else(|| format!("{:016x}.bin", hash));
+
+            let old_bin = match (&old_wad, old_entry) {
+                (Some(wad), Some(entry)) => load_bin(wad, entry, &bin_file)?,
+                _ => None,
+            };
+            let new_bin = match (&new_wad, new_entry) {
+                (Some(wad), Some(entry)) => load_bin(wad, entry, &bin_file)?,
+                _ => None,
+            };
+            if old_bin.is_none() && new_bin.is_none() {
+                continue;
+            }
+            diff.bins_compared += 1;
+            diff_bins(&bin_file, old_bin.as_ref(), new_bin.as_ref(), table, &mut diff);
+        }
+    }
+    Ok(diff)
+}
+
+// Les flottants sont stockés en f32 : on les affiche avec leur précision d'origine
+fn format_value(value: Option<&Value>) -> String {
+    match value {
+        None => "—".to_string(),
+        Some(Value::Number(number)) if number.is_f64() => (number.as_f64().unwrap_or_default() as f32).to_string(),
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(items)) => format!(
+            "[{}]",
+            items.iter().map(|item| format_value(Some(item))).collect::<Vec<_>>().join(", ")
+        ),
+        Some(other) => other.to_string(),
+    }
+}
+
+pub fn to_markdown(diff: &GameDataDiff) -> String {
+    let mut out = format!("# Données de jeu : {} → {}\n\n", diff.old_label, diff.new_label);
+    out.push_str(&format!(
+        "{} fichiers .bin comparés, {} entrées modifiées, {} ajoutées, {} supprimées.\n",
+        diff.bins_compared,
+        diff.changed.len(),
+        diff.added.len(),
+        diff.removed.len()
+    ));
+
+    if !diff.changed.is_empty() {
+        out.push_str("\n## Modifications\n");
+        for entry in &diff.changed {
+            out.push_str(&format!("\n### {}\n\n", entry.entry));
+            for change in &entry.changes {
+                out.push_str(&format!(
+                    "- `{}` : {} → {}\n",
+                    change.property,
+                    format_value(change.old.as_ref()),
+                    format_value(change.new.as_ref())
+                ));
+            }
+        }
+    }
+    for (title, names) in [("Entrées ajoutées", &diff.added), ("Entrées supprimées", &diff.removed)] {
+        if names.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n## {}\n\n", title));
+        for name in names {
+            out.push_str(&format!("- {}\n", name));
+        }
+    }
+    out
+}
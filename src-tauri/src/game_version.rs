@@ -0,0 +1,185 @@
+// Version réelle du jeu lue dans les fichiers téléchargés (ressource de version de l'exécutable ou métadonnées du client)
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub const GAME_EXECUTABLE: &str = "League of Legends.exe";
+pub const CONTENT_METADATA: &str = "content-metadata.json";
+pub const VERSION_CACHE_FILE_NAME: &str = "game_versions.json";
+
+const RT_VERSION: u32 = 16;
+const RESOURCE_DIRECTORY_INDEX: usize = 2;
+const VS_FIXEDFILEINFO_SIGNATURE: u32 = 0xFEEF_04BD;
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, String> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| "Exécutable tronqué".to_string())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| "Exécutable tronqué".to_string())
+}
+
+// Sections (adresse virtuelle, taille, position dans le fichier) pour convertir les RVA
+struct Sections(Vec<(u32, u32, u32)>);
+
+impl Sections {
+    fn offset(&self, rva: u32) -> Result<usize, String> {
+        // Une section dont la fin ou la position dans le fichier dépasse 32 bits ne contient rien
+        self.0
+            .iter()
+            .find_map(|(address, size, raw)| {
+                let end = address.checked_add(*size)?;
+                (rva >= *address && rva < end).then(|| (rva - address).checked_add(*raw))?
+            })
+            .map(|offset| offset as usize)
+            .ok_or_else(|| format!("Adresse {:#x} hors des sections", rva))
+    }
+}
+
+// Première entrée d'un répertoire de ressources, ou celle portant l'identifiant demandé
+fn resource_entry(data: &[u8], directory: usize, id: Option<u32>) -> Result<u32, String> {
+    let named = read_u16(data, directory + 12)? as usize;
+    let ids = read_u16(data, directory + 14)? as usize;
+    for i in 0..named + ids {
+        let entry = directory + 16 + i * 8;
+        let matches = match id {
+            None => true,
+            Some(id) => i >= named && read_u32(data, entry)? == id,
+        };
+        if matches {
+            return read_u32(data, entry + 4);
+        }
+    }
+    Err("Ressource de version absente de l'exécutable".to_string())
+}
+
+// Version de fichier (VS_FIXEDFILEINFO) d'un exécutable PE, ex : "14.17.612.7771"
+pub fn pe_file_version(data: &[u8]) -> Result<String, String> {
+    if !data.starts_with(b"MZ") {
+        return Err("Exécutable invalide (signature MZ)".to_string());
+    }
+    let pe = read_u32(data, 0x3C)? as usize;
+    if data.get(pe..pe + 4) != Some(b"PE\0\0") {
+        return Err("Exécutable invalide (signature PE)".to_string());
+    }
+    let section_count = read_u16(data, pe + 6)? as usize;
+    let optional = pe + 24;
+    let optional_size = read_u16(data, pe + 20)? as usize;
+    let data_directories = match read_u16(data, optional)? {
+        0x10B => optional + 96,
+        0x20B => optional + 112,
+        other => return Err(format!("En-tête optionnel PE inconnu : {:#x}", other)),
+    };
+    let resource_rva = read_u32(data, data_directories + RESOURCE_DIRECTORY_INDEX * 8)?;
+    if resource_rva == 0 {
+        return Err("L'exécutable ne contient pas de ressources".to_string());
+    }
+
+    let table = optional + optional_size;
+    let sections = Sections(
+        (0..section_count)
+            .map(|i| {
+                let header = table + i * 40;
+                let virtual_size = read_u32(data, header + 8)?;
+                let raw_size = read_u32(data, header + 16)?;
+                Ok((read_u32(data, header + 12)?, virtual_size.max(raw_size), read_u32(data, header + 20)?))
+            })
+            .collect::<Result<Vec<_>, String>>()?,
+    );
+
+    // Type RT_VERSION -> premier nom -> première langue -> données
+    let root = sections.offset(resource_rva)?;
+    let mut directory = root;
+    let mut id = Some(RT_VERSION);
+    let mut entry = 0;
+    for _ in 0..3 {
+        entry = resource_entry(data, directory, id)?;
+        if entry & 0x8000_0000 == 0 {
+            break;
+        }
+        directory = root + (entry & 0x7FFF_FFFF) as usize;
+        id = None;
+    }
+    if entry & 0x8000_0000 != 0 {
+        return Err("Ressource de version mal formée".to_string());
+    }
+    let data_entry = root + entry as usize;
+    let start = sections.offset(read_u32(data, data_entry)?)?;
+    let size = read_u32(data, data_entry + 4)? as usize;
+    let info = data.get(start..start + size).ok_or("Ressource de version tronquée")?;
+
+    let fixed = (0..info.len().saturating_sub(16))
+        .step_by(4)
+        .find(|i| read_u32(info, *i).ok() == Some(VS_FIXEDFILEINFO_SIGNATURE))
+        .ok_or("Informations de version absentes")?;
+    let (high, low) = (read_u32(info, fixed + 8)?, read_u32(info, fixed + 12)?);
+    Ok(format!("{}.{}.{}.{}", high >> 16, high & 0xFFFF, low >> 16, low & 0xFFFF))
+}
+
+// content-metadata.json : {"version": "14.17.612.7771+branch.releases-14-17..."}
+pub fn metadata_version(text: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(text).ok()?;
+    let version = json.get("version")?.as_str()?;
+    let build = version.split('+').next().unwrap_or(version).trim();
+    (!build.is_empty()).then(|| build.to_string())
+}
+
+fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir).ok()?.flatten().map(|e| e.path()).collect();
+    entries.sort();
+    if let Some(file) = entries
+        .iter()
+        .find(|p| p.is_file() && p.file_name().is_some_and(|n| n.to_string_lossy().eq_ignore_ascii_case(name)))
+    {
+        return Some(file.clone());
+    }
+    entries.iter().filter(|p| p.is_dir()).find_map(|p| find_file(p, name))
+}
+
+// Cherche l'exécutable du jeu, puis les métadonnées du client, dans le dossier d'un patch
+pub fn detect_game_version(dir: &Path) -> Result<String, String> {
+    let mut errors = Vec::new();
+    if let Some(exe) = find_file(dir, GAME_EXECUTABLE) {
+        let data = std::fs::read(&exe).map_err(|e| format!("Impossible de lire {}: {}", exe.display(), e))?;
+        match pe_file_version(&data) {
+            Ok(version) => return Ok(version),
+            Err(e) => errors.push(format!("{}: {}", GAME_EXECUTABLE, e)),
+        }
+    }
+    if let Some(metadata) = find_file(dir, CONTENT_METADATA) {
+        let text = std::fs::read_to_string(&metadata).map_err(|e| format!("Impossible de lire {}: {}", metadata.display(), e))?;
+        match metadata_version(&text) {
+            Some(version) => return Ok(version),
+            None => errors.push(format!("{}: champ version absent", CONTENT_METADATA)),
+        }
+    }
+    if errors.is_empty() {
+        Err(format!("Ni {} ni {} dans {}", GAME_EXECUTABLE, CONTENT_METADATA, dir.display()))
+    } else {
+        Err(errors.join(", "))
+    }
+}
+
+// Numéro de patch affiché à partir de la version complète ("14.17.612.7771" -> "14.17")
+pub fn patch_number(build: &str) -> String {
+    build.split('.').take(2).collect::<Vec<_>>().join(".")
+}
+
+// Versions détectées par URL de manifest, conservées entre deux sessions
+pub fn load_versions(path: &Path) -> HashMap<String, String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_versions(path: &Path, versions: &HashMap<String, String>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(versions).map_err(|e| e.to_string())?;
+    std::fs::write(path, content).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))
+}
@@ -0,0 +1,164 @@
+// Tables de hash communautaires ("<hash> <chemin>") pour retrouver les noms des entrées WAD et des champs bin
+use crate::stringtable::rst_key_hash;
+use crate::wad::{find_wads, Wad};
+use serde::Serialize;
+use std::path::Path;
+
+pub const HASH_CACHE_FILE_NAME: &str = "hashes.game.txt";
+pub const DEFAULT_HASH_LIST_URLS: &[&str] = &[
+    "https://raw.communitydragon.org/data/hashes/lol/hashes.game.txt.0",
+    "https://raw.communitydragon.org/data/hashes/lol/hashes.game.txt.1",
+    "https://raw.communitydragon.org/data/hashes/lol/hashes.binentries.txt",
+    "https://raw.communitydragon.org/data/hashes/lol/hashes.binfields.txt",
+    "https://raw.communitydragon.org/data/hashes/lol/hashes.bintypes.txt",
+    "https://raw.communitydragon.org/data/hashes/lol/hashes.binhashes.txt",
+    "https://raw.communitydragon.org/data/hashes/lol/hashes.rst.xxh64.txt",
+    "https://raw.communitydragon.org/data/hashes/lol/hashes.rst.xxh3.txt",
+];
+
+// Tous les chemins dans une seule chaîne et un index trié par hash : quelques millions de lignes restent compactes
+#[derive(Debug, Default)]
+pub struct HashTable {
+    names: String,
+    index: Vec<(u64, u32, u32)>,
+}
+
+impl HashTable {
+    pub fn parse(text: &str) -> HashTable {
+        let mut table = HashTable::default();
+        table.extend(text);
+        table
+    }
+
+    pub fn load_file(path: &Path) -> Result<HashTable, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+        Ok(HashTable::parse(&text))
+    }
+
+    // Ajoute les lignes valides, les lignes suivantes remplacent les précédentes pour un même hash
+    pub fn extend(&mut self, text: &str) {
+        for line in text.lines() {
+            let Some((hash, path)) = line.trim().split_once(' ') else {
+                continue;
+            };
+            let (Ok(hash), path) = (u64::from_str_radix(hash, 16), path.trim()) else {
+                continue;
+            };
+            if path.is_empty() {
+                continue;
+            }
+            self.index.push((hash, self.names.len() as u32, path.len() as u32));
+            self.names.push_str(path);
+        }
+        // Tri stable puis dédoublonnage en gardant la dernière définition
+        self.index.sort_by_key(|(hash, _, _)| *hash);
+        let mut deduped: Vec<(u64, u32, u32)> = Vec::with_capacity(self.index.len());
+        for entry in self.index.drain(..) {
+            match deduped.last_mut() {
+                Some(last) if last.0 == entry.0 => *last = entry,
+                _ => deduped.push(entry),
+            }
+        }
+        self.index = deduped;
+    }
+
+    pub fn get(&self, hash: u64) -> Option<&str> {
+        self.index
+            .binary_search_by_key(&hash, |(h, _, _)| *h)
+            .ok()
+            .map(|i| {
+                let (_, start, len) = self.index[i];
+                &self.names[start as usize..(start + len) as usize]
+            })
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+}
+
+// Contenu d'une liste depuis une URL ou un fichier local
+pub async fn fetch_hash_list(source: &str) -> Result<String, String> {
+    let text = if source.starts_with("http://") || source.starts_with("https://") {
+        let bytes = crate::downloader::fetch_bytes(source).await?;
+        String::from_utf8(bytes).map_err(|_| format!("Liste de hash invalide (UTF-8) : {}", source))?
+    } else {
+        std::fs::read_to_string(source).map_err(|e| format!("Impossible de lire {}: {}", source, e))?
+    };
+    Ok(if is_rst_hash_list(source) { rst_hash_list(&text) } else { text })
+}
+
+// Listes des clés de stringtable ("hashes.rst.xxh64.txt", "hashes.rst.xxh3.txt")
+pub fn is_rst_hash_list(source: &str) -> bool {
+    let name = source.rsplit(['/', '\\']).next().unwrap_or(source).to_lowercase();
+    name.starts_with("hashes.rst")
+}
+
+// Les stringtables ne gardent que 38 à 40 bits du hash, calculé en xxh64 ou en xxh3 selon leur version :
+// chaque clé est réécrite avec le hash tronqué de chaque version pour être trouvée quel que soit le fichier
+pub fn rst_hash_list(text: &str) -> String {
+    let mut lines = String::new();
+    for line in text.lines() {
+        let Some((_, key)) = line.trim().split_once(' ') else {
+            continue;
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            continue;
+        }
+        for version in [3, 4, 5] {
+            if let Ok(hash) = rst_key_hash(key, version) {
+                lines.push_str(&format!("{:010x} {}\n", hash, key));
+            }
+        }
+    }
+    lines
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WadHashReport {
+    pub wad_path: String,
+    pub entries: usize,
+    pub unknown: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnknownHashReport {
+    pub entries: usize,
+    pub unknown: usize,
+    pub wads: Vec<WadHashReport>,
+}
+
+// Hash encore inconnus dans tous les WAD du dossier d'un patch
+pub fn unknown_hashes(output_dir: &Path, table: &HashTable) -> Result<UnknownHashReport, String> {
+    let wads = find_wads(output_dir)?;
+
+    let mut report = UnknownHashReport::default();
+    for path in wads {
+        let wad = Wad::open(&path)?;
+        let unknown: Vec<String> = wad
+            .entries
+            .iter()
+            .filter(|e| table.get(e.path_hash).is_none())
+            .map(|e| format!("{:016x}", e.path_hash))
+            .collect();
+        report.entries += wad.entries.len();
+        report.unknown += unknown.len();
+        report.wads.push(WadHashReport {
+            wad_path: path
+                .strip_prefix(output_dir)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/"),
+            entries: wad.entries.len(),
+            unknown,
+        });
+    }
+    Ok(report)
+}
@@ -0,0 +1,234 @@
+// Historique de tous les manifests vus dans le catalogue (base SQLite locale) et recherche
+use crate::patch_calendar::PatchVersion;
+use crate::realms::Realm;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub const HISTORY_DB_FILE_NAME: &str = "manifest_history.sqlite";
+const DEFAULT_SEARCH_LIMIT: usize = 500;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS manifests (
+    url TEXT PRIMARY KEY,
+    realm TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    date TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    version TEXT NOT NULL,
+    patch_version TEXT,
+    build_version TEXT,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS manifests_realm_date ON manifests (realm, date);
+";
+
+const COLUMNS: &str = "url, realm, hash, date, size_bytes, version, patch_version, build_version, first_seen, last_seen";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestRecord {
+    pub url: String,
+    pub realm: String,
+    pub hash: String,
+    pub date: String,
+    pub size_bytes: u64,
+    // Version affichée dans le catalogue ("Patch 24.17", "Version HASH")
+    pub version: String,
+    pub patch_version: Option<PatchVersion>,
+    pub build_version: Option<String>,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+impl ManifestRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let patch_version: Option<String> = row.get(6)?;
+        Ok(Self {
+            url: row.get(0)?,
+            realm: row.get(1)?,
+            hash: row.get(2)?,
+            date: row.get(3)?,
+            size_bytes: row.get::<_, i64>(4)? as u64,
+            version: row.get(5)?,
+            patch_version: patch_version.and_then(|v| v.parse().ok()),
+            build_version: row.get(7)?,
+            first_seen: row.get(8)?,
+            last_seen: row.get(9)?,
+        })
+    }
+}
+
+// Critères de recherche, tous facultatifs ; les bornes sont incluses
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryQuery {
+    pub realm: Option<String>,
+    pub from_version: Option<PatchVersion>,
+    pub to_version: Option<PatchVersion>,
+    // Dates "AAAA-MM-JJ"
+    pub from_date: Option<String>,
+    pub to_date: Option<String>,
+    // Recherche dans l'URL, le hash, le serveur et les versions
+    pub text: Option<String>,
+    pub limit: Option<usize>,
+}
+
+pub struct ManifestHistory {
+    connection: Connection,
+}
+
+fn sql_error(e: rusqlite::Error) -> String {
+    format!("Erreur de la base d'historique : {}", e)
+}
+
+impl ManifestHistory {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+        }
+        Self::init(Connection::open(path).map_err(sql_error)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, String> {
+        Self::init(Connection::open_in_memory().map_err(sql_error)?)
+    }
+
+    fn init(connection: Connection) -> Result<Self, String> {
+        connection.execute_batch(SCHEMA).map_err(sql_error)?;
+        Ok(Self { connection })
+    }
+
+    // Ajoute ou met à jour les manifests vus à `seen_at`, renvoie le nombre de nouveaux
+    pub fn record(&mut self, manifests: &[ManifestRecord], seen_at: &str) -> Result<usize, String> {
+        let transaction = self.connection.transaction().map_err(sql_error)?;
+        let mut added = 0;
+        {
+            let mut exists = transaction
+                .prepare("SELECT 1 FROM manifests WHERE url = ?1")
+                .map_err(sql_error)?;
+            let mut upsert = transaction
+                .prepare(
+                    "INSERT INTO manifests (url, realm, hash, date, size_bytes, version, patch_version, build_version, first_seen, last_seen)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)
+                     ON CONFLICT(url) DO UPDATE SET
+                        realm = excluded.realm,
+                        date = excluded.date,
+                        size_bytes = excluded.size_bytes,
+                        version = excluded.version,
+                        patch_version = COALESCE(excluded.patch_version, patch_version),
+                        build_version = COALESCE(excluded.build_version, build_version),
+                        last_seen = excluded.last_seen",
+                )
+                .map_err(sql_error)?;
+            for manifest in manifests {
+                if !exists.exists(params![manifest.url]).map_err(sql_error)? {
+                    added += 1;
+                }
+                upsert
+                    .execute(params![
+                        manifest.url,
+                        manifest.realm,
+                        manifest.hash,
+                        manifest.date,
+                        manifest.size_bytes as i64,
+                        manifest.version,
+                        manifest.patch_version.map(|v| v.to_string()),
+                        manifest.build_version,
+                        seen_at,
+                    ])
+                    .map_err(sql_error)?;
+            }
+        }
+        transaction.commit().map_err(sql_error)?;
+        Ok(added)
+    }
+
+    // Version lue dans les fichiers d'un patch téléchargé
+    pub fn set_build_version(&self, url: &str, build: &str, patch_version: Option<PatchVersion>) -> Result<(), String> {
+        self.connection
+            .execute(
+                "UPDATE manifests SET build_version = ?2, patch_version = COALESCE(?3, patch_version) WHERE url = ?1",
+                params![url, build, patch_version.map(|v| v.to_string())],
+            )
+            .map(|_| ())
+            .map_err(sql_error)
+    }
+
+    pub fn search(&self, query: &HistoryQuery) -> Result<Vec<ManifestRecord>, String> {
+        let mut sql = format!("SELECT {} FROM manifests WHERE 1 = 1", COLUMNS);
+        let mut values: Vec<String> = Vec::new();
+        let mut bind = |sql: &mut String, clause: &str, value: String| {
+            values.push(value);
+            sql.push_str(&clause.replace('?', &format!("?{}", values.len())));
+        };
+        if let Some(realm) = query.realm.as_deref().filter(|r| !r.trim().is_empty()) {
+            bind(&mut sql, " AND realm = ? COLLATE NOCASE", Realm::parse(realm).id);
+        }
+        if let Some(from) = &query.from_date {
+            bind(&mut sql, " AND substr(date, 1, 10) >= ?", from.trim().to_string());
+        }
+        if let Some(to) = &query.to_date {
+            bind(&mut sql, " AND substr(date, 1, 10) <= ?", to.trim().to_string());
+        }
+        if let Some(text) = query.text.as_deref().filter(|t| !t.trim().is_empty()) {
+            let pattern = format!("%{}%", text.trim());
+            bind(
+                &mut sql,
+                " AND (url LIKE ? OR hash LIKE ? OR realm LIKE ? OR version LIKE ? OR IFNULL(patch_version, '') LIKE ? OR IFNULL(build_version, '') LIKE ?)",
+                pattern,
+            );
+        }
+        sql.push_str(" ORDER BY date DESC, realm");
+
+        let mut statement = self.connection.prepare(&sql).map_err(sql_error)?;
+        let rows = statement
+            .query_map(rusqlite::params_from_iter(values.iter()), ManifestRecord::from_row)
+            .map_err(sql_error)?;
+        let mut records = Vec::new();
+        for record in rows {
+            let record = record.map_err(sql_error)?;
+            // Comparaison des versions de patch en Rust : "24.9" < "24.17"
+            let in_range = match (query.from_version, query.to_version) {
+                (None, None) => true,
+                (from, to) => record.patch_version.is_some_and(|version| {
+                    from.map_or(true, |from| version >= from) && to.map_or(true, |to| version <= to)
+                }),
+            };
+            if in_range {
+                records.push(record);
+            }
+            if records.len() >= query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT) {
+                break;
+            }
+        }
+        Ok(records)
+    }
+
+    // Dernier manifest publié sur le serveur à cette date ("ce qui était en jeu sur EUW1 le 2024-03-10")
+    pub fn live_at(&self, realm: &str, date: &str) -> Result<Option<ManifestRecord>, String> {
+        self.connection
+            .query_row(
+                &format!(
+                    "SELECT {} FROM manifests WHERE realm = ?1 COLLATE NOCASE AND substr(date, 1, 10) <= ?2 ORDER BY date DESC LIMIT 1",
+                    COLUMNS
+                ),
+                params![Realm::parse(realm).id, date.trim()],
+                ManifestRecord::from_row,
+            )
+            .optional()
+            .map_err(sql_error)
+    }
+
+    pub fn len(&self) -> Result<usize, String> {
+        self.connection
+            .query_row("SELECT COUNT(*) FROM manifests", [], |row| row.get::<_, i64>(0))
+            .map(|count| count as usize)
+            .map_err(sql_error)
+    }
+
+    pub fn is_empty(&self) -> Result<bool, String> {
+        Ok(self.len()? == 0)
+    }
+}
@@ -0,0 +1,123 @@
+// Journal des fichiers et dossiers créés par une tâche, pour supprimer exactement ceux-là à l'annulation
+use crate::downloader::PARTIAL_SUFFIX;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Une ligne JSON par chemin, ajoutée avant la création : un arrêt brutal ne perd aucune entrée
+#[derive(Debug)]
+pub struct FileJournal {
+    path: PathBuf,
+    file: Mutex<std::fs::File>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupReport {
+    pub removed_files: usize,
+    pub removed_dirs: usize,
+    // Dossiers non vides (fichiers ajoutés par l'utilisateur) ou suppressions en échec
+    pub kept: Vec<String>,
+}
+
+impl FileJournal {
+    // Ouvre le journal en ajout : une tâche reprise complète le journal de ses sessions précédentes
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Impossible d'ouvrir le journal {}: {}", path.display(), e))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Note un chemin sur le point d'être créé ; un chemin déjà présent appartient à l'utilisateur et n'est pas noté
+    pub fn record_new(&self, path: &Path) -> Result<bool, String> {
+        if path.symlink_metadata().is_ok() {
+            return Ok(false);
+        }
+        let line = serde_json::to_string(&path.to_string_lossy()).map_err(|e| e.to_string())?;
+        let mut file = self.file.lock().map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| format!("Erreur d'écriture du journal {}: {}", self.path.display(), e))?;
+        Ok(true)
+    }
+
+    // Équivalent de `create_dir_all` qui note chaque dossier manquant, du plus haut au plus profond
+    pub fn create_dir_all(&self, dir: &Path) -> Result<(), String> {
+        let missing: Vec<&Path> = dir.ancestors().take_while(|ancestor| !ancestor.exists()).collect();
+        for ancestor in missing.iter().rev() {
+            self.record_new(ancestor)?;
+        }
+        std::fs::create_dir_all(dir).map_err(|e| format!("Impossible de créer {}: {}", dir.display(), e))
+    }
+}
+
+// Chemins notés dans un journal, dans l'ordre de création ; un journal absent est vide
+pub fn read_journal(path: &Path) -> Result<Vec<PathBuf>, String> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Impossible de lire le journal {}: {}", path.display(), e)),
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<String>(line).ok())
+        .map(PathBuf::from)
+        .collect())
+}
+
+// Supprime les fichiers temporaires de téléchargement notés dans le journal, sans toucher au reste
+pub fn remove_recorded_partials(journal_path: &Path) -> Result<usize, String> {
+    let mut removed = 0;
+    for path in read_journal(journal_path)? {
+        if !path.to_string_lossy().ends_with(PARTIAL_SUFFIX) || !path.is_file() {
+            continue;
+        }
+        std::fs::remove_file(&path).map_err(|e| format!("Impossible de supprimer {}: {}", path.display(), e))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+// Supprime les fichiers puis les dossiers notés, du plus récent au plus ancien, puis le journal lui-même.
+// Les dossiers qui contiennent encore autre chose sont conservés.
+pub fn remove_created(journal_path: &Path) -> Result<CleanupReport, String> {
+    let mut report = CleanupReport::default();
+    let mut seen = HashSet::new();
+    for path in read_journal(journal_path)?.into_iter().rev() {
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+        let Ok(metadata) = path.symlink_metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            match std::fs::remove_dir(&path) {
+                Ok(_) => report.removed_dirs += 1,
+                Err(_) => report.kept.push(path.to_string_lossy().to_string()),
+            }
+        } else {
+            match std::fs::remove_file(&path) {
+                Ok(_) => report.removed_files += 1,
+                Err(_) => report.kept.push(path.to_string_lossy().to_string()),
+            }
+        }
+    }
+    match std::fs::remove_file(journal_path) {
+        Ok(_) => Ok(report),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(report),
+        Err(e) => Err(format!("Impossible de supprimer le journal {}: {}", journal_path.display(), e)),
+    }
+}
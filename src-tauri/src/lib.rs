@@ -1,6 +1,37 @@
+pub mod archive;
+pub mod audio;
+pub mod catalogue;
 mod commands;
+pub mod diff;
+pub mod disk_space;
+pub mod downloader;
+pub mod error;
+pub mod game_diff;
+pub mod game_version;
+pub mod hashes;
+pub mod history;
+pub mod journal;
+#[cfg(any(test, feature = "test-support"))]
+pub mod mock_cdn;
+pub mod model;
+pub mod output_template;
+pub mod patch_calendar;
+pub mod products;
+pub mod prop_bin;
+pub mod realms;
+pub mod rman;
+pub mod settings;
+pub mod stall;
+pub mod stringtable;
+pub mod tasks;
+pub mod texture;
+pub mod wad;
+pub mod watcher;
+pub mod webhooks;
 
 use commands::AppState;
+use settings::SettingsStore;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -8,14 +39,56 @@ pub fn run() {
     .manage(AppState::default())
     .invoke_handler(tauri::generate_handler![
       commands::fetch_manifests,
+      commands::list_realms,
+      commands::filter_manifests,
       commands::start_download,
       commands::pause_download,
       commands::resume_download,
       commands::cancel_download,
       commands::get_download_progress,
+      commands::list_downloads,
+      commands::remove_download,
+      commands::clear_finished,
       commands::select_download_folder,
+      commands::verify_download,
+      commands::diff_manifests,
+      commands::diff_game_data,
+      commands::extract_string_tables,
+      commands::diff_string_tables,
+      commands::detect_game_version,
+      commands::run_watch_now,
+      commands::test_webhook,
+      commands::get_webhook_log,
+      commands::search_manifest_history,
+      commands::manifest_live_at,
+      commands::export_patch_archive,
+      commands::list_wad_entries,
+      commands::extract_wad_entries,
+      commands::update_hash_table,
+      commands::unknown_wad_hashes,
+      commands::convert_bin_to_json,
+      commands::convert_wad_bin_to_json,
+      commands::convert_texture_to_png,
+      commands::convert_wad_texture_to_png,
+      commands::convert_task_textures,
+      commands::list_audio_streams,
+      commands::extract_audio_streams,
+      commands::export_model_gltf,
+      commands::get_settings,
+      commands::save_settings,
     ])
     .setup(|app| {
+      let settings_path = app.path().app_config_dir()?.join(settings::SETTINGS_FILE_NAME);
+      let store = SettingsStore::load(settings_path);
+      app.manage(store);
+
+      // Fichiers temporaires d'une session interrompue (fermeture, plantage)
+      tauri::async_runtime::spawn_blocking(commands::sweep_partial_downloads);
+      tauri::async_runtime::spawn(commands::load_cached_hash_table(app.handle().clone()));
+      tauri::async_runtime::spawn(commands::load_cached_game_versions(app.handle().clone()));
+      tauri::async_runtime::spawn(commands::open_manifest_history(app.handle().clone()));
+      tauri::async_runtime::spawn(commands::run_watcher(app.handle().clone()));
+
       if cfg!(debug_assertions) {
         app.handle().plugin(
           tauri_plugin_log::Builder::default()
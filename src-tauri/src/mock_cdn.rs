@@ -0,0 +1,539 @@
+// Serveur CDN local pour les tests : génère des manifestes RMAN, des bundles et un catalogue CSV
+use crate::products::Product;
+use crate::rman::{chunk_hash, HASH_TYPE_SHA256, RMAN_HEADER_SIZE, RMAN_MAGIC};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+pub const DEFAULT_CHUNK_SIZE: usize = 16 * 1024;
+const CHUNKS_PER_BUNDLE: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct MockFile {
+    pub path: String,
+    pub data: Vec<u8>,
+    pub languages: Vec<String>,
+}
+
+impl MockFile {
+    pub fn new(path: &str, data: Vec<u8>) -> Self {
+        Self {
+            path: path.to_string(),
+            data,
+            languages: Vec::new(),
+        }
+    }
+
+    pub fn with_languages(mut self, languages: &[&str]) -> Self {
+        self.languages = languages.iter().map(|l| l.to_string()).collect();
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockRelease {
+    pub product: Product,
+    pub realm: String,
+    pub date: String,
+    pub files: Vec<MockFile>,
+}
+
+impl MockRelease {
+    pub fn new(realm: &str, date: &str, files: Vec<MockFile>) -> Self {
+        Self {
+            product: Product::Lol,
+            realm: realm.to_string(),
+            date: date.to_string(),
+            files,
+        }
+    }
+
+    pub fn with_product(mut self, product: Product) -> Self {
+        self.product = product;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PublishedManifest {
+    pub id: u64,
+    pub url: String,
+    pub size_bytes: u64,
+}
+
+type Routes = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+// Bundle en cours de construction : chunks (id, taille compressée, taille décompressée)
+struct MockBundle {
+    id: u64,
+    data: Vec<u8>,
+    chunks: Vec<(u64, u32, u32)>,
+}
+
+pub struct MockCdn {
+    addr: SocketAddr,
+    routes: Routes,
+    requests: Arc<Mutex<Vec<String>>>,
+    catalogue: Mutex<Vec<String>>,
+    next_id: AtomicU64,
+    chunk_size: usize,
+    hash_type: u8,
+    server: JoinHandle<()>,
+}
+
+impl MockCdn {
+    pub async fn start() -> std::io::Result<Self> {
+        Self::start_with(DEFAULT_CHUNK_SIZE, HASH_TYPE_SHA256).await
+    }
+
+    pub async fn start_with(chunk_size: usize, hash_type: u8) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let routes: Routes = Arc::new(Mutex::new(HashMap::new()));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let server_routes = routes.clone();
+        let server_requests = requests.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let routes = server_routes.clone();
+                let requests = server_requests.clone();
+                tokio::spawn(async move {
+                    let _ = handle_connection(stream, routes, requests).await;
+                });
+            }
+        });
+
+        let cdn = Self {
+            addr,
+            routes,
+            requests,
+            catalogue: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(1),
+            chunk_size: chunk_size.max(1),
+            hash_type,
+            server,
+        };
+        cdn.write_catalogue().await;
+        Ok(cdn)
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    // Équivalent local de http://lol.secure.dyn.riotcdn.net/channels/public
+    pub fn cdn_url(&self) -> String {
+        format!("{}/channels/public", self.base_url())
+    }
+
+    pub fn catalogue_url(&self) -> String {
+        format!("{}/catalogue.csv", self.base_url())
+    }
+
+    // Chemins demandés au serveur depuis son démarrage
+    pub async fn requests(&self) -> Vec<String> {
+        self.requests.lock().await.clone()
+    }
+
+    pub async fn clear_requests(&self) {
+        self.requests.lock().await.clear();
+    }
+
+    pub async fn set_route(&self, path: &str, data: Vec<u8>) {
+        self.routes.lock().await.insert(path.to_string(), data);
+    }
+
+    pub async fn remove_route(&self, path: &str) -> Option<Vec<u8>> {
+        self.routes.lock().await.remove(path)
+    }
+
+    // Découpe les fichiers en chunks, écrit les bundles et le manifest, puis l'ajoute au catalogue
+    pub async fn publish(&self, release: &MockRelease) -> PublishedManifest {
+        let manifest_id = self.next_id();
+        let mut bundles: Vec<MockBundle> = Vec::new();
+        let mut known_chunks = HashSet::new();
+        let mut file_chunks = Vec::new();
+
+        for file in &release.files {
+            let mut ids = Vec::new();
+            for piece in file.data.chunks(self.chunk_size) {
+                let id = chunk_hash(piece, self.hash_type).unwrap_or_else(|| self.next_id());
+                ids.push(id);
+                if !known_chunks.insert(id) {
+                    continue;
+                }
+                let compressed = zstd::bulk::compress(piece, 3).expect("compression zstd");
+                if bundles.last().map_or(true, |b| b.chunks.len() >= CHUNKS_PER_BUNDLE) {
+                    bundles.push(MockBundle {
+                        id: self.next_id(),
+                        data: Vec::new(),
+                        chunks: Vec::new(),
+                    });
+                }
+                let bundle = bundles.last_mut().unwrap();
+                bundle.data.extend_from_slice(&compressed);
+                bundle.chunks.push((id, compressed.len() as u32, piece.len() as u32));
+            }
+            file_chunks.push(ids);
+        }
+
+        let body = self.build_body(release, &bundles, &file_chunks);
+        let manifest = build_rman(manifest_id, &body);
+
+        let size_bytes: u64 = release.files.iter().map(|f| f.data.len() as u64).sum();
+        let manifest_path = format!("/channels/public/releases/{:016X}.manifest", manifest_id);
+        {
+            let mut routes = self.routes.lock().await;
+            for bundle in &bundles {
+                routes.insert(format!("/channels/public/bundles/{:016X}.bundle", bundle.id), bundle.data.clone());
+            }
+            routes.insert(manifest_path.clone(), manifest);
+        }
+
+        let url = format!("{}{}", self.base_url(), manifest_path);
+        self.catalogue.lock().await.push(format!(
+            "{},{},{},{},,{}",
+            release.product, release.realm, url, release.date, size_bytes
+        ));
+        self.write_catalogue().await;
+
+        PublishedManifest {
+            id: manifest_id,
+            url,
+            size_bytes,
+        }
+    }
+
+    fn next_id(&self) -> u64 {
+        0x1000_0000_0000_0000 | self.next_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    async fn write_catalogue(&self) {
+        let mut csv = String::from("product,realm,manifest,date,unused,size\n");
+        for row in self.catalogue.lock().await.iter() {
+            csv.push_str(row);
+            csv.push('\n');
+        }
+        self.set_route("/catalogue.csv", csv.into_bytes()).await;
+    }
+
+    fn build_body(
+        &self,
+        release: &MockRelease,
+        bundles: &[MockBundle],
+        file_chunks: &[Vec<u64>],
+    ) -> Vec<u8> {
+        let bundle_tables = bundles
+            .iter()
+            .map(|bundle| {
+                let chunk_tables = bundle
+                    .chunks
+                    .iter()
+                    .map(|(chunk_id, compressed, uncompressed)| {
+                        FbTable::new()
+                            .field(0, FbValue::U64(*chunk_id))
+                            .field(1, FbValue::U32(*compressed))
+                            .field(2, FbValue::U32(*uncompressed))
+                    })
+                    .collect();
+                FbTable::new()
+                    .field(0, FbValue::U64(bundle.id))
+                    .field(1, FbValue::Tables(chunk_tables))
+            })
+            .collect();
+
+        // Langues numérotées à partir de 1, le bit (id - 1) des flags de fichier leur correspond
+        let languages: BTreeSet<&str> = release
+            .files
+            .iter()
+            .flat_map(|f| f.languages.iter().map(String::as_str))
+            .collect();
+        let language_ids: HashMap<&str, u8> = languages
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (*name, i as u8 + 1))
+            .collect();
+        let language_tables = languages
+            .iter()
+            .map(|name| {
+                FbTable::new()
+                    .field(0, FbValue::U8(language_ids[name]))
+                    .field(1, FbValue::Str(name.to_string()))
+            })
+            .collect();
+
+        let mut directories: BTreeMap<String, (u64, u64, String)> = BTreeMap::new();
+        let mut file_tables = Vec::new();
+        for (file, chunk_ids) in release.files.iter().zip(file_chunks) {
+            let mut parent_id = 0u64;
+            let mut prefix = String::new();
+            let mut parts: Vec<&str> = file.path.split('/').collect();
+            let name = parts.pop().unwrap_or_default();
+            for part in parts {
+                prefix = if prefix.is_empty() { part.to_string() } else { format!("{}/{}", prefix, part) };
+                let entry = directories
+                    .entry(prefix.clone())
+                    .or_insert_with(|| (self.next_id(), parent_id, part.to_string()));
+                parent_id = entry.0;
+            }
+
+            let locale_flags = file
+                .languages
+                .iter()
+                .fold(0u64, |flags, l| flags | 1u64 << (language_ids[l.as_str()] - 1));
+
+            file_tables.push(
+                FbTable::new()
+                    .field(0, FbValue::U64(self.next_id()))
+                    .field(1, FbValue::U64(parent_id))
+                    .field(2, FbValue::U32(file.data.len() as u32))
+                    .field(3, FbValue::Str(name.to_string()))
+                    .field(4, FbValue::U64(locale_flags))
+                    .field(7, FbValue::U64s(chunk_ids.clone()))
+                    .field(11, FbValue::U8(0)),
+            );
+        }
+
+        let directory_tables = directories
+            .into_values()
+            .map(|(id, parent_id, name)| {
+                FbTable::new()
+                    .field(0, FbValue::U64(id))
+                    .field(1, FbValue::U64(parent_id))
+                    .field(2, FbValue::Str(name))
+            })
+            .collect();
+
+        let params = FbTable::new()
+            .field(1, FbValue::U8(self.hash_type))
+            .field(4, FbValue::U32(self.chunk_size as u32));
+
+        let root = FbTable::new()
+            .field(0, FbValue::Tables(bundle_tables))
+            .field(1, FbValue::Tables(language_tables))
+            .field(2, FbValue::Tables(file_tables))
+            .field(3, FbValue::Tables(directory_tables))
+            .field(5, FbValue::Tables(vec![params]));
+
+        let mut buf = vec![0u8; 4];
+        let root_pos = root.write(&mut buf);
+        buf[0..4].copy_from_slice(&(root_pos as u32).to_le_bytes());
+        buf
+    }
+}
+
+impl Drop for MockCdn {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+// En-tête RMAN v2.0 suivi du corps compressé
+fn build_rman(manifest_id: u64, body: &[u8]) -> Vec<u8> {
+    let compressed = zstd::bulk::compress(body, 3).expect("compression zstd");
+    let mut data = Vec::with_capacity(RMAN_HEADER_SIZE + compressed.len());
+    data.extend_from_slice(RMAN_MAGIC);
+    data.push(2);
+    data.push(0);
+    data.extend_from_slice(&0x0200u16.to_le_bytes());
+    data.extend_from_slice(&(RMAN_HEADER_SIZE as u32).to_le_bytes());
+    data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    data.extend_from_slice(&manifest_id.to_le_bytes());
+    data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    data.extend_from_slice(&compressed);
+    data
+}
+
+// Données pseudo-aléatoires reproductibles pour les fixtures
+pub fn sample_data(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 16) as u8 + b'a'
+        })
+        .collect()
+}
+
+enum FbValue {
+    U8(u8),
+    U32(u32),
+    U64(u64),
+    Str(String),
+    U64s(Vec<u64>),
+    Tables(Vec<FbTable>),
+}
+
+// Écriture flatbuffer "en avant" : chaque table est suivie de ses enfants
+struct FbTable {
+    fields: Vec<(usize, FbValue)>,
+}
+
+impl FbTable {
+    fn new() -> Self {
+        Self { fields: Vec::new() }
+    }
+
+    fn field(mut self, index: usize, value: FbValue) -> Self {
+        self.fields.push((index, value));
+        self
+    }
+
+    fn write(&self, buf: &mut Vec<u8>) -> usize {
+        let slots = self.fields.iter().map(|(i, _)| i + 1).max().unwrap_or(0);
+
+        // Disposition de la table : soffset puis les champs
+        let mut layout = Vec::new();
+        let mut size = 4usize;
+        for (index, value) in &self.fields {
+            let width = match value {
+                FbValue::U8(_) => 1,
+                FbValue::U64(_) => 8,
+                _ => 4,
+            };
+            size = size.div_ceil(width) * width;
+            layout.push((*index, size));
+            size += width;
+        }
+
+        align(buf, 2);
+        let vtable_pos = buf.len();
+        buf.extend_from_slice(&((4 + slots * 2) as u16).to_le_bytes());
+        buf.extend_from_slice(&(size as u16).to_le_bytes());
+        let mut offsets = vec![0u16; slots];
+        for (index, offset) in &layout {
+            offsets[*index] = *offset as u16;
+        }
+        for offset in offsets {
+            buf.extend_from_slice(&offset.to_le_bytes());
+        }
+
+        align(buf, 8);
+        let table_pos = buf.len();
+        buf.resize(table_pos + size, 0);
+        buf[table_pos..table_pos + 4].copy_from_slice(&((table_pos - vtable_pos) as i32).to_le_bytes());
+
+        for ((_, value), (_, offset)) in self.fields.iter().zip(&layout) {
+            let pos = table_pos + offset;
+            match value {
+                FbValue::U8(v) => buf[pos] = *v,
+                FbValue::U32(v) => buf[pos..pos + 4].copy_from_slice(&v.to_le_bytes()),
+                FbValue::U64(v) => buf[pos..pos + 8].copy_from_slice(&v.to_le_bytes()),
+                FbValue::Str(s) => {
+                    align(buf, 4);
+                    let target = buf.len();
+                    patch_offset(buf, pos, target);
+                    buf.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                    buf.extend_from_slice(s.as_bytes());
+                    buf.push(0);
+                }
+                FbValue::U64s(values) => {
+                    while (buf.len() + 4) % 8 != 0 {
+                        buf.push(0);
+                    }
+                    let target = buf.len();
+                    patch_offset(buf, pos, target);
+                    buf.extend_from_slice(&(values.len() as u32).to_le_bytes());
+                    for v in values {
+                        buf.extend_from_slice(&v.to_le_bytes());
+                    }
+                }
+                FbValue::Tables(tables) => {
+                    align(buf, 4);
+                    let target = buf.len();
+                    patch_offset(buf, pos, target);
+                    buf.extend_from_slice(&(tables.len() as u32).to_le_bytes());
+                    let elements = buf.len();
+                    buf.resize(elements + tables.len() * 4, 0);
+                    for (i, table) in tables.iter().enumerate() {
+                        let child = table.write(buf);
+                        patch_offset(buf, elements + i * 4, child);
+                    }
+                }
+            }
+        }
+        table_pos
+    }
+}
+
+fn align(buf: &mut Vec<u8>, alignment: usize) {
+    while buf.len() % alignment != 0 {
+        buf.push(0);
+    }
+}
+
+fn patch_offset(buf: &mut [u8], at: usize, target: usize) {
+    buf[at..at + 4].copy_from_slice(&((target - at) as u32).to_le_bytes());
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    routes: Routes,
+    requests: Arc<Mutex<Vec<String>>>,
+) -> std::io::Result<()> {
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 || head.len() > 16 * 1024 {
+            return Ok(());
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&head).to_string();
+    let mut lines = head.lines();
+    let path = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or("/")
+        .to_string();
+    let range = lines.find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if !name.trim().eq_ignore_ascii_case("range") {
+            return None;
+        }
+        let (start, end) = value.trim().strip_prefix("bytes=")?.split_once('-')?;
+        Some((start.parse::<usize>().ok()?, end.parse::<usize>().ok()))
+    });
+
+    requests.lock().await.push(path.clone());
+    let body = routes.lock().await.get(&path).cloned();
+
+    let response = match body {
+        None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+        Some(data) => match range {
+            Some((start, end)) if start < data.len() => {
+                let end = end.unwrap_or(data.len() - 1).min(data.len() - 1);
+                let mut response = format!(
+                    "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\nConnection: close\r\n\r\n",
+                    end + 1 - start,
+                    start,
+                    end,
+                    data.len()
+                )
+                .into_bytes();
+                response.extend_from_slice(&data[start..=end]);
+                response
+            }
+            Some(_) => b"HTTP/1.1 416 Range Not Satisfiable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+            None => {
+                let mut response =
+                    format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", data.len()).into_bytes();
+                response.extend_from_slice(&data);
+                response
+            }
+        },
+    };
+
+    stream.write_all(&response).await?;
+    stream.shutdown().await
+}
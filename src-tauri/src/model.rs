@@ -0,0 +1,516 @@
+// Modèles 3D : maillages .skn, squelettes .skl et export glTF 2.0 binaire (.glb)
+use serde_json::{json, Value};
+
+pub const SKN_MAGIC: u32 = 0x0011_2233;
+pub const SKL_FORMAT_TOKEN: u32 = 0x22FD_4FC3;
+const SKL_JOINT_SIZE: usize = 100;
+// Nom (64 octets) puis premier sommet, nombre de sommets, premier index et nombre d'index
+const SKN_SUBMESH_SIZE: usize = 80;
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_JSON_CHUNK: u32 = 0x4E4F_534A;
+const GLB_BIN_CHUNK: u32 = 0x004E_4942;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const FLOAT: u32 = 5126;
+const UNSIGNED_SHORT: u32 = 5123;
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], position: usize) -> Reader<'a> {
+        Reader { data, position }
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let slice = self
+            .position
+            .checked_add(count)
+            .and_then(|end| self.data.get(self.position..end))
+            .ok_or_else(|| format!("Modèle tronqué à l'octet {}", self.position))?;
+        self.position += count;
+        Ok(slice)
+    }
+
+    // Nombre d'éléments annoncé, borné par ce qui reste à lire : évite une réservation démesurée
+    fn capacity(&self, count: usize, element_size: usize) -> usize {
+        count.min(self.data.len().saturating_sub(self.position) / element_size)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        Ok(self.u16()? as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(self.u32()? as i32)
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn vec<const N: usize>(&mut self) -> Result<[f32; N], String> {
+        let mut values = [0.0; N];
+        for value in &mut values {
+            *value = self.f32()?;
+        }
+        Ok(values)
+    }
+
+    // Chaîne terminée par un zéro à une position absolue
+    fn c_string(&self, position: usize) -> Result<String, String> {
+        let bytes = self.data.get(position..).ok_or("Nom hors du fichier")?;
+        let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        Ok(String::from_utf8_lossy(&bytes[..end]).to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkinVertex {
+    pub position: [f32; 3],
+    pub bones: [u8; 4],
+    pub weights: [f32; 4],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Submesh {
+    pub name: String,
+    pub start_vertex: u32,
+    pub vertex_count: u32,
+    pub start_index: u32,
+    pub index_count: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct SkinnedMesh {
+    pub version: (u16, u16),
+    pub submeshes: Vec<Submesh>,
+    pub indices: Vec<u16>,
+    pub vertices: Vec<SkinVertex>,
+}
+
+impl SkinnedMesh {
+    pub fn parse(data: &[u8]) -> Result<SkinnedMesh, String> {
+        let mut r = Reader::new(data, 0);
+        if r.u32()? != SKN_MAGIC {
+            return Err("Fichier .skn invalide (signature)".to_string());
+        }
+        let version = (r.u16()?, r.u16()?);
+        if !matches!(version.0, 1 | 2 | 4) {
+            return Err(format!("Version .skn non prise en charge : {}.{}", version.0, version.1));
+        }
+
+        let submesh_count = r.u32()? as usize;
+        let mut submeshes = Vec::with_capacity(r.capacity(submesh_count, SKN_SUBMESH_SIZE));
+        for _ in 0..submesh_count {
+            let raw = r.bytes(64)?;
+            let end = raw.iter().position(|b| *b == 0).unwrap_or(raw.len());
+            submeshes.push(Submesh {
+                name: String::from_utf8_lossy(&raw[..end]).to_string(),
+                start_vertex: r.u32()?,
+                vertex_count: r.u32()?,
+                start_index: r.u32()?,
+                index_count: r.u32()?,
+            });
+        }
+        if version.0 == 4 {
+            r.u32()?;
+        }
+        let (index_count, vertex_count) = (r.u32()? as usize, r.u32()? as usize);
+        // v4 : taille et type de sommet (52 de base, 56 avec couleur, 72 avec tangente), puis boîte et sphère englobantes
+        let vertex_size = if version.0 == 4 {
+            let size = r.u32()? as usize;
+            r.u32()?;
+            r.bytes(40)?;
+            size
+        } else {
+            52
+        };
+        if vertex_size < 52 {
+            return Err(format!("Taille de sommet .skn invalide : {}", vertex_size));
+        }
+
+        let mut indices = Vec::with_capacity(r.capacity(index_count, 2));
+        for _ in 0..index_count {
+            indices.push(r.u16()?);
+        }
+        let mut vertices = Vec::with_capacity(r.capacity(vertex_count, vertex_size));
+        for _ in 0..vertex_count {
+            let start = r.position;
+            let position = r.vec::<3>()?;
+            let bones = [r.u8()?, r.u8()?, r.u8()?, r.u8()?];
+            let weights = r.vec::<4>()?;
+            let normal = r.vec::<3>()?;
+            let uv = r.vec::<2>()?;
+            r.position = start
+                .checked_add(vertex_size)
+                .ok_or_else(|| format!("Taille de sommet .skn invalide : {}", vertex_size))?;
+            vertices.push(SkinVertex {
+                position,
+                bones,
+                weights,
+                normal,
+                uv,
+            });
+        }
+        if let Some(index) = indices.iter().find(|i| **i as usize >= vertex_count) {
+            return Err(format!("Index de sommet hors limites : {}", index));
+        }
+
+        // Les anciens fichiers sans sous-maillage forment un seul groupe
+        if submeshes.is_empty() {
+            submeshes.push(Submesh {
+                name: "default".to_string(),
+                start_vertex: 0,
+                vertex_count: vertex_count as u32,
+                start_index: 0,
+                index_count: index_count as u32,
+            });
+        }
+        Ok(SkinnedMesh {
+            version,
+            submeshes,
+            indices,
+            vertices,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Joint {
+    pub name: String,
+    pub parent: i16,
+    pub translation: [f32; 3],
+    pub scale: [f32; 3],
+    pub rotation: [f32; 4],
+    pub inverse_bind_translation: [f32; 3],
+    pub inverse_bind_scale: [f32; 3],
+    pub inverse_bind_rotation: [f32; 4],
+}
+
+#[derive(Debug, Clone)]
+pub struct Skeleton {
+    pub joints: Vec<Joint>,
+    // Correspondance index d'os du .skn -> articulation
+    pub influences: Vec<u16>,
+}
+
+impl Skeleton {
+    // Format actuel (jeton 0x22FD4FC3) ; l'ancien format "r3d2sklt" n'est plus livré
+    pub fn parse(data: &[u8]) -> Result<Skeleton, String> {
+        let mut r = Reader::new(data, 4);
+        if r.u32()? != SKL_FORMAT_TOKEN {
+            return Err("Fichier .skl non pris en charge (ancien format ou signature invalide)".to_string());
+        }
+        r.u32()?;
+        r.u16()?;
+        let joint_count = r.u16()? as usize;
+        let influence_count = r.u32()? as usize;
+        let joints_offset = r.i32()? as usize;
+        r.i32()?;
+        let influences_offset = r.i32()? as usize;
+
+        let mut joints = Vec::with_capacity(Reader::new(data, joints_offset).capacity(joint_count, SKL_JOINT_SIZE));
+        for i in 0..joint_count {
+            let start = joints_offset
+                .checked_add(i * SKL_JOINT_SIZE)
+                .ok_or_else(|| format!("Articulation {} hors du fichier", i))?;
+            let mut j = Reader::new(data, start);
+            j.u16()?;
+            j.i16()?;
+            let parent = j.i16()?;
+            j.bytes(10)?;
+            let translation = j.vec::<3>()?;
+            let scale = j.vec::<3>()?;
+            let rotation = j.vec::<4>()?;
+            let inverse_bind_translation = j.vec::<3>()?;
+            let inverse_bind_scale = j.vec::<3>()?;
+            let inverse_bind_rotation = j.vec::<4>()?;
+            // Position du nom relative au champ lui-même
+            let name_field = j.position;
+            let name_offset = j.i32()?;
+            let name = j.c_string((name_field as i64 + name_offset as i64) as usize)?;
+            joints.push(Joint {
+                name,
+                parent,
+                translation,
+                scale,
+                rotation,
+                inverse_bind_translation,
+                inverse_bind_scale,
+                inverse_bind_rotation,
+            });
+        }
+
+        let mut r = Reader::new(data, influences_offset);
+        let mut influences = Vec::with_capacity(r.capacity(influence_count, 2));
+        for _ in 0..influence_count {
+            influences.push(r.u16()?);
+        }
+        if let Some(joint) = influences.iter().find(|j| **j as usize >= joint_count) {
+            return Err(format!("Influence vers une articulation inexistante : {}", joint));
+        }
+        Ok(Skeleton { joints, influences })
+    }
+}
+
+// Squelette livré à côté du maillage : même chemin en .skl
+pub fn skeleton_path(mesh_path: &str) -> Option<String> {
+    let lower = mesh_path.to_lowercase();
+    lower.ends_with(".skn").then(|| format!("{}.skl", &mesh_path[..mesh_path.len() - 4]))
+}
+
+// Texture de couleur du même dossier (suffixe "_tx_cm" chez Riot), la première par ordre alphabétique
+pub fn matching_texture<'a>(mesh_path: &str, names: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let lower = mesh_path.to_lowercase();
+    let dir = &lower[..lower.rfind('/').map(|i| i + 1).unwrap_or(0)];
+    names
+        .filter(|name| {
+            let name = name.to_lowercase();
+            name.starts_with(dir)
+                && !name[dir.len()..].contains('/')
+                && name.contains("_tx_cm")
+                && (name.ends_with(".dds") || name.ends_with(".tex"))
+        })
+        .min()
+}
+
+// Matrice TRS en colonnes, comme l'attend glTF
+fn trs_matrix(translation: [f32; 3], rotation: [f32; 4], scale: [f32; 3]) -> [f32; 16] {
+    let [x, y, z, w] = rotation;
+    let r = [
+        [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y + z * w), 2.0 * (x * z - y * w)],
+        [2.0 * (x * y - z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z + x * w)],
+        [2.0 * (x * z + y * w), 2.0 * (y * z - x * w), 1.0 - 2.0 * (x * x + y * y)],
+    ];
+    [
+        r[0][0] * scale[0],
+        r[0][1] * scale[0],
+        r[0][2] * scale[0],
+        0.0,
+        r[1][0] * scale[1],
+        r[1][1] * scale[1],
+        r[1][2] * scale[1],
+        0.0,
+        r[2][0] * scale[2],
+        r[2][1] * scale[2],
+        r[2][2] * scale[2],
+        0.0,
+        translation[0],
+        translation[1],
+        translation[2],
+        1.0,
+    ]
+}
+
+// Buffer binaire unique du .glb, chaque vue alignée sur 4 octets
+#[derive(Default)]
+struct GltfBuffer {
+    bin: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl GltfBuffer {
+    fn view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        while self.bin.len() % 4 != 0 {
+            self.bin.push(0);
+        }
+        let mut view = json!({"buffer": 0, "byteOffset": self.bin.len(), "byteLength": bytes.len()});
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+        self.bin.extend_from_slice(bytes);
+        self.views.push(view);
+        self.views.len() - 1
+    }
+
+    fn accessor(&mut self, accessor: Value) -> usize {
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn floats<const N: usize>(&mut self, values: &[[f32; N]], kind: &str, bounds: bool) -> usize {
+        let bytes: Vec<u8> = values.iter().flatten().flat_map(|v| v.to_le_bytes()).collect();
+        let view = self.view(&bytes, Some(ARRAY_BUFFER));
+        let mut accessor = json!({"bufferView": view, "componentType": FLOAT, "count": values.len(), "type": kind});
+        if bounds {
+            let min: Vec<f32> = (0..N).map(|i| values.iter().map(|v| v[i]).fold(f32::MAX, f32::min)).collect();
+            let max: Vec<f32> = (0..N).map(|i| values.iter().map(|v| v[i]).fold(f32::MIN, f32::max)).collect();
+            accessor["min"] = json!(min);
+            accessor["max"] = json!(max);
+        }
+        self.accessor(accessor)
+    }
+}
+
+// Exporte le maillage (et son squelette s'il est fourni) en .glb, avec la texture PNG en matériau
+pub fn to_glb(mesh: &SkinnedMesh, skeleton: Option<&Skeleton>, texture_png: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    if mesh.vertices.is_empty() {
+        return Err("Maillage vide".to_string());
+    }
+    let mut buffer = GltfBuffer::default();
+    let positions: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| v.position).collect();
+    let normals: Vec<[f32; 3]> = mesh.vertices.iter().map(|v| v.normal).collect();
+    let uvs: Vec<[f32; 2]> = mesh.vertices.iter().map(|v| v.uv).collect();
+    let mut attributes = json!({
+        "POSITION": buffer.floats(&positions, "VEC3", true),
+        "NORMAL": buffer.floats(&normals, "VEC3", false),
+        "TEXCOORD_0": buffer.floats(&uvs, "VEC2", false),
+    });
+
+    let mut nodes = Vec::new();
+    let mut skins = Vec::new();
+    if let Some(skeleton) = skeleton {
+        // Les index d'os du .skn passent par la table d'influences ; les poids sont normalisés
+        let mut joints = Vec::with_capacity(mesh.vertices.len() * 8);
+        let mut weights = Vec::with_capacity(mesh.vertices.len());
+        for vertex in &mesh.vertices {
+            for bone in vertex.bones {
+                let joint = skeleton
+                    .influences
+                    .get(bone as usize)
+                    .copied()
+                    .ok_or_else(|| format!("Os {} absent du squelette", bone))?;
+                joints.extend_from_slice(&joint.to_le_bytes());
+            }
+            let total: f32 = vertex.weights.iter().sum();
+            weights.push(if total > 0.0 { vertex.weights.map(|w| w / total) } else { [1.0, 0.0, 0.0, 0.0] });
+        }
+        let view = buffer.view(&joints, Some(ARRAY_BUFFER));
+        attributes["JOINTS_0"] = json!(buffer.accessor(
+            json!({"bufferView": view, "componentType": UNSIGNED_SHORT, "count": mesh.vertices.len(), "type": "VEC4"})
+        ));
+        attributes["WEIGHTS_0"] = json!(buffer.floats(&weights, "VEC4", false));
+
+        let matrices: Vec<[f32; 16]> = skeleton
+            .joints
+            .iter()
+            .map(|j| trs_matrix(j.inverse_bind_translation, j.inverse_bind_rotation, j.inverse_bind_scale))
+            .collect();
+        let bytes: Vec<u8> = matrices.iter().flatten().flat_map(|v| v.to_le_bytes()).collect();
+        let view = buffer.view(&bytes, None);
+        let inverse_bind_matrices = buffer.accessor(
+            json!({"bufferView": view, "componentType": FLOAT, "count": matrices.len(), "type": "MAT4"}),
+        );
+
+        for (index, joint) in skeleton.joints.iter().enumerate() {
+            let children: Vec<usize> = skeleton
+                .joints
+                .iter()
+                .enumerate()
+                .filter(|(_, child)| child.parent as i64 == index as i64)
+                .map(|(i, _)| i)
+                .collect();
+            let mut node = json!({
+                "name": joint.name,
+                "translation": joint.translation,
+                "rotation": joint.rotation,
+                "scale": joint.scale,
+            });
+            if !children.is_empty() {
+                node["children"] = json!(children);
+            }
+            nodes.push(node);
+        }
+        skins.push(json!({
+            "joints": (0..skeleton.joints.len()).collect::<Vec<_>>(),
+            "inverseBindMatrices": inverse_bind_matrices,
+        }));
+    }
+
+    let mut document = json!({
+        "asset": {"version": "2.0", "generator": "lol-patch-downloader"},
+        "scene": 0,
+    });
+    let material = match texture_png {
+        Some(png) => {
+            let view = buffer.view(png, None);
+            document["images"] = json!([{"bufferView": view, "mimeType": "image/png"}]);
+            document["samplers"] = json!([{}]);
+            document["textures"] = json!([{"source": 0, "sampler": 0}]);
+            json!({"pbrMetallicRoughness": {"baseColorTexture": {"index": 0}, "metallicFactor": 0.0}})
+        }
+        None => json!({"pbrMetallicRoughness": {"metallicFactor": 0.0}}),
+    };
+
+    let mut primitives = Vec::new();
+    for submesh in &mesh.submeshes {
+        let start = submesh.start_index as usize;
+        let indices = mesh
+            .indices
+            .get(start..start + submesh.index_count as usize)
+            .ok_or_else(|| format!("Sous-maillage {} hors des index", submesh.name))?;
+        let bytes: Vec<u8> = indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+        let view = buffer.view(&bytes, Some(ELEMENT_ARRAY_BUFFER));
+        let accessor = buffer.accessor(
+            json!({"bufferView": view, "componentType": UNSIGNED_SHORT, "count": indices.len(), "type": "SCALAR"}),
+        );
+        primitives.push(json!({"attributes": attributes, "indices": accessor, "material": 0}));
+    }
+
+    // Le nœud du maillage suit les articulations ; les racines du squelette sont aussi dans la scène
+    let mesh_node = nodes.len();
+    let mut node = json!({"name": "mesh", "mesh": 0});
+    if !skins.is_empty() {
+        node["skin"] = json!(0);
+    }
+    nodes.push(node);
+    let mut roots: Vec<usize> = match skeleton {
+        Some(skeleton) => (0..skeleton.joints.len())
+            .filter(|i| {
+                let parent = skeleton.joints[*i].parent;
+                parent < 0 || parent as usize >= skeleton.joints.len()
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    roots.push(mesh_node);
+
+    document["scenes"] = json!([{"nodes": roots}]);
+    document["nodes"] = json!(nodes);
+    document["meshes"] = json!([{"primitives": primitives}]);
+    document["materials"] = json!([material]);
+    if !skins.is_empty() {
+        document["skins"] = json!(skins);
+    }
+    while buffer.bin.len() % 4 != 0 {
+        buffer.bin.push(0);
+    }
+    document["buffers"] = json!([{"byteLength": buffer.bin.len()}]);
+    document["bufferViews"] = json!(buffer.views);
+    document["accessors"] = json!(buffer.accessors);
+
+    let mut json_chunk = serde_json::to_vec(&document).map_err(|e| e.to_string())?;
+    while json_chunk.len() % 4 != 0 {
+        json_chunk.push(b' ');
+    }
+    let total = 12 + 8 + json_chunk.len() + 8 + buffer.bin.len();
+    let mut glb = Vec::with_capacity(total);
+    glb.extend_from_slice(GLB_MAGIC);
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(total as u32).to_le_bytes());
+    glb.extend_from_slice(&(json_chunk.len() as u32).to_le_bytes());
+    glb.extend_from_slice(&GLB_JSON_CHUNK.to_le_bytes());
+    glb.extend_from_slice(&json_chunk);
+    glb.extend_from_slice(&(buffer.bin.len() as u32).to_le_bytes());
+    glb.extend_from_slice(&GLB_BIN_CHUNK.to_le_bytes());
+    glb.extend_from_slice(&buffer.bin);
+    Ok(glb)
+}
@@ -0,0 +1,133 @@
+// Modèle du dossier de sortie de chaque patch, ex: "{root}/{realm}/{version}/{manifest_hash}"
+use std::path::{Component, Path, PathBuf};
+
+pub const DEFAULT_OUTPUT_TEMPLATE: &str = "{root}/{realm}/{version}/{manifest_hash}";
+pub const TEMPLATE_PLACEHOLDERS: &[&str] = &["root", "product", "channel", "realm", "version", "manifest_hash", "date", "language", "content"];
+
+#[derive(Debug, Clone, Default)]
+pub struct TemplateValues {
+    pub root: PathBuf,
+    pub product: String,
+    pub channel: String,
+    pub realm: String,
+    pub version: String,
+    pub manifest_hash: String,
+    pub date: String,
+    pub language: String,
+    pub content: String,
+}
+
+impl TemplateValues {
+    fn get(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "product" => &self.product,
+            "channel" => &self.channel,
+            "realm" => &self.realm,
+            "version" => &self.version,
+            "manifest_hash" => &self.manifest_hash,
+            "date" => &self.date,
+            "language" => &self.language,
+            "content" => &self.content,
+            _ => return None,
+        };
+        Some(sanitize_component(value))
+    }
+}
+
+// Rend une valeur utilisable comme nom de dossier sous Windows comme sous Unix
+pub fn sanitize_component(value: &str) -> String {
+    let cleaned: String = value
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_end_matches('.').trim();
+    if cleaned.is_empty() || cleaned == "." || cleaned == ".." {
+        "unknown".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+pub fn validate_template(template: &str) -> Result<(), String> {
+    expand_template(template, &TemplateValues::default()).map(|_| ())
+}
+
+pub fn expand_template(template: &str, values: &TemplateValues) -> Result<PathBuf, String> {
+    if template.trim().is_empty() {
+        return Err("Le modèle de dossier de sortie est vide".to_string());
+    }
+
+    // Le dossier de téléchargement vient toujours en tête : un préfixe absolu
+    // ou relatif ("/data/{realm}") perdrait ses séparateurs une fois nettoyé
+    let mut segments = template.trim().split(['/', '\\']);
+    if segments.next() != Some("{root}") {
+        return Err("Le modèle de dossier de sortie doit commencer par {root}".to_string());
+    }
+    let mut path = values.root.clone();
+    for segment in segments {
+        if segment == "{root}" {
+            return Err("{root} doit être au début du modèle de dossier de sortie".to_string());
+        }
+        if segment.is_empty() {
+            continue;
+        }
+
+        let mut expanded = String::new();
+        let mut rest = segment;
+        while let Some(start) = rest.find('{') {
+            expanded.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .map(|e| start + e)
+                .ok_or_else(|| format!("Accolade non fermée dans le modèle: {}", template))?;
+            let name = &rest[start + 1..end];
+            let value = values.get(name).ok_or_else(|| {
+                format!(
+                    "Variable inconnue {{{}}} dans le modèle (disponibles: {})",
+                    name,
+                    TEMPLATE_PLACEHOLDERS.join(", ")
+                )
+            })?;
+            expanded.push_str(&value);
+            rest = &rest[end + 1..];
+        }
+        expanded.push_str(rest);
+        path.push(sanitize_component(&expanded));
+    }
+    Ok(path)
+}
+
+// Comparaison de dossiers insensible aux séparateurs et, sous Windows, à la casse
+pub fn same_directory(a: &Path, b: &Path) -> bool {
+    let normalize = |path: &Path| -> Vec<String> {
+        path.components()
+            .filter(|c| !matches!(c, Component::CurDir))
+            .map(|c| {
+                let part = c.as_os_str().to_string_lossy().to_string();
+                if cfg!(windows) {
+                    part.to_lowercase()
+                } else {
+                    part
+                }
+            })
+            .collect()
+    };
+    normalize(a) == normalize(b)
+}
+
+// Tâche active écrivant déjà dans le dossier cible (ou dans un dossier parent/enfant)
+pub fn find_collision<'a>(
+    active: impl IntoIterator<Item = (&'a str, &'a Path)>,
+    target: &Path,
+) -> Option<&'a str> {
+    active.into_iter().find_map(|(task_id, dir)| {
+        let overlaps = same_directory(dir, target)
+            || target.ancestors().skip(1).any(|a| same_directory(a, dir))
+            || dir.ancestors().skip(1).any(|a| same_directory(a, target));
+        overlaps.then_some(task_id)
+    })
+}
@@ -0,0 +1,253 @@
+// Versions de patch (24.17, 25.S1.3, micro-patchs) et calendrier des sorties, chargé depuis un fichier de données modifiable
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+pub const CALENDAR_FILE_NAME: &str = "patch_calendar.json";
+const BUILTIN_CALENDAR: &str = include_str!("../assets/patch_calendar.json");
+
+// Les builds du client ont dix ans de retard sur le nom du patch (build 14.17 -> patch 24.17)
+const BUILD_YEAR_OFFSET: u16 = 10;
+// Au-delà, une date n'est plus rattachée au dernier patch connu (calendrier pas à jour)
+const MAX_PATCH_DAYS: i64 = 28;
+// Après le dernier patch connu : un patch toutes les deux semaines, 24 par an, pendant un an au plus
+const CADENCE_DAYS: i64 = 14;
+const PATCHES_PER_YEAR: u16 = 24;
+const MAX_PROJECTED_DAYS: i64 = 366;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PatchVersion {
+    pub year: u16,
+    // Saison de la numérotation 2025+ (25.S1.3), absente pour 24.17
+    pub season: Option<u16>,
+    // Rang du patch dans l'année, y compris avec une saison (25.S1.3 est le 3e patch de 2025)
+    pub patch: u16,
+    // 0 pour la sortie du patch, puis 1, 2... pour les micro-patchs qui suivent
+    pub hotfix: u16,
+}
+
+impl PatchVersion {
+    pub fn is_hotfix(&self) -> bool {
+        self.hotfix > 0
+    }
+
+    // Même patch, sans le numéro de micro-patch
+    pub fn base(&self) -> PatchVersion {
+        PatchVersion { hotfix: 0, ..*self }
+    }
+}
+
+impl Ord for PatchVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.year, self.patch, self.hotfix, self.season).cmp(&(other.year, other.patch, other.hotfix, other.season))
+    }
+}
+
+impl PartialOrd for PatchVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for PatchVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.season {
+            Some(season) => write!(f, "{}.S{}.{}", self.year, season, self.patch)?,
+            None => write!(f, "{}.{}", self.year, self.patch)?,
+        }
+        if self.hotfix > 0 {
+            write!(f, ".{}", self.hotfix)?;
+        }
+        Ok(())
+    }
+}
+
+// "24.17", "24.17.1", "25.S1.3", "25.S1.3.1"
+impl FromStr for PatchVersion {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Version de patch invalide : {}", text);
+        let parts: Vec<&str> = text.trim().split('.').collect();
+        let number = |part: &str| part.parse::<u16>().map_err(|_| invalid());
+        let (season, rest) = match parts.get(1).and_then(|part| part.strip_prefix(['S', 's'])) {
+            Some(season) => (Some(number(season)?), &parts[2..]),
+            None => (None, parts.get(1..).unwrap_or_default()),
+        };
+        let (patch, hotfix) = match rest {
+            [patch] => (number(patch)?, 0),
+            [patch, hotfix] => (number(patch)?, number(hotfix)?),
+            _ => return Err(invalid()),
+        };
+        Ok(PatchVersion {
+            year: number(parts[0])?,
+            season,
+            patch,
+            hotfix,
+        })
+    }
+}
+
+impl Serialize for PatchVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PatchVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// Dates du catalogue : "2024-08-28" ou "2024-08-28 14:03:11"
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text.trim().get(..10)?, "%Y-%m-%d").ok()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarPatch {
+    pub version: PatchVersion,
+    pub date: NaiveDate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchCalendar {
+    // Écart toléré entre la date d'un manifest et la sortie officielle du patch
+    pub window_days: i64,
+    pub patches: Vec<CalendarPatch>,
+}
+
+impl Default for PatchCalendar {
+    fn default() -> Self {
+        Self::parse(BUILTIN_CALENDAR).expect("Calendrier des patchs intégré invalide")
+    }
+}
+
+impl PatchCalendar {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut calendar: PatchCalendar =
+            serde_json::from_str(text).map_err(|e| format!("Calendrier des patchs invalide : {}", e))?;
+        calendar.patches.sort_by_key(|patch| patch.date);
+        Ok(calendar)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+        Self::parse(&text)
+    }
+
+    // Sorties estimées après le dernier patch connu, jusqu'à la date (calendrier pas encore complété) :
+    // le numéro avance à chaque sortie et repart à 1 au début de l'année suivante
+    fn projected_until(&self, date: NaiveDate) -> Vec<CalendarPatch> {
+        let mut projected = Vec::new();
+        let Some(last) = self.patches.last() else {
+            return projected;
+        };
+        let limit = date.min(last.date + Duration::days(MAX_PROJECTED_DAYS)) + Duration::days(self.window_days);
+        let mut version = last.version.base();
+        let mut release = last.date + Duration::days(CADENCE_DAYS);
+        while release <= limit {
+            let year = (release.year() % 100) as u16;
+            if year != version.year {
+                version = PatchVersion {
+                    year,
+                    season: None,
+                    patch: 1,
+                    hotfix: 0,
+                };
+                projected.push(CalendarPatch { version, date: release });
+            } else if version.patch < PATCHES_PER_YEAR {
+                version.patch += 1;
+                projected.push(CalendarPatch { version, date: release });
+            }
+            release += Duration::days(CADENCE_DAYS);
+        }
+        projected
+    }
+
+    // Sortie officielle (ou estimée) dans la fenêtre de tolérance autour de la date
+    pub fn release_near(&self, date: NaiveDate) -> Option<CalendarPatch> {
+        self.patches
+            .iter()
+            .chain(&self.projected_until(date))
+            .filter(|patch| (date - patch.date).num_days().abs() <= self.window_days)
+            .min_by_key(|patch| (date - patch.date).num_days().abs())
+            .cloned()
+    }
+
+    // Patch en jeu à cette date (le dernier sorti)
+    pub fn live_at(&self, date: NaiveDate) -> Option<CalendarPatch> {
+        self.patches
+            .iter()
+            .chain(&self.projected_until(date))
+            .rev()
+            .find(|patch| patch.date <= date)
+            .filter(|patch| (date - patch.date).num_days() <= MAX_PATCH_DAYS)
+            .cloned()
+    }
+
+    // Version d'un manifest d'après sa date : sortie du patch, ou micro-patch du patch en jeu
+    pub fn version_for_date(&self, date: NaiveDate) -> Option<PatchVersion> {
+        if let Some(release) = self.release_near(date) {
+            return Some(release.version);
+        }
+        self.live_at(date).map(|patch| PatchVersion { hotfix: 1, ..patch.version })
+    }
+
+    // Versions d'une série de manifests d'un même serveur, micro-patchs numérotés par date
+    pub fn label_versions(&self, dates: &[&str]) -> Vec<Option<PatchVersion>> {
+        let resolved: Vec<Option<(PatchVersion, NaiveDate)>> = dates
+            .iter()
+            .map(|date| {
+                let date = parse_date(date)?;
+                self.version_for_date(date).map(|version| (version, date))
+            })
+            .collect();
+
+        let mut hotfix_dates: BTreeMap<PatchVersion, BTreeSet<NaiveDate>> = BTreeMap::new();
+        for (version, date) in resolved.iter().flatten() {
+            if version.is_hotfix() {
+                hotfix_dates.entry(version.base()).or_default().insert(*date);
+            }
+        }
+        resolved
+            .into_iter()
+            .map(|resolved| {
+                let (version, date) = resolved?;
+                if !version.is_hotfix() {
+                    return Some(version);
+                }
+                let rank = hotfix_dates[&version.base()].iter().position(|d| *d == date).unwrap_or_default();
+                Some(PatchVersion {
+                    hotfix: rank as u16 + 1,
+                    ..version
+                })
+            })
+            .collect()
+    }
+
+    // Version d'un build du client ("14.17.612.7771" -> 24.17, "15.3.x" -> 25.S1.3 d'après le calendrier)
+    pub fn from_build(&self, build: &str) -> Option<PatchVersion> {
+        let mut parts = build.trim().split('.');
+        let major: u16 = parts.next()?.parse().ok()?;
+        let patch: u16 = parts.next()?.parse().ok()?;
+        let year = major + BUILD_YEAR_OFFSET;
+        let known = self
+            .patches
+            .iter()
+            .find(|entry| entry.version.year == year && entry.version.patch == patch)
+            .map(|entry| entry.version);
+        Some(known.unwrap_or(PatchVersion {
+            year,
+            season: None,
+            patch,
+            hotfix: 0,
+        }))
+    }
+}
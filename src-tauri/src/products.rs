@@ -0,0 +1,115 @@
+// Produits Riot distribués par manifests RMAN : jeu LoL, client LoL (LCU), TFT et Riot Client
+use crate::realms::Realm;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Product {
+    // Jeu League of Legends
+    #[default]
+    Lol,
+    // Client LoL (LCU)
+    LolClient,
+    Tft,
+    RiotClient,
+}
+
+pub const PRODUCTS: &[Product] = &[Product::Lol, Product::LolClient, Product::Tft, Product::RiotClient];
+
+impl Product {
+    // Identifiant de la première colonne du catalogue
+    pub fn id(&self) -> &'static str {
+        match self {
+            Product::Lol => "lol",
+            Product::LolClient => "lol_client",
+            Product::Tft => "tft",
+            Product::RiotClient => "riot_client",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Product::Lol => "League of Legends",
+            Product::LolClient => "Client League of Legends",
+            Product::Tft => "Teamfight Tactics",
+            Product::RiotClient => "Riot Client",
+        }
+    }
+
+    // CDN des bundles du produit
+    pub fn cdn_url(&self) -> &'static str {
+        match self {
+            Product::Lol | Product::LolClient => "http://lol.secure.dyn.riotcdn.net/channels/public",
+            Product::Tft => "http://tft.secure.dyn.riotcdn.net/channels/public",
+            Product::RiotClient => "http://ks-foundation.secure.dyn.riotcdn.net/channels/public",
+        }
+    }
+
+    // Le Riot Client n'a pas de fichiers par langue de jeu : fichiers internationaux seulement
+    pub fn default_language(&self) -> Option<&'static str> {
+        match self {
+            Product::RiotClient => Some("none"),
+            _ => None,
+        }
+    }
+
+    // Les numéros de patch du calendrier ne concernent que le jeu
+    pub fn uses_patch_calendar(&self) -> bool {
+        matches!(self, Product::Lol | Product::Tft)
+    }
+}
+
+impl fmt::Display for Product {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+// Accepte aussi les variantes d'écriture rencontrées dans les catalogues
+impl FromStr for Product {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_lowercase().replace('-', "_").as_str() {
+            "lol" | "league_of_legends" => Ok(Product::Lol),
+            "lol_client" | "lcu" | "league_client" => Ok(Product::LolClient),
+            "tft" | "bacon" => Ok(Product::Tft),
+            "riot_client" | "riotclient" | "rc" | "keystone" => Ok(Product::RiotClient),
+            _ => Err(format!("Produit inconnu : {}", text)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    #[default]
+    Live,
+    Pbe,
+    Staging,
+    Tournament,
+}
+
+impl Channel {
+    // Canal d'après le nom du serveur du catalogue ("PBE1", "LIVESTAGING", "LOLTMNT02"...)
+    pub fn from_realm(realm: &str) -> Self {
+        Realm::parse(realm).kind
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Channel::Live => "live",
+            Channel::Pbe => "pbe",
+            Channel::Staging => "staging",
+            Channel::Tournament => "tournament",
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
@@ -0,0 +1,458 @@
+// Fichiers de propriétés .bin (PROP / PTCH) : lecture complète et conversion en JSON lisible
+use crate::hashes::HashTable;
+use serde_json::{json, Map, Value};
+
+pub const PROP_MAGIC: &[u8; 4] = b"PROP";
+pub const PTCH_MAGIC: &[u8; 4] = b"PTCH";
+
+// Imbrication maximale des valeurs (listes, structures, options, maps) : au-delà, le fichier est refusé
+pub const MAX_VALUE_DEPTH: usize = 64;
+
+// Hash des noms d'entrées, de classes et de champs : FNV-1a 32 bits du nom en minuscules
+pub fn fnv1a(name: &str) -> u32 {
+    name.to_lowercase()
+        .bytes()
+        .fold(0x811C_9DC5u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum BinType {
+    None = 0,
+    Bool = 1,
+    I8 = 2,
+    U8 = 3,
+    I16 = 4,
+    U16 = 5,
+    I32 = 6,
+    U32 = 7,
+    I64 = 8,
+    U64 = 9,
+    F32 = 10,
+    Vec2 = 11,
+    Vec3 = 12,
+    Vec4 = 13,
+    Mtx44 = 14,
+    Rgba = 15,
+    String = 16,
+    Hash = 17,
+    File = 18,
+    List = 0x80,
+    List2 = 0x81,
+    Pointer = 0x82,
+    Embed = 0x83,
+    Link = 0x84,
+    Option = 0x85,
+    Map = 0x86,
+    Flag = 0x87,
+}
+
+impl BinType {
+    pub fn from_u8(value: u8) -> Result<BinType, String> {
+        use BinType::*;
+        let kind = match value {
+            0 => None,
+            1 => Bool,
+            2 => I8,
+            3 => U8,
+            4 => I16,
+            5 => U16,
+            6 => I32,
+            7 => U32,
+            8 => I64,
+            9 => U64,
+            10 => F32,
+            11 => Vec2,
+            12 => Vec3,
+            13 => Vec4,
+            14 => Mtx44,
+            15 => Rgba,
+            16 => String,
+            17 => Hash,
+            18 => File,
+            0x80 => List,
+            0x81 => List2,
+            0x82 => Pointer,
+            0x83 => Embed,
+            0x84 => Link,
+            0x85 => Option,
+            0x86 => Map,
+            0x87 => Flag,
+            other => return Err(format!("Type de champ inconnu: 0x{:02x}", other)),
+        };
+        Ok(kind)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinValue {
+    None,
+    Bool(bool),
+    I8(i8),
+    U8(u8),
+    I16(i16),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    I64(i64),
+    U64(u64),
+    F32(f32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+    Mtx44([f32; 16]),
+    Rgba([u8; 4]),
+    String(String),
+    Hash(u32),
+    File(u64),
+    List { value_type: BinType, items: Vec<BinValue> },
+    Pointer { class: u32, fields: Vec<BinField> },
+    Embed { class: u32, fields: Vec<BinField> },
+    Link(u32),
+    Option { value_type: BinType, value: Option<Box<BinValue>> },
+    Map { key_type: BinType, value_type: BinType, entries: Vec<(BinValue, BinValue)> },
+    Flag(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinField {
+    pub name: u32,
+    pub value: BinValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinEntry {
+    pub path: u32,
+    pub class: u32,
+    pub fields: Vec<BinField>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinPatch {
+    pub entry: u32,
+    pub path: String,
+    pub value: BinValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropBin {
+    pub is_patch: bool,
+    pub version: u32,
+    pub linked: Vec<String>,
+    pub entries: Vec<BinEntry>,
+    pub patches: Vec<BinPatch>,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    // Capacité bornée par les octets restants : un nombre d'éléments lu dans le fichier n'est pas fiable
+    fn capacity(&self, count: usize, min_size: usize) -> usize {
+        count.min((self.data.len() - self.pos) / min_size.max(1))
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len()).ok_or_else(|| {
+            format!("Fichier bin tronqué (lecture de {} octets à la position {})", len, self.pos)
+        })?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn f32s<const N: usize>(&mut self) -> Result<[f32; N], String> {
+        let mut values = [0f32; N];
+        for value in values.iter_mut() {
+            *value = f32::from_le_bytes(self.array()?);
+        }
+        Ok(values)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.bytes(len)?).into_owned())
+    }
+
+    fn kind(&mut self) -> Result<BinType, String> {
+        BinType::from_u8(self.u8()?)
+    }
+
+    fn fields(&mut self) -> Result<Vec<BinField>, String> {
+        let count = self.u16()?;
+        // Nom (u32) et type (u8) au minimum
+        let mut fields = Vec::with_capacity(self.capacity(count as usize, 5));
+        for _ in 0..count {
+            let name = self.u32()?;
+            let kind = self.kind()?;
+            fields.push(BinField {
+                name,
+                value: self.value(kind)?,
+            });
+        }
+        Ok(fields)
+    }
+
+    fn value(&mut self, kind: BinType) -> Result<BinValue, String> {
+        if self.depth >= MAX_VALUE_DEPTH {
+            return Err(format!("Fichier bin invalide (plus de {} niveaux d'imbrication)", MAX_VALUE_DEPTH));
+        }
+        self.depth += 1;
+        let value = self.read_value(kind);
+        self.depth -= 1;
+        value
+    }
+
+    fn read_value(&mut self, kind: BinType) -> Result<BinValue, String> {
+        let value = match kind {
+            BinType::None => BinValue::None,
+            BinType::Bool => BinValue::Bool(self.u8()? != 0),
+            BinType::I8 => BinValue::I8(self.u8()? as i8),
+            BinType::U8 => BinValue::U8(self.u8()?),
+            BinType::I16 => BinValue::I16(self.u16()? as i16),
+            BinType::U16 => BinValue::U16(self.u16()?),
+            BinType::I32 => BinValue::I32(self.u32()? as i32),
+            BinType::U32 => BinValue::U32(self.u32()?),
+            BinType::I64 => BinValue::I64(self.u64()? as i64),
+            BinType::U64 => BinValue::U64(self.u64()?),
+            BinType::F32 => BinValue::F32(self.f32s::<1>()?[0]),
+            BinType::Vec2 => BinValue::Vec2(self.f32s()?),
+            BinType::Vec3 => BinValue::Vec3(self.f32s()?),
+            BinType::Vec4 => BinValue::Vec4(self.f32s()?),
+            BinType::Mtx44 => BinValue::Mtx44(self.f32s()?),
+            BinType::Rgba => BinValue::Rgba(self.array()?),
+            BinType::String => BinValue::String(self.string()?),
+            BinType::Hash => BinValue::Hash(self.u32()?),
+            BinType::File => BinValue::File(self.u64()?),
+            BinType::List | BinType::List2 => {
+                let value_type = self.kind()?;
+                let _size = self.u32()?;
+                let count = self.u32()?;
+                let mut items = Vec::with_capacity(self.capacity(count as usize, 1));
+                for _ in 0..count {
+                    items.push(self.value(value_type)?);
+                }
+                BinValue::List { value_type, items }
+            }
+            BinType::Pointer | BinType::Embed => {
+                let class = self.u32()?;
+                let fields = if class == 0 {
+                    Vec::new()
+                } else {
+                    let _size = self.u32()?;
+                    self.fields()?
+                };
+                if kind == BinType::Pointer {
+                    BinValue::Pointer { class, fields }
+                } else {
+                    BinValue::Embed { class, fields }
+                }
+            }
+            BinType::Link => BinValue::Link(self.u32()?),
+            BinType::Option => {
+                let value_type = self.kind()?;
+                let value = match self.u8()? {
+                    0 => None,
+                    _ => Some(Box::new(self.value(value_type)?)),
+                };
+                BinValue::Option { value_type, value }
+            }
+            BinType::Map => {
+                let key_type = self.kind()?;
+                let value_type = self.kind()?;
+                let _size = self.u32()?;
+                let count = self.u32()?;
+                let mut entries = Vec::with_capacity(self.capacity(count as usize, 1));
+                for _ in 0..count {
+                    entries.push((self.value(key_type)?, self.value(value_type)?));
+                }
+                BinValue::Map { key_type, value_type, entries }
+            }
+            BinType::Flag => BinValue::Flag(self.u8()? != 0),
+        };
+        Ok(value)
+    }
+}
+
+impl PropBin {
+    pub fn parse(data: &[u8]) -> Result<PropBin, String> {
+        let mut reader = Reader { data, pos: 0, depth: 0 };
+        let mut magic = reader.array::<4>()?;
+        let is_patch = &magic == PTCH_MAGIC;
+        if is_patch {
+            let _unknown = reader.u64()?;
+            magic = reader.array()?;
+        }
+        if &magic != PROP_MAGIC {
+            return Err("Fichier bin invalide (signature PROP absente)".to_string());
+        }
+
+        let version = reader.u32()?;
+        let mut linked = Vec::new();
+        if version >= 2 {
+            for _ in 0..reader.u32()? {
+                linked.push(reader.string()?);
+            }
+        }
+
+        let count = reader.u32()? as usize;
+        let mut classes = Vec::with_capacity(reader.capacity(count, 4));
+        for _ in 0..count {
+            classes.push(reader.u32()?);
+        }
+        // Taille (u32), chemin (u32) et nombre de champs (u16) au minimum
+        let mut entries = Vec::with_capacity(reader.capacity(count, 10));
+        for class in classes {
+            let length = reader.u32()? as usize;
+            let start = reader.pos;
+            let path = reader.u32()?;
+            let fields = reader.fields()?;
+            if reader.pos != start + length {
+                return Err(format!("Taille incohérente pour l'entrée {:08x}", path));
+            }
+            entries.push(BinEntry { path, class, fields });
+        }
+
+        let mut patches = Vec::new();
+        if is_patch && reader.pos < data.len() {
+            for _ in 0..reader.u32()? {
+                let entry = reader.u32()?;
+                let _length = reader.u32()?;
+                let kind = reader.kind()?;
+                let path = reader.string()?;
+                let value = reader.value(kind)?;
+                patches.push(BinPatch { entry, path, value });
+            }
+        }
+
+        Ok(PropBin {
+            is_patch,
+            version,
+            linked,
+            entries,
+            patches,
+        })
+    }
+
+    // JSON lisible : noms résolus par la table de hash, sinon hash en hexadécimal
+    pub fn to_json(&self, table: &HashTable) -> Value {
+        let mut entries = Map::new();
+        for entry in &self.entries {
+            let mut object = fields_to_json(&entry.fields, table);
+            object.insert("__type".to_string(), Value::String(name32(entry.class, table)));
+            entries.insert(name32(entry.path, table), Value::Object(object));
+        }
+
+        let mut result = json!({
+            "type": if self.is_patch { "PTCH" } else { "PROP" },
+            "version": self.version,
+            "linked": self.linked,
+            "entries": entries,
+        });
+        if self.is_patch {
+            let patches: Vec<Value> = self
+                .patches
+                .iter()
+                .map(|patch| {
+                    json!({
+                        "entry": name32(patch.entry, table),
+                        "path": patch.path,
+                        "value": value_to_json(&patch.value, table),
+                    })
+                })
+                .collect();
+            result["patches"] = Value::Array(patches);
+        }
+        result
+    }
+}
+
+fn name32(hash: u32, table: &HashTable) -> String {
+    table
+        .get(hash as u64)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{{{:08x}}}", hash))
+}
+
+fn name64(hash: u64, table: &HashTable) -> String {
+    table
+        .get(hash)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{{{:016x}}}", hash))
+}
+
+fn fields_to_json(fields: &[BinField], table: &HashTable) -> Map<String, Value> {
+    fields
+        .iter()
+        .map(|field| (name32(field.name, table), value_to_json(&field.value, table)))
+        .collect()
+}
+
+// Clé de map JSON : les valeurs simples sont converties en texte
+fn key_to_string(value: &BinValue, table: &HashTable) -> String {
+    match value_to_json(value, table) {
+        Value::String(text) => text,
+        other => other.to_string(),
+    }
+}
+
+pub fn value_to_json(value: &BinValue, table: &HashTable) -> Value {
+    match value {
+        BinValue::None => Value::Null,
+        BinValue::Bool(v) | BinValue::Flag(v) => json!(v),
+        BinValue::I8(v) => json!(v),
+        BinValue::U8(v) => json!(v),
+        BinValue::I16(v) => json!(v),
+        BinValue::U16(v) => json!(v),
+        BinValue::I32(v) => json!(v),
+        BinValue::U32(v) => json!(v),
+        BinValue::I64(v) => json!(v),
+        BinValue::U64(v) => json!(v),
+        BinValue::F32(v) => json!(v),
+        BinValue::Vec2(v) => json!(v),
+        BinValue::Vec3(v) => json!(v),
+        BinValue::Vec4(v) => json!(v),
+        BinValue::Mtx44(v) => json!(v.to_vec()),
+        BinValue::Rgba(v) => json!(v),
+        BinValue::String(v) => json!(v),
+        BinValue::Hash(v) | BinValue::Link(v) => json!(name32(*v, table)),
+        BinValue::File(v) => json!(name64(*v, table)),
+        BinValue::List { items, .. } => Value::Array(items.iter().map(|item| value_to_json(item, table)).collect()),
+        BinValue::Pointer { class: 0, .. } | BinValue::Embed { class: 0, .. } => Value::Null,
+        BinValue::Pointer { class, fields } | BinValue::Embed { class, fields } => {
+            let mut object = fields_to_json(fields, table);
+            object.insert("__type".to_string(), Value::String(name32(*class, table)));
+            Value::Object(object)
+        }
+        BinValue::Option { value, .. } => value.as_ref().map(|v| value_to_json(v, table)).unwrap_or(Value::Null),
+        BinValue::Map { entries, .. } => Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| (key_to_string(key, table), value_to_json(value, table)))
+                .collect(),
+        ),
+    }
+}
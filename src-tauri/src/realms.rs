@@ -0,0 +1,159 @@
+// Registre des serveurs (realms) : identifiants canoniques, noms, type et région de plateforme
+use crate::products::Channel;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const DEFAULT_PREFERRED_REALM: &str = "EUW1";
+
+// Regroupement des serveurs par région de routage Riot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlatformRegion {
+    Americas,
+    Europe,
+    Asia,
+    Sea,
+    Unknown,
+}
+
+struct RealmDefinition {
+    id: &'static str,
+    name: &'static str,
+    kind: Channel,
+    region: PlatformRegion,
+    aliases: &'static [&'static str],
+}
+
+const fn live(id: &'static str, name: &'static str, region: PlatformRegion, aliases: &'static [&'static str]) -> RealmDefinition {
+    RealmDefinition {
+        id,
+        name,
+        kind: Channel::Live,
+        region,
+        aliases,
+    }
+}
+
+const REGISTRY: &[RealmDefinition] = &[
+    live("NA1", "Amérique du Nord", PlatformRegion::Americas, &["NA"]),
+    live("BR1", "Brésil", PlatformRegion::Americas, &["BR"]),
+    live("LA1", "Amérique latine Nord", PlatformRegion::Americas, &["LAN"]),
+    live("LA2", "Amérique latine Sud", PlatformRegion::Americas, &["LAS"]),
+    live("EUW1", "Europe Ouest", PlatformRegion::Europe, &["EUW"]),
+    live("EUN1", "Europe Nord & Est", PlatformRegion::Europe, &["EUNE", "EUN"]),
+    live("TR1", "Turquie", PlatformRegion::Europe, &["TR"]),
+    live("RU", "Russie", PlatformRegion::Europe, &["RU1"]),
+    live("ME1", "Moyen-Orient", PlatformRegion::Europe, &["ME"]),
+    live("KR", "Corée", PlatformRegion::Asia, &["KR1"]),
+    live("JP1", "Japon", PlatformRegion::Asia, &["JP"]),
+    live("OC1", "Océanie", PlatformRegion::Sea, &["OCE"]),
+    live("PH2", "Philippines", PlatformRegion::Sea, &["PH"]),
+    live("SG2", "Singapour", PlatformRegion::Sea, &["SG"]),
+    live("TH2", "Thaïlande", PlatformRegion::Sea, &["TH"]),
+    live("TW2", "Taïwan", PlatformRegion::Sea, &["TW"]),
+    live("VN2", "Viêt Nam", PlatformRegion::Sea, &["VN"]),
+    RealmDefinition {
+        id: "PBE1",
+        name: "Public Beta Environment",
+        kind: Channel::Pbe,
+        region: PlatformRegion::Americas,
+        aliases: &["PBE"],
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Realm {
+    // Identifiant canonique ("EUW1"), ou le nom du catalogue tel quel s'il est inconnu
+    pub id: String,
+    pub name: String,
+    pub kind: Channel,
+    pub region: PlatformRegion,
+    // Présent dans le registre
+    pub known: bool,
+}
+
+impl Realm {
+    fn from_definition(definition: &RealmDefinition) -> Self {
+        Self {
+            id: definition.id.to_string(),
+            name: definition.name.to_string(),
+            kind: definition.kind,
+            region: definition.region,
+            known: true,
+        }
+    }
+
+    // Reconnaît les identifiants, les alias ("EUW", "NA") et les familles LIVESTAGING / LOLTMNT
+    pub fn parse(text: &str) -> Self {
+        let id = text.trim().to_string();
+        let upper = id.to_uppercase();
+        if let Some(definition) = REGISTRY
+            .iter()
+            .find(|definition| definition.id == upper || definition.aliases.contains(&upper.as_str()))
+        {
+            return Self::from_definition(definition);
+        }
+        let (name, kind) = if upper.contains("PBE") {
+            (format!("PBE ({})", id), Channel::Pbe)
+        } else if upper.contains("LIVESTAGING") {
+            (format!("Pré-production ({})", id), Channel::Staging)
+        } else if upper.contains("LOLTMNT") {
+            (format!("Serveur de tournoi ({})", id), Channel::Tournament)
+        } else {
+            (id.clone(), Channel::Live)
+        };
+        Self {
+            id,
+            name,
+            kind,
+            region: PlatformRegion::Unknown,
+            known: false,
+        }
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.kind == Channel::Live
+    }
+}
+
+// Deux écritures du même serveur ("EUW" et "euw1")
+pub fn same_realm(a: &str, b: &str) -> bool {
+    Realm::parse(a).id.eq_ignore_ascii_case(&Realm::parse(b).id)
+}
+
+// Tous les serveurs du registre
+pub fn registry() -> Vec<Realm> {
+    REGISTRY.iter().map(Realm::from_definition).collect()
+}
+
+// Serveurs regroupés par région, les serveurs live d'abord puis par identifiant
+pub fn group_by_region(realms: &[Realm]) -> BTreeMap<PlatformRegion, Vec<Realm>> {
+    let mut groups: BTreeMap<PlatformRegion, Vec<Realm>> = BTreeMap::new();
+    for realm in realms {
+        let group = groups.entry(realm.region).or_default();
+        if !group.iter().any(|r| r.id.eq_ignore_ascii_case(&realm.id)) {
+            group.push(realm.clone());
+        }
+    }
+    for group in groups.values_mut() {
+        group.sort_by(|a, b| (!a.is_live(), &a.id).cmp(&(!b.is_live(), &b.id)));
+    }
+    groups
+}
+
+// Serveur à utiliser parmi ceux disponibles : le préféré, sinon un serveur live de la même région, sinon le premier live
+pub fn pick_preferred(available: &[Realm], preferred: &str) -> Option<Realm> {
+    let preferred = Realm::parse(preferred);
+    available
+        .iter()
+        .find(|realm| realm.id.eq_ignore_ascii_case(&preferred.id))
+        .or_else(|| {
+            available
+                .iter()
+                .find(|realm| realm.is_live() && realm.known && realm.region == preferred.region)
+        })
+        .or_else(|| available.iter().find(|realm| realm.is_live()))
+        .or_else(|| available.first())
+        .cloned()
+}
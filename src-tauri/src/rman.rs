@@ -0,0 +1,364 @@
+// Lecture des manifestes RMAN de Riot (en-tête + corps flatbuffer compressé en zstd)
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::HashMap;
+
+pub const RMAN_MAGIC: &[u8; 4] = b"RMAN";
+pub const RMAN_HEADER_SIZE: usize = 28;
+
+// Types de hash utilisés pour identifier les chunks
+pub const HASH_TYPE_NONE: u8 = 0;
+pub const HASH_TYPE_SHA512: u8 = 1;
+pub const HASH_TYPE_SHA256: u8 = 2;
+pub const HASH_TYPE_RITO_HKDF: u8 = 3;
+
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub id: u64,
+    pub bundle_id: u64,
+    pub bundle_offset: u64,
+    pub compressed_size: u32,
+    pub uncompressed_size: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ManifestFile {
+    pub id: u64,
+    pub path: String,
+    pub size: u64,
+    pub languages: Vec<String>,
+    pub chunk_ids: Vec<u64>,
+    pub link: String,
+    pub hash_type: u8,
+}
+
+impl ManifestFile {
+    // Position de chaque chunk dans le fichier final
+    pub fn chunk_offsets(&self, manifest: &Manifest) -> Vec<(u64, u64)> {
+        let mut offset = 0u64;
+        self.chunk_ids
+            .iter()
+            .map(|id| {
+                let start = offset;
+                offset += manifest
+                    .chunks
+                    .get(id)
+                    .map(|c| c.uncompressed_size as u64)
+                    .unwrap_or(0);
+                (*id, start)
+            })
+            .collect()
+    }
+
+    // Un fichier sans langue est "international" et toujours inclus
+    pub fn matches_language(&self, language: &str) -> bool {
+        if self.languages.is_empty() {
+            return true;
+        }
+        let language = language.trim();
+        if language.is_empty() || language.eq_ignore_ascii_case("none") {
+            return false;
+        }
+        self.languages.iter().any(|l| l.eq_ignore_ascii_case(language))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pub id: u64,
+    pub languages: Vec<String>,
+    pub chunks: HashMap<u64, Chunk>,
+    pub files: Vec<ManifestFile>,
+}
+
+impl Manifest {
+    pub fn parse(data: &[u8]) -> Result<Manifest, String> {
+        if data.len() < RMAN_HEADER_SIZE || &data[0..4] != RMAN_MAGIC {
+            return Err("Fichier manifest invalide (signature RMAN absente)".to_string());
+        }
+        let major = data[4];
+        if major != 2 {
+            return Err(format!("Version de manifest non supportée: {}", major));
+        }
+        let offset = read_u32(data, 8)? as usize;
+        let compressed_len = read_u32(data, 12)? as usize;
+        let id = read_u64(data, 16)?;
+        let body_len = read_u32(data, 24)? as usize;
+
+        let compressed = data
+            .get(offset..offset + compressed_len)
+            .ok_or("Corps du manifest tronqué")?;
+        let body = zstd::bulk::decompress(compressed, body_len)
+            .map_err(|e| format!("Erreur de décompression du manifest: {}", e))?;
+
+        let mut manifest = Self::parse_body(&body)?;
+        manifest.id = id;
+        Ok(manifest)
+    }
+
+    fn parse_body(body: &[u8]) -> Result<Manifest, String> {
+        let root = Table::root(body)?;
+
+        let mut chunks = HashMap::new();
+        for bundle in root.tables(0)? {
+            let bundle_id = bundle.u64(0)?;
+            let mut bundle_offset = 0u64;
+            for chunk in bundle.tables(1)? {
+                let chunk = Chunk {
+                    id: chunk.u64(0)?,
+                    bundle_id,
+                    bundle_offset,
+                    compressed_size: chunk.u32(1)?,
+                    uncompressed_size: chunk.u32(2)?,
+                };
+                bundle_offset += chunk.compressed_size as u64;
+                chunks.insert(chunk.id, chunk);
+            }
+        }
+
+        let mut language_names = HashMap::new();
+        for language in root.tables(1)? {
+            language_names.insert(language.u8(0)?, language.string(1)?);
+        }
+
+        let mut directories = HashMap::new();
+        for directory in root.tables(3)? {
+            directories.insert(directory.u64(0)?, (directory.u64(1)?, directory.string(2)?));
+        }
+
+        let mut hash_types = Vec::new();
+        for params in root.tables(5)? {
+            hash_types.push(params.u8(1)?);
+        }
+
+        let mut files = Vec::new();
+        for file in root.tables(2)? {
+            let locale_flags = file.u64(4)?;
+            let mut languages: Vec<(u8, String)> = language_names
+                .iter()
+                .filter(|(id, _)| **id > 0 && **id <= 64 && locale_flags & (1u64 << (**id - 1)) != 0)
+                .map(|(id, name)| (*id, name.clone()))
+                .collect();
+            languages.sort();
+
+            let params_index = file.u8(11)? as usize;
+            files.push(ManifestFile {
+                id: file.u64(0)?,
+                path: build_path(&directories, file.u64(1)?, &file.string(3)?)?,
+                size: file.u32(2)? as u64,
+                languages: languages.into_iter().map(|(_, name)| name).collect(),
+                chunk_ids: file.u64s(7)?,
+                link: file.string(9)?,
+                hash_type: hash_types.get(params_index).copied().unwrap_or(HASH_TYPE_NONE),
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut languages: Vec<(u8, String)> = language_names.into_iter().collect();
+        languages.sort();
+
+        Ok(Manifest {
+            id: 0,
+            languages: languages.into_iter().map(|(_, name)| name).collect(),
+            chunks,
+            files,
+        })
+    }
+
+    pub fn file(&self, path: &str) -> Option<&ManifestFile> {
+        self.files.iter().find(|f| f.path == path)
+    }
+}
+
+// Reconstruire le chemin complet d'un fichier à partir de l'arbre des dossiers
+fn build_path(
+    directories: &HashMap<u64, (u64, String)>,
+    mut directory_id: u64,
+    name: &str,
+) -> Result<String, String> {
+    let mut parts = vec![name.to_string()];
+    let mut depth = 0;
+    while directory_id != 0 {
+        let (parent_id, dir_name) = directories
+            .get(&directory_id)
+            .ok_or_else(|| format!("Dossier {:016X} introuvable dans le manifest", directory_id))?;
+        if !dir_name.is_empty() {
+            parts.push(dir_name.clone());
+        }
+        directory_id = *parent_id;
+        depth += 1;
+        if depth > 256 {
+            return Err("Arborescence du manifest invalide (boucle)".to_string());
+        }
+    }
+    parts.reverse();
+    Ok(parts.join("/"))
+}
+
+// Calcul de l'identifiant d'un chunk selon le type de hash du manifest
+pub fn chunk_hash(data: &[u8], hash_type: u8) -> Option<u64> {
+    match hash_type {
+        HASH_TYPE_SHA512 => Some(first_u64(&Sha512::digest(data))),
+        HASH_TYPE_SHA256 => Some(first_u64(&Sha256::digest(data))),
+        HASH_TYPE_RITO_HKDF => Some(rito_hkdf(data)),
+        _ => None,
+    }
+}
+
+fn first_u64(digest: &[u8]) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+// Variante HMAC-SHA256 (32 tours) utilisée par les manifestes récents
+fn rito_hkdf(data: &[u8]) -> u64 {
+    let mut key = [0u8; 64];
+    key[..32].copy_from_slice(&Sha256::digest(data));
+
+    let mut ipad = [0u8; 64];
+    let mut opad = [0u8; 64];
+    for i in 0..64 {
+        ipad[i] = key[i] ^ 0x36;
+        opad[i] = key[i] ^ 0x5C;
+    }
+
+    let round = |input: &[u8]| -> [u8; 32] {
+        let inner = Sha256::new().chain_update(ipad).chain_update(input).finalize();
+        Sha256::new().chain_update(opad).chain_update(inner).finalize().into()
+    };
+
+    let mut buffer = round(&[0, 0, 0, 1]);
+    let mut result = buffer;
+    for _ in 0..31 {
+        buffer = round(&buffer);
+        for i in 0..8 {
+            result[i] ^= buffer[i];
+        }
+    }
+    first_u64(&result)
+}
+
+fn read_bytes<const N: usize>(data: &[u8], pos: usize) -> Result<[u8; N], String> {
+    data.get(pos..pos + N)
+        .map(|b| b.try_into().unwrap())
+        .ok_or_else(|| format!("Lecture hors limites à l'offset {}", pos))
+}
+
+fn read_u16(data: &[u8], pos: usize) -> Result<u16, String> {
+    Ok(u16::from_le_bytes(read_bytes(data, pos)?))
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Result<u32, String> {
+    Ok(u32::from_le_bytes(read_bytes(data, pos)?))
+}
+
+fn read_i32(data: &[u8], pos: usize) -> Result<i32, String> {
+    Ok(i32::from_le_bytes(read_bytes(data, pos)?))
+}
+
+fn read_u64(data: &[u8], pos: usize) -> Result<u64, String> {
+    Ok(u64::from_le_bytes(read_bytes(data, pos)?))
+}
+
+// Lecteur minimal de tables flatbuffers
+struct Table<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    vtable: usize,
+    vtable_len: usize,
+}
+
+impl<'a> Table<'a> {
+    fn root(buf: &'a [u8]) -> Result<Self, String> {
+        Self::at(buf, read_u32(buf, 0)? as usize)
+    }
+
+    fn at(buf: &'a [u8], pos: usize) -> Result<Self, String> {
+        let vtable = pos as i64 - read_i32(buf, pos)? as i64;
+        if vtable < 0 {
+            return Err("Vtable flatbuffer invalide".to_string());
+        }
+        let vtable = vtable as usize;
+        let vtable_len = read_u16(buf, vtable)? as usize;
+        Ok(Table { buf, pos, vtable, vtable_len })
+    }
+
+    fn field(&self, index: usize) -> Result<Option<usize>, String> {
+        let entry = 4 + index * 2;
+        if entry + 2 > self.vtable_len {
+            return Ok(None);
+        }
+        match read_u16(self.buf, self.vtable + entry)? {
+            0 => Ok(None),
+            offset => Ok(Some(self.pos + offset as usize)),
+        }
+    }
+
+    fn indirect(&self, index: usize) -> Result<Option<usize>, String> {
+        match self.field(index)? {
+            Some(pos) => Ok(Some(pos + read_u32(self.buf, pos)? as usize)),
+            None => Ok(None),
+        }
+    }
+
+    fn u8(&self, index: usize) -> Result<u8, String> {
+        match self.field(index)? {
+            Some(pos) => Ok(read_bytes::<1>(self.buf, pos)?[0]),
+            None => Ok(0),
+        }
+    }
+
+    fn u32(&self, index: usize) -> Result<u32, String> {
+        match self.field(index)? {
+            Some(pos) => read_u32(self.buf, pos),
+            None => Ok(0),
+        }
+    }
+
+    fn u64(&self, index: usize) -> Result<u64, String> {
+        match self.field(index)? {
+            Some(pos) => read_u64(self.buf, pos),
+            None => Ok(0),
+        }
+    }
+
+    fn string(&self, index: usize) -> Result<String, String> {
+        match self.indirect(index)? {
+            Some(pos) => {
+                let len = read_u32(self.buf, pos)? as usize;
+                let bytes = self
+                    .buf
+                    .get(pos + 4..pos + 4 + len)
+                    .ok_or("Chaîne flatbuffer tronquée")?;
+                Ok(String::from_utf8_lossy(bytes).to_string())
+            }
+            None => Ok(String::new()),
+        }
+    }
+
+    fn vector(&self, index: usize) -> Result<Option<(usize, usize)>, String> {
+        match self.indirect(index)? {
+            Some(pos) => Ok(Some((pos + 4, read_u32(self.buf, pos)? as usize))),
+            None => Ok(None),
+        }
+    }
+
+    fn tables(&self, index: usize) -> Result<Vec<Table<'a>>, String> {
+        let Some((start, len)) = self.vector(index)? else {
+            return Ok(Vec::new());
+        };
+        (0..len)
+            .map(|i| {
+                let pos = start + i * 4;
+                Table::at(self.buf, pos + read_u32(self.buf, pos)? as usize)
+            })
+            .collect()
+    }
+
+    fn u64s(&self, index: usize) -> Result<Vec<u64>, String> {
+        let Some((start, len)) = self.vector(index)? else {
+            return Ok(Vec::new());
+        };
+        (0..len).map(|i| read_u64(self.buf, start + i * 8)).collect()
+    }
+}
@@ -0,0 +1,197 @@
+// Paramètres de l'application persistés en JSON (format versionné)
+use crate::hashes::DEFAULT_HASH_LIST_URLS;
+use crate::output_template::{self, DEFAULT_OUTPUT_TEMPLATE};
+use crate::realms::DEFAULT_PREFERRED_REALM;
+use crate::stall::StallSettings;
+use crate::watcher::WatchSettings;
+use crate::webhooks::WebhookTarget;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+pub const SETTINGS_VERSION: u32 = 1;
+pub const SETTINGS_FILE_NAME: &str = "settings.json";
+pub const MAX_CONCURRENT_DOWNLOADS_LIMIT: u32 = 10;
+
+// Même forme que `AppSettings` dans src/types/index.ts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    pub download_path: String,
+    // Sous-dossier de chaque patch, ex: "{root}/{realm}/{version}/{manifest_hash}"
+    pub output_template: String,
+    pub language: String,
+    pub content_filter: String,
+    // Serveur affiché par défaut, ex: "EUW1"
+    pub preferred_realm: String,
+    pub max_concurrent_downloads: u32,
+    // Limite en Ko/s, 0 = illimité
+    pub download_speed: u64,
+    // Détection des téléchargements bloqués et redémarrage automatique
+    pub stall: StallSettings,
+    pub theme: String,
+    pub auto_start: bool,
+    pub notifications: bool,
+    // Listes "<hash> <chemin>" (URL ou fichier local) pour nommer les entrées WAD
+    pub hash_list_urls: Vec<String>,
+    // Téléchargement automatique des nouveaux patchs
+    pub watch: WatchSettings,
+    // Notifications des nouveaux patchs et des téléchargements terminés ou en échec
+    pub webhooks: Vec<WebhookTarget>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            download_path: String::new(),
+            output_template: DEFAULT_OUTPUT_TEMPLATE.to_string(),
+            language: "en_us".to_string(),
+            content_filter: String::new(),
+            preferred_realm: DEFAULT_PREFERRED_REALM.to_string(),
+            max_concurrent_downloads: 3,
+            download_speed: 0,
+            stall: StallSettings::default(),
+            theme: "dark".to_string(),
+            auto_start: false,
+            notifications: true,
+            hash_list_urls: DEFAULT_HASH_LIST_URLS.iter().map(|url| url.to_string()).collect(),
+            watch: WatchSettings::default(),
+            webhooks: Vec::new(),
+        }
+    }
+}
+
+impl AppSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !is_valid_language(&self.language) {
+            return Err(format!("Langue invalide: {}", self.language));
+        }
+        if self.max_concurrent_downloads == 0 || self.max_concurrent_downloads > MAX_CONCURRENT_DOWNLOADS_LIMIT {
+            return Err(format!(
+                "Le nombre de téléchargements simultanés doit être entre 1 et {}",
+                MAX_CONCURRENT_DOWNLOADS_LIMIT
+            ));
+        }
+        output_template::validate_template(&self.output_template)?;
+        if self.hash_list_urls.iter().any(|url| url.trim().is_empty()) {
+            return Err("Source de hash vide".to_string());
+        }
+        if self.preferred_realm.trim().is_empty() {
+            return Err("Serveur préféré vide".to_string());
+        }
+        self.stall.validate()?;
+        self.watch.validate()?;
+        for target in &self.webhooks {
+            target.validate()?;
+        }
+        if !self.watch.language.trim().is_empty() && !is_valid_language(&self.watch.language) {
+            return Err(format!("Langue invalide: {}", self.watch.language));
+        }
+        if self.theme != "light" && self.theme != "dark" {
+            return Err(format!("Thème invalide: {}", self.theme));
+        }
+        if !self.download_path.trim().is_empty() {
+            let path = Path::new(self.download_path.trim());
+            if path.exists() && !path.is_dir() {
+                return Err(format!("{} n'est pas un dossier", self.download_path));
+            }
+        }
+        Ok(())
+    }
+
+    // Limite de débit en octets par seconde pour le moteur
+    pub fn speed_limit_bytes(&self) -> u64 {
+        self.download_speed.saturating_mul(1024)
+    }
+}
+
+// Code langue du type "en_us" ou "none" pour les fichiers internationaux seulement
+fn is_valid_language(language: &str) -> bool {
+    if language == "none" {
+        return true;
+    }
+    let parts: Vec<&str> = language.split('_').collect();
+    parts.len() == 2
+        && parts
+            .iter()
+            .all(|p| p.len() == 2 && p.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SettingsFile {
+    version: u32,
+    settings: AppSettings,
+}
+
+// Convertit le contenu du fichier vers la version courante
+fn migrate(value: serde_json::Value) -> Result<AppSettings, String> {
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    match version {
+        // Fichier sans enveloppe : les paramètres sont à la racine
+        0 => serde_json::from_value(value).map_err(|e| e.to_string()),
+        1 => serde_json::from_value::<SettingsFile>(value)
+            .map(|file| file.settings)
+            .map_err(|e| e.to_string()),
+        v => Err(format!(
+            "Version de paramètres {} plus récente que celle supportée ({})",
+            v, SETTINGS_VERSION
+        )),
+    }
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Mutex<AppSettings>,
+}
+
+impl SettingsStore {
+    // Charge le fichier s'il existe, sinon (ou s'il est invalide) utilise les valeurs par défaut
+    pub fn load(path: PathBuf) -> Self {
+        let settings = match std::fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str(&content).map_err(|e| e.to_string()).and_then(migrate) {
+                Ok(settings) if settings.validate().is_ok() => settings,
+                Ok(_) | Err(_) => {
+                    println!("⚠️ Paramètres invalides dans {}, valeurs par défaut utilisées", path.display());
+                    let _ = std::fs::copy(&path, path.with_extension("json.bak"));
+                    AppSettings::default()
+                }
+            },
+            Err(_) => AppSettings::default(),
+        };
+        Self {
+            path,
+            settings: Mutex::new(settings),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub async fn get(&self) -> AppSettings {
+        self.settings.lock().await.clone()
+    }
+
+    pub async fn save(&self, settings: AppSettings) -> Result<(), String> {
+        settings.validate()?;
+        let mut current = self.settings.lock().await;
+
+        let file = SettingsFile {
+            version: SETTINGS_VERSION,
+            settings: settings.clone(),
+        };
+        let content = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+        }
+        // Écriture dans un fichier temporaire puis renommage pour ne jamais laisser un fichier à moitié écrit
+        let temp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&temp_path, content)
+            .and_then(|_| std::fs::rename(&temp_path, &self.path))
+            .map_err(|e| format!("Impossible d'enregistrer les paramètres: {}", e))?;
+
+        *current = settings;
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+// Détection des téléchargements bloqués : trop peu d'octets reçus sur une fenêtre glissante
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+pub const MIN_STALL_WINDOW_SECONDS: u64 = 10;
+pub const MAX_STALL_RESTARTS: u32 = 20;
+// Pause avant de relancer un téléchargement bloqué
+pub const STALL_RESTART_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StallSettings {
+    pub enabled: bool,
+    // Durée de la fenêtre glissante
+    pub window_seconds: u64,
+    // En dessous de ce nombre d'octets reçus sur la fenêtre, le téléchargement est bloqué
+    pub min_bytes: u64,
+    // Redémarrages automatiques depuis le point de reprise avant de passer la tâche en échec
+    pub max_restarts: u32,
+}
+
+impl Default for StallSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_seconds: 60,
+            min_bytes: 16 * 1024,
+            max_restarts: 3,
+        }
+    }
+}
+
+impl StallSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.window_seconds < MIN_STALL_WINDOW_SECONDS {
+            return Err(format!(
+                "La fenêtre de détection des blocages doit être d'au moins {} secondes",
+                MIN_STALL_WINDOW_SECONDS
+            ));
+        }
+        if self.max_restarts > MAX_STALL_RESTARTS {
+            return Err(format!("Au plus {} redémarrages automatiques", MAX_STALL_RESTARTS));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct StallDetector {
+    enabled: bool,
+    window: Duration,
+    min_bytes: u64,
+    // Relevés (instant, octets reçus) couvrant la fenêtre, le plus ancien servant de référence
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl StallDetector {
+    // Avec une limite de débit, le seuil ne dépasse pas la moitié de ce que la limite laisse passer
+    pub fn new(settings: &StallSettings, speed_limit: u64) -> Self {
+        let mut min_bytes = settings.min_bytes;
+        if speed_limit > 0 {
+            min_bytes = min_bytes.min(speed_limit.saturating_mul(settings.window_seconds) / 2);
+        }
+        Self {
+            enabled: settings.enabled,
+            window: Duration::from_secs(settings.window_seconds),
+            min_bytes,
+            samples: VecDeque::new(),
+        }
+    }
+
+    // Ajoute un relevé du compteur d'octets reçus ; vrai si moins du minimum est arrivé sur toute la fenêtre
+    pub fn observe(&mut self, at: Instant, received_bytes: u64) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        self.samples.push_back((at, received_bytes));
+        // Garder un seul relevé antérieur au début de la fenêtre
+        while self.samples.len() > 1 && at.duration_since(self.samples[1].0) >= self.window {
+            self.samples.pop_front();
+        }
+        let (since, baseline) = self.samples[0];
+        at.duration_since(since) >= self.window && received_bytes.saturating_sub(baseline) < self.min_bytes
+    }
+
+    // Nouvelle tentative : la fenêtre repart de zéro
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+}
@@ -0,0 +1,250 @@
+// Tables de textes localisés : fichiers .stringtable (RST) et anciens fontconfig, par langue
+use crate::hashes::HashTable;
+use crate::wad::{self, Wad};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+pub const RST_MAGIC: &[u8; 3] = b"RST";
+pub const DEFAULT_STRING_TABLE_SCOPES: &[&str] = &["DATA/FINAL/Localized/"];
+
+// Clé de texte -> texte, pour une langue
+pub type Strings = BTreeMap<String, String>;
+
+// Nombre de bits du hash de clé dans chaque entrée, le reste est la position du texte
+fn rst_hash_bits(version: u8) -> Result<u32, String> {
+    match version {
+        2 | 3 => Ok(40),
+        4 => Ok(39),
+        5 => Ok(38),
+        other => Err(format!("Version de stringtable non prise en charge : {}", other)),
+    }
+}
+
+// Hash d'une clé tel que stocké dans une stringtable : xxh64 de la clé en minuscules, xxh3 à partir de la v5
+pub fn rst_key_hash(key: &str, version: u8) -> Result<u64, String> {
+    let bits = rst_hash_bits(version)?;
+    let key = key.to_lowercase();
+    let hash = if version >= 5 {
+        xxhash_rust::xxh3::xxh3_64(key.as_bytes())
+    } else {
+        xxhash_rust::xxh64::xxh64(key.as_bytes(), 0)
+    };
+    Ok(hash & ((1u64 << bits) - 1))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| "Stringtable tronquée".to_string())
+}
+
+pub fn parse_rst(data: &[u8], table: &HashTable) -> Result<Strings, String> {
+    if !data.starts_with(RST_MAGIC) || data.len() < 4 {
+        return Err("Stringtable invalide (signature RST attendue)".to_string());
+    }
+    let version = data[3];
+    let bits = rst_hash_bits(version)?;
+    let mut position = 4;
+    // v2 : configuration de police optionnelle
+    if version == 2 {
+        let has_config = *data.get(position).ok_or("Stringtable tronquée")?;
+        position += 1;
+        if has_config != 0 {
+            position += 4 + read_u32(data, position)? as usize;
+        }
+    }
+    let count = read_u32(data, position)? as usize;
+    position += 4;
+    let entries = data
+        .get(position..position + count * 8)
+        .ok_or("Stringtable tronquée")?
+        .chunks_exact(8)
+        .map(|raw| u64::from_le_bytes(raw.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    position += count * 8;
+    if version < 5 {
+        position += 1;
+    }
+    let text = data.get(position..).ok_or("Stringtable tronquée")?;
+
+    let mut strings = Strings::new();
+    for entry in entries {
+        let hash = entry & ((1u64 << bits) - 1);
+        let offset = (entry >> bits) as usize;
+        let raw = text.get(offset..).ok_or_else(|| format!("Texte {:010x} hors du fichier", hash))?;
+        let end = raw.iter().position(|b| *b == 0).unwrap_or(raw.len());
+        let key = table
+            .get(hash)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{{{:010x}}}", hash));
+        strings.insert(key, String::from_utf8_lossy(&raw[..end]).to_string());
+    }
+    Ok(strings)
+}
+
+// Ancien format texte : lignes `tr "clé" = "texte"`
+pub fn parse_fontconfig(text: &str) -> Strings {
+    text.lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("tr ")?;
+            let (key, value) = rest.split_once('=')?;
+            let unquote = |s: &str| {
+                let s = s.trim();
+                s.strip_prefix('"').and_then(|s| s.strip_suffix('"')).map(str::to_string)
+            };
+            Some((unquote(key)?, unquote(value)?))
+        })
+        .collect()
+}
+
+pub fn is_string_table_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let file = name.rsplit('/').next().unwrap_or(&name);
+    file.ends_with(".stringtable") || (file.starts_with("fontconfig") && file.ends_with(".txt"))
+}
+
+fn parse_entry(name: Option<&str>, data: &[u8], table: &HashTable) -> Result<Option<Strings>, String> {
+    if data.starts_with(RST_MAGIC) {
+        return parse_rst(data, table).map(Some);
+    }
+    match name {
+        Some(name) if is_string_table_name(name) => Ok(Some(parse_fontconfig(&String::from_utf8_lossy(data)))),
+        _ => Ok(None),
+    }
+}
+
+// Textes de toutes les langues d'un patch, d'après les WAD localisés ("Global.fr_FR.wad.client")
+pub fn load_languages(
+    output_dir: &Path,
+    scopes: &[String],
+    languages: &[String],
+    table: &HashTable,
+) -> Result<BTreeMap<String, Strings>, String> {
+    let mut result: BTreeMap<String, Strings> = BTreeMap::new();
+    for path in wad::find_wads(output_dir)? {
+        let relative = path
+            .strip_prefix(output_dir)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let lower = relative.to_lowercase();
+        if !scopes.is_empty() && !scopes.iter().any(|scope| lower.starts_with(&scope.to_lowercase())) {
+            continue;
+        }
+        let Some(language) = wad::wad_locale(&relative) else {
+            continue;
+        };
+        if !languages.is_empty() && !languages.iter().any(|l| l.eq_ignore_ascii_case(language)) {
+            continue;
+        }
+
+        let mut wad = Wad::open(&path)?;
+        wad.load_subchunk_toc(&relative)?;
+        let strings = result.entry(language.to_string()).or_default();
+        for entry in &wad.entries {
+            let name = table.get(entry.path_hash);
+            // Entrées nommées : seulement les tables ; inconnues : reconnues à leur signature
+            if name.is_some_and(|name| !is_string_table_name(name)) {
+                continue;
+            }
+            if let Some(parsed) = parse_entry(name, &wad.read(entry)?, table)? {
+                strings.extend(parsed);
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StringTableSummary {
+    pub language: String,
+    pub strings: usize,
+    pub path: String,
+}
+
+// Écrit un fichier <langue>.json par langue
+pub fn write_languages(languages: &BTreeMap<String, Strings>, output_dir: &Path) -> Result<Vec<StringTableSummary>, String> {
+    std::fs::create_dir_all(output_dir).map_err(|e| format!("Impossible de créer {}: {}", output_dir.display(), e))?;
+    let mut summaries = Vec::new();
+    for (language, strings) in languages {
+        let path: PathBuf = output_dir.join(format!("{}.json", crate::output_template::sanitize_component(language)));
+        let content = serde_json::to_string_pretty(strings).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))?;
+        summaries.push(StringTableSummary {
+            language: language.clone(),
+            strings: strings.len(),
+            path: path.to_string_lossy().to_string(),
+        });
+    }
+    Ok(summaries)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StringEntry {
+    pub key: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RewordedString {
+    pub key: String,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LanguageDiff {
+    pub language: String,
+    pub added: Vec<StringEntry>,
+    pub removed: Vec<StringEntry>,
+    pub reworded: Vec<RewordedString>,
+    pub unchanged: usize,
+}
+
+pub fn diff_strings(language: &str, old: &Strings, new: &Strings) -> LanguageDiff {
+    let mut diff = LanguageDiff {
+        language: language.to_string(),
+        ..Default::default()
+    };
+    for (key, text) in new {
+        match old.get(key) {
+            None => diff.added.push(StringEntry {
+                key: key.clone(),
+                text: text.clone(),
+            }),
+            Some(previous) if previous != text => diff.reworded.push(RewordedString {
+                key: key.clone(),
+                old: previous.clone(),
+                new: text.clone(),
+            }),
+            Some(_) => diff.unchanged += 1,
+        }
+    }
+    for (key, text) in old {
+        if !new.contains_key(key) {
+            diff.removed.push(StringEntry {
+                key: key.clone(),
+                text: text.clone(),
+            });
+        }
+    }
+    diff
+}
+
+// Une différence par langue présente dans l'un ou l'autre patch
+pub fn diff_languages(old: &BTreeMap<String, Strings>, new: &BTreeMap<String, Strings>) -> Vec<LanguageDiff> {
+    let empty = Strings::new();
+    let languages: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    languages
+        .into_iter()
+        .map(|language| {
+            diff_strings(
+                language,
+                old.get(language).unwrap_or(&empty),
+                new.get(language).unwrap_or(&empty),
+            )
+        })
+        .collect()
+}
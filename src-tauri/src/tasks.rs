@@ -0,0 +1,228 @@
+// Tâches de téléchargement et d'export : identifiants uniques, recherche et pagination
+use crate::realms;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use ulid::Ulid;
+
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+// Cycle de vie d'une tâche : en file → téléchargement → pause / vérification → terminée, en échec ou annulée
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    // Anciens noms acceptés à la lecture
+    #[default]
+    #[serde(alias = "pending")]
+    Queued,
+    Downloading,
+    Paused,
+    Verifying,
+    Completed,
+    #[serde(alias = "error")]
+    Failed,
+    Cancelled,
+}
+
+impl TaskStatus {
+    pub fn id(&self) -> &'static str {
+        match self {
+            TaskStatus::Queued => "queued",
+            TaskStatus::Downloading => "downloading",
+            TaskStatus::Paused => "paused",
+            TaskStatus::Verifying => "verifying",
+            TaskStatus::Completed => "completed",
+            TaskStatus::Failed => "failed",
+            TaskStatus::Cancelled => "cancelled",
+        }
+    }
+
+    // En file, en cours, en pause ou en vérification : la tâche occupe encore son dossier
+    pub fn is_active(&self) -> bool {
+        matches!(self, TaskStatus::Queued | TaskStatus::Downloading | TaskStatus::Paused | TaskStatus::Verifying)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.is_active()
+    }
+
+    pub fn can_transition_to(&self, next: TaskStatus) -> bool {
+        use TaskStatus::*;
+        match self {
+            Queued => matches!(next, Downloading | Paused | Failed | Cancelled),
+            Downloading => matches!(next, Paused | Verifying | Completed | Failed | Cancelled),
+            Paused => matches!(next, Queued | Cancelled),
+            Verifying => matches!(next, Completed | Failed | Cancelled),
+            // Revérification d'une tâche terminée, reprise d'une tâche en échec
+            Completed => matches!(next, Verifying),
+            Failed => matches!(next, Queued | Verifying),
+            Cancelled => false,
+        }
+    }
+}
+
+impl fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+// Sort des fichiers d'une tâche annulée
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CancelMode {
+    // Conservés pour une reprise par un nouveau téléchargement du même patch
+    #[default]
+    Keep,
+    // Suppression des fichiers et dossiers créés par la tâche, d'après son journal
+    Delete,
+}
+
+// Changement de statut horodaté
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusChange {
+    pub status: TaskStatus,
+    pub at: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DownloadTask {
+    pub id: String,
+    pub manifest: String,
+    pub version: String,
+    pub status: TaskStatus,
+    pub progress: f64,
+    pub speed: String,
+    pub eta: String,
+    pub error: Option<String>,
+    pub start_time: String,
+    pub end_time: Option<String>,
+    pub output_path: Option<String>,
+    // Serveur du manifest dans le catalogue, s'il y figure
+    #[serde(default)]
+    pub realm: Option<String>,
+    // Statuts successifs, le premier étant la mise en file
+    #[serde(default)]
+    pub history: Vec<StatusChange>,
+    // Redémarrages automatiques après un blocage
+    #[serde(default)]
+    pub stall_restarts: u32,
+    // Langue et filtre retenus au lancement, repris par la reprise et la vérification
+    #[serde(default)]
+    pub language: String,
+    #[serde(default)]
+    pub content_filter: String,
+}
+
+impl DownloadTask {
+    pub fn new(id: String, manifest: String, version: String, output_path: Option<String>, realm: Option<String>) -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        Self {
+            id,
+            manifest,
+            version,
+            status: TaskStatus::Queued,
+            progress: 0.0,
+            speed: "0 MB/s".to_string(),
+            eta: "--".to_string(),
+            error: None,
+            start_time: now.clone(),
+            end_time: None,
+            output_path,
+            realm,
+            history: vec![StatusChange {
+                status: TaskStatus::Queued,
+                at: now,
+                reason: None,
+            }],
+            stall_restarts: 0,
+            language: String::new(),
+            content_filter: String::new(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status.is_active()
+    }
+
+    // Change de statut si la transition est permise et l'ajoute à l'historique ; rester dans le même statut ne fait rien
+    pub fn transition(&mut self, next: TaskStatus, reason: Option<String>) -> Result<(), String> {
+        if self.status == next {
+            return Ok(());
+        }
+        if !self.status.can_transition_to(next) {
+            return Err(format!("Tâche {} : passage de {} à {} impossible", self.id, self.status, next));
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        self.status = next;
+        if next != TaskStatus::Downloading {
+            self.speed = "0 MB/s".to_string();
+            self.eta = "--".to_string();
+        }
+        self.end_time = next.is_finished().then(|| now.clone());
+        self.history.push(StatusChange { status: next, at: now, reason });
+        Ok(())
+    }
+}
+
+// Identifiant ULID préfixé ("task_01J...", "export_01J...") : unique même pour deux clics dans la même milliseconde, et trié par date de création
+pub fn new_task_id(prefix: &str) -> String {
+    format!("{}_{}", prefix, Ulid::new())
+}
+
+// Critères de `list_downloads`, tous facultatifs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TaskQuery {
+    // Statuts acceptés, vide = tous
+    pub status: Vec<TaskStatus>,
+    pub realm: Option<String>,
+    // Recherche dans la version affichée
+    pub version: Option<String>,
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskPage {
+    pub tasks: Vec<DownloadTask>,
+    // Nombre de tâches correspondant aux critères, toutes pages confondues
+    pub total: usize,
+    pub offset: usize,
+}
+
+impl TaskQuery {
+    pub fn matches(&self, task: &DownloadTask) -> bool {
+        let status = self.status.is_empty() || self.status.contains(&task.status);
+        let realm = self
+            .realm
+            .as_deref()
+            .filter(|r| !r.trim().is_empty())
+            .map_or(true, |r| task.realm.as_deref().is_some_and(|t| realms::same_realm(r, t)));
+        let version = self
+            .version
+            .as_deref()
+            .filter(|v| !v.trim().is_empty())
+            .map_or(true, |v| task.version.to_lowercase().contains(&v.trim().to_lowercase()));
+        status && realm && version
+    }
+
+    // Tâches correspondantes, les plus récentes d'abord
+    pub fn page<'a>(&self, tasks: impl IntoIterator<Item = &'a DownloadTask>) -> TaskPage {
+        let mut matching: Vec<&DownloadTask> = tasks.into_iter().filter(|task| self.matches(task)).collect();
+        matching.sort_by(|a, b| (&b.start_time, &b.id).cmp(&(&a.start_time, &a.id)));
+        TaskPage {
+            total: matching.len(),
+            offset: self.offset,
+            tasks: matching
+                .into_iter()
+                .skip(self.offset)
+                .take(self.limit.unwrap_or(DEFAULT_PAGE_SIZE))
+                .cloned()
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,643 @@
+// Décodage des textures .tex (format Riot) et .dds vers PNG : BC1, BC3, BC7 et formats non compressés
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub const TEX_MAGIC: &[u8; 4] = b"TEX\0";
+pub const DDS_MAGIC: &[u8; 4] = b"DDS ";
+const TEX_HEADER_SIZE: usize = 12;
+const DDS_HEADER_SIZE: usize = 128;
+const DDS_DX10_HEADER_SIZE: usize = 20;
+const TEXTURE_EXTENSIONS: &[&str] = &["tex", "dds"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFormat {
+    Bc1,
+    Bc3,
+    Bc7,
+    Bgra8,
+    Rgba8,
+    // Pixels non compressés décrits par des masques (DDS classiques 16/24/32 bits)
+    Masked { bytes: usize, masks: [u32; 4] },
+}
+
+impl TextureFormat {
+    // Taille d'un niveau, None si elle dépasse l'espace adressable (dimensions aberrantes)
+    fn level_size(&self, width: u32, height: u32) -> Option<usize> {
+        let blocks = (width.div_ceil(4) as usize).checked_mul(height.div_ceil(4) as usize)?;
+        let pixels = (width as usize).checked_mul(height as usize)?;
+        match self {
+            TextureFormat::Bc1 => blocks.checked_mul(8),
+            TextureFormat::Bc3 | TextureFormat::Bc7 => blocks.checked_mul(16),
+            TextureFormat::Bgra8 | TextureFormat::Rgba8 => pixels.checked_mul(4),
+            TextureFormat::Masked { bytes, .. } => pixels.checked_mul(*bytes),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Texture<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+    pub mip_count: u32,
+    // Les .tex stockent les niveaux du plus petit au plus grand, les .dds l'inverse
+    smallest_first: bool,
+    data: &'a [u8],
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn full_mip_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+impl<'a> Texture<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Texture<'a>, String> {
+        if data.starts_with(TEX_MAGIC) {
+            Texture::parse_tex(data)
+        } else if data.starts_with(DDS_MAGIC) {
+            Texture::parse_dds(data)
+        } else {
+            Err("Texture inconnue : signature TEX ou DDS attendue".to_string())
+        }
+    }
+
+    fn parse_tex(data: &'a [u8]) -> Result<Texture<'a>, String> {
+        if data.len() < TEX_HEADER_SIZE {
+            return Err("En-tête TEX tronqué".to_string());
+        }
+        let (width, height) = (read_u16(data, 4) as u32, read_u16(data, 6) as u32);
+        let format = match data[9] {
+            10 | 11 => TextureFormat::Bc1,
+            12 => TextureFormat::Bc3,
+            20 => TextureFormat::Bgra8,
+            other => return Err(format!("Format TEX non pris en charge : {}", other)),
+        };
+        let mip_count = if data[11] & 1 != 0 { full_mip_count(width, height) } else { 1 };
+        Ok(Texture {
+            width,
+            height,
+            format,
+            mip_count,
+            smallest_first: true,
+            data: &data[TEX_HEADER_SIZE..],
+        })
+    }
+
+    fn parse_dds(data: &'a [u8]) -> Result<Texture<'a>, String> {
+        if data.len() < DDS_HEADER_SIZE {
+            return Err("En-tête DDS tronqué".to_string());
+        }
+        let (height, width) = (read_u32(data, 12), read_u32(data, 16));
+        // Un en-tête corrompu peut annoncer plus de niveaux que les dimensions n'en permettent
+        let mip_count = read_u32(data, 28).clamp(1, full_mip_count(width, height));
+        let flags = read_u32(data, 80);
+        let mut offset = DDS_HEADER_SIZE;
+
+        let format = if flags & 0x4 != 0 {
+            match &data[84..88] {
+                b"DXT1" => TextureFormat::Bc1,
+                b"DXT5" => TextureFormat::Bc3,
+                b"DX10" => {
+                    if data.len() < DDS_HEADER_SIZE + DDS_DX10_HEADER_SIZE {
+                        return Err("En-tête DDS DX10 tronqué".to_string());
+                    }
+                    offset += DDS_DX10_HEADER_SIZE;
+                    match read_u32(data, DDS_HEADER_SIZE) {
+                        71 | 72 => TextureFormat::Bc1,
+                        77 | 78 => TextureFormat::Bc3,
+                        98 | 99 => TextureFormat::Bc7,
+                        28 | 29 => TextureFormat::Rgba8,
+                        87 | 91 => TextureFormat::Bgra8,
+                        other => return Err(format!("Format DXGI non pris en charge : {}", other)),
+                    }
+                }
+                other => return Err(format!("FourCC DDS non pris en charge : {}", String::from_utf8_lossy(other))),
+            }
+        } else if flags & 0x40 != 0 {
+            let bits = read_u32(data, 88);
+            if !matches!(bits, 16 | 24 | 32) {
+                return Err(format!("Profondeur DDS non prise en charge : {} bits", bits));
+            }
+            let alpha = if flags & 0x1 != 0 { read_u32(data, 104) } else { 0 };
+            TextureFormat::Masked {
+                bytes: bits as usize / 8,
+                masks: [read_u32(data, 92), read_u32(data, 96), read_u32(data, 100), alpha],
+            }
+        } else {
+            return Err("Format de pixels DDS non pris en charge".to_string());
+        };
+
+        Ok(Texture {
+            width,
+            height,
+            format,
+            mip_count,
+            smallest_first: false,
+            data: &data[offset..],
+        })
+    }
+
+    pub fn level_dimensions(&self, level: u32) -> (u32, u32) {
+        let shift = |size: u32| size.checked_shr(level).unwrap_or(0).max(1);
+        (shift(self.width), shift(self.height))
+    }
+
+    fn level_data(&self, level: u32) -> Result<&'a [u8], String> {
+        if level >= self.mip_count {
+            return Err(format!("Niveau de mipmap {} absent ({} niveaux)", level, self.mip_count));
+        }
+        let overflow = || format!("Taille du niveau {} hors limites", level);
+        let size_of = |l: u32| {
+            let (w, h) = self.level_dimensions(l);
+            self.format.level_size(w, h)
+        };
+        let preceding = if self.smallest_first { level + 1..self.mip_count } else { 0..level };
+        let offset = preceding
+            .map(size_of)
+            .try_fold(0usize, |total, size| total.checked_add(size?))
+            .ok_or_else(overflow)?;
+        let size = size_of(level).ok_or_else(overflow)?;
+        let end = offset.checked_add(size).ok_or_else(overflow)?;
+        self.data
+            .get(offset..end)
+            .ok_or_else(|| format!("Données du niveau {} tronquées", level))
+    }
+
+    // Décode un niveau de mipmap (0 = pleine résolution)
+    pub fn decode(&self, level: u32) -> Result<Image, String> {
+        let data = self.level_data(level)?;
+        let (width, height) = self.level_dimensions(level);
+        let rgba = match self.format {
+            TextureFormat::Bc1 => decode_blocks(data, width, height, 8, |block, out| decode_bc1(block, out, true)),
+            TextureFormat::Bc3 => decode_blocks(data, width, height, 16, decode_bc3),
+            TextureFormat::Bc7 => decode_blocks(data, width, height, 16, decode_bc7),
+            TextureFormat::Rgba8 => data.to_vec(),
+            TextureFormat::Bgra8 => data.chunks_exact(4).flat_map(|p| [p[2], p[1], p[0], p[3]]).collect(),
+            TextureFormat::Masked { bytes, masks } => data
+                .chunks_exact(bytes)
+                .flat_map(|p| {
+                    let mut raw = [0u8; 4];
+                    raw[..bytes].copy_from_slice(p);
+                    let pixel = u32::from_le_bytes(raw);
+                    [
+                        masked_channel(pixel, masks[0]).unwrap_or(0),
+                        masked_channel(pixel, masks[1]).unwrap_or(0),
+                        masked_channel(pixel, masks[2]).unwrap_or(0),
+                        masked_channel(pixel, masks[3]).unwrap_or(255),
+                    ]
+                })
+                .collect(),
+        };
+        Ok(Image { width, height, rgba })
+    }
+}
+
+fn masked_channel(pixel: u32, mask: u32) -> Option<u8> {
+    if mask == 0 {
+        return None;
+    }
+    let shift = mask.trailing_zeros();
+    let max = (mask >> shift) as u64;
+    Some((((pixel & mask) >> shift) as u64 * 255 / max) as u8)
+}
+
+// Parcourt les blocs 4x4 et recopie les pixels visibles (les bords peuvent dépasser l'image)
+fn decode_blocks(data: &[u8], width: u32, height: u32, block_size: usize, decode: impl Fn(&[u8], &mut [[u8; 4]; 16])) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let blocks_x = width.div_ceil(4);
+    let mut rgba = vec![0u8; width * height * 4];
+    let mut pixels = [[0u8; 4]; 16];
+    for (i, block) in data.chunks_exact(block_size).enumerate() {
+        decode(block, &mut pixels);
+        let (bx, by) = ((i % blocks_x) * 4, (i / blocks_x) * 4);
+        for (p, pixel) in pixels.iter().enumerate() {
+            let (x, y) = (bx + p % 4, by + p / 4);
+            if x < width && y < height {
+                let offset = (y * width + x) * 4;
+                rgba[offset..offset + 4].copy_from_slice(pixel);
+            }
+        }
+    }
+    rgba
+}
+
+fn rgb565(color: u16) -> [u8; 4] {
+    let (r, g, b) = ((color >> 11) & 31, (color >> 5) & 63, color & 31);
+    [
+        ((r << 3) | (r >> 2)) as u8,
+        ((g << 2) | (g >> 4)) as u8,
+        ((b << 3) | (b >> 2)) as u8,
+        255,
+    ]
+}
+
+// Bloc couleur BC1 ; dans BC3 il est toujours interprété en mode 4 couleurs
+fn decode_bc1(block: &[u8], out: &mut [[u8; 4]; 16], allow_alpha: bool) {
+    let (c0, c1) = (read_u16(block, 0), read_u16(block, 2));
+    let (a, b) = (rgb565(c0), rgb565(c1));
+    let mix = |wa: u16, wb: u16| -> [u8; 4] {
+        let total = wa + wb;
+        let channel = |i: usize| ((a[i] as u16 * wa + b[i] as u16 * wb) / total) as u8;
+        [channel(0), channel(1), channel(2), 255]
+    };
+    let palette = if c0 > c1 || !allow_alpha {
+        [a, b, mix(2, 1), mix(1, 2)]
+    } else {
+        [a, b, mix(1, 1), [0, 0, 0, 0]]
+    };
+    let indices = read_u32(block, 4);
+    for (i, pixel) in out.iter_mut().enumerate() {
+        *pixel = palette[((indices >> (i * 2)) & 3) as usize];
+    }
+}
+
+fn decode_bc3(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    decode_bc1(&block[8..], out, false);
+    let (a0, a1) = (block[0] as u32, block[1] as u32);
+    let mut alphas = [a0, a1, 0, 0, 0, 0, 0, 255];
+    if a0 > a1 {
+        for i in 1..7 {
+            alphas[i + 1] = ((7 - i as u32) * a0 + i as u32 * a1) / 7;
+        }
+    } else {
+        for i in 1..5 {
+            alphas[i + 1] = ((5 - i as u32) * a0 + i as u32 * a1) / 5;
+        }
+    }
+    let mut bits = [0u8; 8];
+    bits[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(bits);
+    for (i, pixel) in out.iter_mut().enumerate() {
+        pixel[3] = alphas[((indices >> (i * 3)) & 7) as usize] as u8;
+    }
+}
+
+// Modes BC7 : sous-ensembles, bits de partition, de rotation, de sélection d'index,
+// bits de couleur, d'alpha, p-bits par extrémité / partagés, bits d'index primaires et secondaires
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    index_bits2: u32,
+}
+
+const fn mode(values: (usize, u32, u32, u32, u32, u32, bool, bool, u32, u32)) -> Bc7Mode {
+    Bc7Mode {
+        subsets: values.0,
+        partition_bits: values.1,
+        rotation_bits: values.2,
+        index_selection_bits: values.3,
+        color_bits: values.4,
+        alpha_bits: values.5,
+        endpoint_pbits: values.6,
+        shared_pbits: values.7,
+        index_bits: values.8,
+        index_bits2: values.9,
+    }
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    mode((3, 4, 0, 0, 4, 0, true, false, 3, 0)),
+    mode((2, 6, 0, 0, 6, 0, false, true, 3, 0)),
+    mode((3, 6, 0, 0, 5, 0, false, false, 2, 0)),
+    mode((2, 6, 0, 0, 7, 0, true, false, 2, 0)),
+    mode((1, 0, 2, 1, 5, 6, false, false, 2, 3)),
+    mode((1, 0, 2, 0, 7, 8, false, false, 2, 2)),
+    mode((1, 0, 0, 0, 7, 7, true, false, 4, 0)),
+    mode((2, 6, 0, 0, 5, 5, true, false, 2, 0)),
+];
+
+// Partitions à 2 sous-ensembles : bit i = sous-ensemble du pixel i
+const BC7_PARTITIONS2: [u16; 64] = [
+    0xCCCC, 0x8888, 0xEEEE, 0xECC8, 0xC880, 0xFEEC, 0xFEC8, 0xEC80, 0xC800, 0xFFEC, 0xFE80, 0xE800, 0xFFE8, 0xFF00, 0xFFF0,
+    0xF000, 0xF710, 0x008E, 0x7100, 0x08CE, 0x008C, 0x7310, 0x3100, 0x8CCE, 0x088C, 0x3110, 0x6666, 0x366C, 0x17E8, 0x0FF0,
+    0x718E, 0x399C, 0xAAAA, 0xF0F0, 0x5A5A, 0x33CC, 0x3C3C, 0x55AA, 0x9696, 0xA55A, 0x73CE, 0x13C8, 0x324C, 0x3BDC, 0x6996,
+    0xC33C, 0x9966, 0x0660, 0x0272, 0x04E4, 0x4E40, 0x2720, 0xC936, 0x936C, 0x39C6, 0x639C, 0x9336, 0x9CC6, 0x817E, 0xE718,
+    0xCCF0, 0x0FCC, 0x7744, 0xEE22,
+];
+
+const BC7_PARTITIONS3: [[u8; 16]; 64] = [
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 1, 2, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 2, 0, 0, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2],
+    [0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0, 2, 2, 2, 0],
+    [0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2],
+    [0, 1, 1, 1, 0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0],
+    [0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2, 0, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 0, 1, 2, 2, 2, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 0, 0, 1, 1, 0, 0, 2, 2, 1, 0, 2, 2, 1, 0],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1, 0, 0, 0, 0],
+    [0, 0, 1, 2, 0, 0, 1, 2, 1, 1, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1, 0, 1, 1, 0],
+    [0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1],
+    [0, 0, 2, 2, 1, 1, 0, 2, 1, 1, 0, 2, 0, 0, 2, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 0, 0, 2, 2, 2, 2, 2],
+    [0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 0, 0, 2, 0, 0, 0, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 2, 0, 0, 2, 2, 0, 2, 2, 2],
+    [0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0],
+    [0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0],
+    [0, 1, 2, 0, 2, 0, 1, 2, 1, 2, 0, 1, 0, 1, 2, 0],
+    [0, 0, 1, 1, 2, 2, 0, 0, 1, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0, 1, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 0, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 1, 1],
+    [0, 2, 2, 0, 1, 2, 2, 1, 0, 2, 2, 0, 1, 2, 2, 1],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 0, 1, 0, 1],
+    [0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 2, 2, 2, 0, 1, 1, 1],
+    [0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 2, 1, 1, 1, 2],
+    [0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2],
+    [0, 0, 0, 2, 1, 1, 1, 2, 1, 1, 1, 2, 0, 0, 0, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2],
+    [0, 0, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2],
+    [0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 1],
+    [0, 2, 2, 2, 1, 2, 2, 2, 0, 2, 2, 2, 1, 2, 2, 2],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 1, 2, 0, 1, 1, 2, 2, 0, 1, 2, 2, 2, 0],
+];
+
+// Pixels d'ancrage (index stocké avec un bit de moins) du 2e sous-ensemble, puis des 2e et 3e pour 3 sous-ensembles
+const BC7_ANCHORS2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 2, 8, 2, 2, 8, 8, 15, 2, 8, 2, 2, 8, 8, 2, 2, 15, 15, 6,
+    8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6, 6, 2, 6, 8, 15, 15, 2, 2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+const BC7_ANCHORS3_SECOND: [u8; 64] = [
+    3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3, 3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5, 15, 15, 8, 15, 3, 5, 6, 10,
+    8, 15, 15, 3, 15, 5, 15, 15, 15, 15, 3, 15, 5, 5, 5, 8, 5, 10, 5, 10, 8, 13, 15, 12, 3, 3,
+];
+const BC7_ANCHORS3_THIRD: [u8; 64] = [
+    15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8, 15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6, 10, 15, 15, 10, 8, 15, 3,
+    15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8, 15, 3, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+];
+
+const BC7_WEIGHTS2: [u32; 4] = [0, 21, 43, 64];
+const BC7_WEIGHTS3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const BC7_WEIGHTS4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, count: u32) -> u32 {
+        let mut value = 0;
+        for i in 0..count {
+            let bit = (self.data[self.position / 8] >> (self.position % 8)) & 1;
+            value |= (bit as u32) << i;
+            self.position += 1;
+        }
+        value
+    }
+}
+
+fn bc7_interpolate(e0: u32, e1: u32, bits: u32, index: u32) -> u8 {
+    let weight = match bits {
+        2 => BC7_WEIGHTS2[index as usize],
+        3 => BC7_WEIGHTS3[index as usize],
+        _ => BC7_WEIGHTS4[index as usize],
+    };
+    (((64 - weight) * e0 + weight * e1 + 32) >> 6) as u8
+}
+
+fn decode_bc7(block: &[u8], out: &mut [[u8; 4]; 16]) {
+    // Mode = position du premier bit à 1 ; un bloc sans mode est noir transparent
+    let Some(mode_index) = (0..8).find(|m| block[0] & (1 << m) != 0) else {
+        *out = [[0; 4]; 16];
+        return;
+    };
+    let mode = &BC7_MODES[mode_index];
+    let mut bits = BitReader {
+        data: block,
+        position: mode_index + 1,
+    };
+    let partition = bits.read(mode.partition_bits) as usize;
+    let rotation = bits.read(mode.rotation_bits);
+    let index_selection = bits.read(mode.index_selection_bits);
+
+    let mut endpoints = [[[0u32; 4]; 2]; 3];
+    let channels = if mode.alpha_bits > 0 { 4 } else { 3 };
+    for channel in 0..channels {
+        let width = if channel == 3 { mode.alpha_bits } else { mode.color_bits };
+        for subset in endpoints.iter_mut().take(mode.subsets) {
+            for endpoint in subset.iter_mut() {
+                endpoint[channel] = bits.read(width);
+            }
+        }
+    }
+
+    let (mut color_bits, mut alpha_bits) = (mode.color_bits, mode.alpha_bits);
+    if mode.endpoint_pbits || mode.shared_pbits {
+        let mut pbits = [[0u32; 2]; 3];
+        for subset in pbits.iter_mut().take(mode.subsets) {
+            if mode.endpoint_pbits {
+                subset[0] = bits.read(1);
+                subset[1] = bits.read(1);
+            } else {
+                let shared = bits.read(1);
+                *subset = [shared, shared];
+            }
+        }
+        for (subset, pbit) in endpoints.iter_mut().zip(pbits).take(mode.subsets) {
+            for (endpoint, p) in subset.iter_mut().zip(pbit) {
+                for value in endpoint.iter_mut().take(channels) {
+                    *value = (*value << 1) | p;
+                }
+            }
+        }
+        color_bits += 1;
+        if alpha_bits > 0 {
+            alpha_bits += 1;
+        }
+    }
+
+    // Extension des extrémités sur 8 bits en répétant les bits de poids fort
+    let expand = |value: u32, width: u32| -> u32 {
+        if width >= 8 {
+            value
+        } else {
+            (value << (8 - width)) | (value >> (2 * width - 8))
+        }
+    };
+    for subset in endpoints.iter_mut().take(mode.subsets) {
+        for endpoint in subset.iter_mut() {
+            for (channel, value) in endpoint.iter_mut().enumerate() {
+                *value = match channel {
+                    3 if alpha_bits == 0 => 255,
+                    3 => expand(*value, alpha_bits),
+                    _ => expand(*value, color_bits),
+                };
+            }
+        }
+    }
+
+    let subset_of = |pixel: usize| -> usize {
+        match mode.subsets {
+            2 => ((BC7_PARTITIONS2[partition] >> pixel) & 1) as usize,
+            3 => BC7_PARTITIONS3[partition][pixel] as usize,
+            _ => 0,
+        }
+    };
+    let is_anchor = |pixel: usize| -> bool {
+        pixel == 0
+            || match mode.subsets {
+                2 => pixel == BC7_ANCHORS2[partition] as usize,
+                3 => pixel == BC7_ANCHORS3_SECOND[partition] as usize || pixel == BC7_ANCHORS3_THIRD[partition] as usize,
+                _ => false,
+            }
+    };
+
+    let mut primary = [0u32; 16];
+    for (pixel, index) in primary.iter_mut().enumerate() {
+        *index = bits.read(mode.index_bits - is_anchor(pixel) as u32);
+    }
+    let mut secondary = [0u32; 16];
+    if mode.index_bits2 > 0 {
+        for (pixel, index) in secondary.iter_mut().enumerate() {
+            *index = bits.read(mode.index_bits2 - (pixel == 0) as u32);
+        }
+    }
+
+    for (pixel, color) in out.iter_mut().enumerate() {
+        let [e0, e1] = endpoints[subset_of(pixel)];
+        let (color_index, color_width, alpha_index, alpha_width) = if mode.index_bits2 == 0 {
+            (primary[pixel], mode.index_bits, primary[pixel], mode.index_bits)
+        } else if index_selection == 0 {
+            (primary[pixel], mode.index_bits, secondary[pixel], mode.index_bits2)
+        } else {
+            (secondary[pixel], mode.index_bits2, primary[pixel], mode.index_bits)
+        };
+        for channel in 0..3 {
+            color[channel] = bc7_interpolate(e0[channel], e1[channel], color_width, color_index);
+        }
+        color[3] = bc7_interpolate(e0[3], e1[3], alpha_width, alpha_index);
+        match rotation {
+            1 => color.swap(0, 3),
+            2 => color.swap(1, 3),
+            3 => color.swap(2, 3),
+            _ => {}
+        }
+    }
+}
+
+pub fn encode_png(image: &Image) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, image.width, image.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&image.rgba).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+// Décode une texture .tex/.dds et renvoie le PNG du niveau demandé
+pub fn texture_to_png(data: &[u8], mip: u32) -> Result<Vec<u8>, String> {
+    encode_png(&Texture::parse(data)?.decode(mip)?)
+}
+
+pub fn convert_file(input: &Path, output: &Path, mip: u32) -> Result<(), String> {
+    let data = std::fs::read(input).map_err(|e| format!("Impossible de lire {}: {}", input.display(), e))?;
+    let png = texture_to_png(&data, mip).map_err(|e| format!("{}: {}", input.display(), e))?;
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(output, png).map_err(|e| format!("Impossible d'écrire {}: {}", output.display(), e))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TextureFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TextureBatchReport {
+    pub converted: Vec<String>,
+    pub failed: Vec<TextureFailure>,
+}
+
+fn collect_textures(dir: &Path, found: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Impossible de lire {}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_textures(&path, found)?;
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| TEXTURE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+// Convertit toutes les textures d'un dossier en conservant l'arborescence ; une texture illisible n'arrête pas le lot
+pub fn convert_tree(source: &Path, output_dir: &Path, mip: u32) -> Result<TextureBatchReport, String> {
+    let mut textures = Vec::new();
+    collect_textures(source, &mut textures)?;
+    textures.sort();
+
+    let mut report = TextureBatchReport::default();
+    for path in textures {
+        let relative = path.strip_prefix(source).unwrap_or(&path);
+        let output = output_dir.join(relative).with_extension("png");
+        match convert_file(&path, &output, mip) {
+            Ok(()) => report.converted.push(output.to_string_lossy().to_string()),
+            Err(error) => report.failed.push(TextureFailure {
+                path: path.to_string_lossy().to_string(),
+                error,
+            }),
+        }
+    }
+    Ok(report)
+}
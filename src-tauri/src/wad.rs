@@ -0,0 +1,378 @@
+// Lecture des archives WAD v3 (.wad.client) : table des entrées et décompression
+use crate::hashes::HashTable;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+pub const WAD_MAGIC: &[u8; 2] = b"RW";
+pub const WAD_HEADER_SIZE: u64 = 272;
+pub const WAD_ENTRY_SIZE: usize = 32;
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+// Réservation maximale avant décompression : les tailles annoncées ne sont pas fiables
+const MAX_PREALLOCATION: usize = 64 * 1024 * 1024;
+
+// Hash des chemins dans les WAD : xxHash64 du chemin en minuscules
+pub fn path_hash(path: &str) -> u64 {
+    xxhash_rust::xxh64::xxh64(path.to_lowercase().replace('\\', "/").as_bytes(), 0)
+}
+
+// Table des sous-chunks stockée dans le WAD lui-même, ex: "data/final/champions/ahri.wad.subchunktoc"
+pub fn subchunk_toc_path(wad_path: &str) -> String {
+    let lower = wad_path.to_lowercase().replace('\\', "/");
+    format!("{}.subchunktoc", lower.trim_end_matches(".client"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WadCompression {
+    None,
+    Gzip,
+    // Redirection vers un autre fichier, les données contiennent son chemin
+    Satellite,
+    Zstd,
+    ZstdChunked,
+}
+
+impl WadCompression {
+    fn from_type(value: u8) -> Result<Self, String> {
+        match value & 0x0F {
+            0 => Ok(WadCompression::None),
+            1 => Ok(WadCompression::Gzip),
+            2 => Ok(WadCompression::Satellite),
+            3 => Ok(WadCompression::Zstd),
+            4 => Ok(WadCompression::ZstdChunked),
+            other => Err(format!("Type de compression WAD inconnu: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WadEntry {
+    // En hexadécimal côté frontend, un u64 ne tient pas dans un nombre JavaScript
+    #[serde(serialize_with = "serialize_hash")]
+    pub path_hash: u64,
+    pub offset: u32,
+    pub compressed_size: u32,
+    pub size: u32,
+    pub compression: WadCompression,
+    pub subchunk_count: u8,
+    pub duplicate: bool,
+    pub first_subchunk: u16,
+    #[serde(serialize_with = "serialize_hash")]
+    pub checksum: u64,
+}
+
+fn serialize_hash<S: serde::Serializer>(hash: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{:016x}", hash))
+}
+
+pub fn parse_hash(value: &str) -> Result<u64, String> {
+    u64::from_str_radix(value.trim().trim_start_matches("0x"), 16).map_err(|_| format!("Hash invalide: {}", value))
+}
+
+// Entrée accompagnée de son chemin quand la table de hash le connaît
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedWadEntry {
+    pub path: Option<String>,
+    #[serde(flatten)]
+    pub entry: WadEntry,
+}
+
+// Entrée désignée par son chemin ou directement par son hash (16 caractères hexadécimaux)
+pub fn entry_hash(name_or_hash: &str) -> u64 {
+    let trimmed = name_or_hash.trim().trim_start_matches("0x");
+    match (trimmed.len(), u64::from_str_radix(trimmed, 16)) {
+        (16, Ok(hash)) => hash,
+        _ => path_hash(name_or_hash.trim()),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SubChunk {
+    compressed_size: u32,
+    size: u32,
+}
+
+#[derive(Debug)]
+pub struct Wad {
+    path: PathBuf,
+    pub version: (u8, u8),
+    pub entries: Vec<WadEntry>,
+    subchunks: Vec<SubChunk>,
+}
+
+// Décompresse au plus `limit` octets (plus un pour détecter le dépassement) à la suite de `data`
+fn read_limited(reader: impl Read, limit: usize, data: &mut Vec<u8>) -> std::io::Result<usize> {
+    reader.take(limit as u64 + 1).read_to_end(data)
+}
+
+fn read_u16(data: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes(data[pos..pos + 2].try_into().unwrap())
+}
+
+fn read_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], pos: usize) -> u64 {
+    u64::from_le_bytes(data[pos..pos + 8].try_into().unwrap())
+}
+
+impl Wad {
+    pub fn open(path: &Path) -> Result<Wad, String> {
+        let mut reader = BufReader::new(File::open(path).map_err(|e| format!("Impossible d'ouvrir {}: {}", path.display(), e))?);
+        let file_len = reader
+            .get_ref()
+            .metadata()
+            .map_err(|e| e.to_string())?
+            .len();
+
+        let mut header = [0u8; WAD_HEADER_SIZE as usize];
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| format!("{} est trop court pour un WAD", path.display()))?;
+        if &header[0..2] != WAD_MAGIC {
+            return Err(format!("{} n'est pas un WAD (signature invalide)", path.display()));
+        }
+        let version = (header[2], header[3]);
+        if version.0 != 3 {
+            return Err(format!("Version WAD {}.{} non supportée (v3 attendue)", version.0, version.1));
+        }
+        // Après la signature ECDSA (256 octets) et la somme de contrôle (8 octets)
+        let count = read_u32(&header, 268) as usize;
+        if WAD_HEADER_SIZE + (count * WAD_ENTRY_SIZE) as u64 > file_len {
+            return Err(format!("Table des entrées tronquée ({} entrées)", count));
+        }
+
+        let mut table = vec![0u8; count * WAD_ENTRY_SIZE];
+        reader.read_exact(&mut table).map_err(|e| e.to_string())?;
+        let mut entries = Vec::with_capacity(count);
+        for raw in table.chunks_exact(WAD_ENTRY_SIZE) {
+            let entry = WadEntry {
+                path_hash: read_u64(raw, 0),
+                offset: read_u32(raw, 8),
+                compressed_size: read_u32(raw, 12),
+                size: read_u32(raw, 16),
+                compression: WadCompression::from_type(raw[20])?,
+                subchunk_count: raw[20] >> 4,
+                duplicate: raw[21] != 0,
+                first_subchunk: read_u16(raw, 22),
+                checksum: read_u64(raw, 24),
+            };
+            if entry.offset as u64 + entry.compressed_size as u64 > file_len {
+                return Err(format!("Entrée {:016x} hors du fichier", entry.path_hash));
+            }
+            entries.push(entry);
+        }
+
+        Ok(Wad {
+            path: path.to_path_buf(),
+            version,
+            entries,
+            subchunks: Vec::new(),
+        })
+    }
+
+    pub fn entry(&self, path_hash: u64) -> Option<&WadEntry> {
+        self.entries.iter().find(|e| e.path_hash == path_hash)
+    }
+
+    pub fn named_entries(&self, table: &HashTable) -> Vec<NamedWadEntry> {
+        self.entries
+            .iter()
+            .map(|entry| NamedWadEntry {
+                path: table.get(entry.path_hash).map(str::to_string),
+                entry: entry.clone(),
+            })
+            .collect()
+    }
+
+    // Charge la table des sous-chunks à partir du chemin du WAD dans le manifest
+    pub fn load_subchunk_toc(&mut self, wad_path: &str) -> Result<bool, String> {
+        let Some(entry) = self.entry(path_hash(&subchunk_toc_path(wad_path))).cloned() else {
+            return Ok(false);
+        };
+        let data = self.read(&entry)?;
+        self.subchunks = data
+            .chunks_exact(16)
+            .map(|raw| SubChunk {
+                compressed_size: read_u32(raw, 0),
+                size: read_u32(raw, 4),
+            })
+            .collect();
+        Ok(true)
+    }
+
+    pub fn read_raw(&self, entry: &WadEntry) -> Result<Vec<u8>, String> {
+        let mut file = File::open(&self.path).map_err(|e| format!("Impossible d'ouvrir {}: {}", self.path.display(), e))?;
+        let mut data = vec![0u8; entry.compressed_size as usize];
+        file.seek(SeekFrom::Start(entry.offset as u64))
+            .and_then(|_| file.read_exact(&mut data))
+            .map_err(|e| format!("Lecture de l'entrée {:016x} impossible: {}", entry.path_hash, e))?;
+        Ok(data)
+    }
+
+    // Données décompressées d'une entrée
+    pub fn read(&self, entry: &WadEntry) -> Result<Vec<u8>, String> {
+        let raw = self.read_raw(entry)?;
+        let data = match entry.compression {
+            WadCompression::None | WadCompression::Satellite => raw,
+            WadCompression::Gzip => {
+                let mut data = Vec::with_capacity((entry.size as usize).min(MAX_PREALLOCATION));
+                read_limited(flate2::read::GzDecoder::new(raw.as_slice()), entry.size as usize, &mut data)
+                    .map_err(|e| format!("Erreur gzip pour {:016x}: {}", entry.path_hash, e))?;
+                data
+            }
+            WadCompression::Zstd => {
+                let mut data = Vec::with_capacity((entry.size as usize).min(MAX_PREALLOCATION));
+                zstd::stream::Decoder::new(raw.as_slice())
+                    .and_then(|decoder| read_limited(decoder, entry.size as usize, &mut data))
+                    .map_err(|e| format!("Erreur zstd pour {:016x}: {}", entry.path_hash, e))?;
+                data
+            }
+            WadCompression::ZstdChunked => self.read_chunked(entry, &raw)?,
+        };
+        if entry.compression != WadCompression::Satellite && data.len() != entry.size as usize {
+            return Err(format!(
+                "Taille inattendue pour {:016x}: {} au lieu de {}",
+                entry.path_hash,
+                data.len(),
+                entry.size
+            ));
+        }
+        Ok(data)
+    }
+
+    fn read_chunked(&self, entry: &WadEntry, raw: &[u8]) -> Result<Vec<u8>, String> {
+        let first = entry.first_subchunk as usize;
+        let count = entry.subchunk_count as usize;
+        let size = entry.size as usize;
+        let mut data = Vec::with_capacity(size.min(MAX_PREALLOCATION));
+
+        // Avec la table : chaque sous-chunk est brut ou compressé selon sa taille
+        if count > 0 && first + count <= self.subchunks.len() {
+            let mut pos = 0usize;
+            for subchunk in &self.subchunks[first..first + count] {
+                let part = raw
+                    .get(pos..pos + subchunk.compressed_size as usize)
+                    .ok_or_else(|| format!("Sous-chunk tronqué dans {:016x}", entry.path_hash))?;
+                if data.len() + subchunk.size as usize > size {
+                    return Err(format!("Sous-chunks plus grands que l'entrée {:016x}", entry.path_hash));
+                }
+                if subchunk.compressed_size == subchunk.size {
+                    data.extend_from_slice(part);
+                } else {
+                    let decoded = zstd::stream::Decoder::new(part)
+                        .and_then(|decoder| read_limited(decoder, subchunk.size as usize, &mut data))
+                        .map_err(|e| format!("Erreur zstd pour {:016x}: {}", entry.path_hash, e))?;
+                    if decoded != subchunk.size as usize {
+                        return Err(format!(
+                            "Sous-chunk de {:016x} : {} octets au lieu de {}",
+                            entry.path_hash, decoded, subchunk.size
+                        ));
+                    }
+                }
+                pos += subchunk.compressed_size as usize;
+            }
+            return Ok(data);
+        }
+
+        // Sans la table : données brutes éventuelles puis trames zstd consécutives
+        let start = raw
+            .windows(4)
+            .position(|w| w == ZSTD_MAGIC)
+            .unwrap_or(raw.len());
+        data.extend_from_slice(&raw[..start]);
+        if start < raw.len() {
+            let remaining = size.saturating_sub(data.len());
+            zstd::stream::Decoder::new(&raw[start..])
+                .and_then(|decoder| read_limited(decoder, remaining, &mut data))
+                .map_err(|e| format!("Erreur zstd pour {:016x}: {}", entry.path_hash, e))?;
+        }
+        Ok(data)
+    }
+}
+
+// Extension probable d'après les premiers octets, pour nommer les entrées sans chemin connu
+pub fn guess_extension(data: &[u8]) -> &'static str {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"PROP", "bin"),
+        (b"PTCH", "bin"),
+        (b"DDS ", "dds"),
+        (b"TEX\0", "tex"),
+        (b"\x89PNG", "png"),
+        (b"BKHD", "bnk"),
+        (b"r3d2Mesh", "scb"),
+        (b"r3d2sklt", "skl"),
+        (b"r3d2anmd", "anm"),
+        (b"r3d2canm", "anm"),
+        (b"RST", "stringtable"),
+        (b"r3d2wght", "wgt"),
+        (b"r3d2", "wpk"),
+        (b"OEGM", "mapgeo"),
+        (b"[Obj", "sco"),
+        (b"<?xml", "xml"),
+        (b"{", "json"),
+    ];
+    if data.len() >= 8 && read_u32(data, 4) == 0x22FD_4FC3 {
+        return "skl";
+    }
+    if data.len() >= 4 && read_u32(data, 0) == 0x0011_2233 {
+        return "skn";
+    }
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| data.starts_with(magic))
+        .map(|(_, ext)| *ext)
+        .unwrap_or("bin")
+}
+
+fn collect_wads(dir: &Path, found: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Impossible de lire {}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_wads(&path, found)?;
+        } else if path.to_string_lossy().to_lowercase().ends_with(".wad.client") {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+// Langue d'un WAD localisé ("Ahri.fr_FR.wad.client" -> "fr_FR")
+pub fn wad_locale(path: &str) -> Option<&str> {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let stem = name.get(..name.len().checked_sub(".wad.client".len())?)?;
+    stem.rsplit_once('.').map(|(_, locale)| locale).filter(|locale| !locale.is_empty())
+}
+
+// Tous les WAD d'un dossier de patch, triés
+pub fn find_wads(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut found = Vec::new();
+    collect_wads(dir, &mut found)?;
+    found.sort();
+    Ok(found)
+}
+
+// Extrait les entrées demandées (toutes si vide) à leur chemin connu, sinon sous la forme "<hash>.<ext>"
+pub fn extract_entries(wad: &Wad, hashes: &[u64], output_dir: &Path, table: &HashTable) -> Result<Vec<PathBuf>, String> {
+    std::fs::create_dir_all(output_dir).map_err(|e| format!("Impossible de créer {}: {}", output_dir.display(), e))?;
+    let mut written = Vec::new();
+    for entry in wad.entries.iter().filter(|e| hashes.is_empty() || hashes.contains(&e.path_hash)) {
+        let data = wad.read(entry)?;
+        let path = match table.get(entry.path_hash) {
+            Some(name) => crate::downloader::local_path(output_dir, name)?,
+            None => output_dir.join(format!("{:016x}.{}", entry.path_hash, guess_extension(&data))),
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&path, &data).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))?;
+        written.push(path);
+    }
+    Ok(written)
+}
@@ -0,0 +1,179 @@
+// Surveillance du catalogue : téléchargement automatique des nouveaux patchs des serveurs suivis
+use crate::catalogue::CatalogueEntry;
+use crate::realms::{same_realm, Realm};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+pub const WATCH_STATE_FILE_NAME: &str = "watch_state.json";
+pub const MIN_WATCH_INTERVAL_MINUTES: u64 = 5;
+
+// Profil de la surveillance, enregistré avec les paramètres
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WatchSettings {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    // Serveurs suivis, ex: ["EUW1", "PBE1"]
+    pub realms: Vec<String>,
+    // Catalogue des manifests (liste et surveillance), vide = variable d'environnement du catalogue
+    pub catalogue_url: String,
+    // Vides = langue et filtre des paramètres généraux
+    pub language: String,
+    pub content_filter: String,
+    // Patchs conservés par serveur, 0 = tout garder
+    pub keep_latest: u32,
+}
+
+impl Default for WatchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 60,
+            realms: Vec::new(),
+            catalogue_url: String::new(),
+            language: String::new(),
+            content_filter: String::new(),
+            keep_latest: 0,
+        }
+    }
+}
+
+impl WatchSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_minutes < MIN_WATCH_INTERVAL_MINUTES {
+            return Err(format!(
+                "L'intervalle de surveillance doit être d'au moins {} minutes",
+                MIN_WATCH_INTERVAL_MINUTES
+            ));
+        }
+        if self.enabled && self.realms.iter().all(|realm| realm.trim().is_empty()) {
+            return Err("Aucun serveur à surveiller".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn follows(&self, realm: &str) -> bool {
+        self.realms.iter().any(|r| same_realm(r, realm))
+    }
+}
+
+// Téléchargement lancé par la surveillance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedDownload {
+    pub manifest: String,
+    pub realm: String,
+    pub date: String,
+    pub task_id: String,
+    pub output_path: Option<String>,
+    pub completed: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WatchState {
+    // Serveurs déjà parcourus une première fois : pour un serveur ajouté ensuite, seul son dernier patch est téléchargé
+    pub baselined_realms: BTreeSet<String>,
+    // Manifests déjà traités (téléchargés ou antérieurs à la surveillance)
+    pub seen: BTreeSet<String>,
+    pub downloads: Vec<WatchedDownload>,
+}
+
+impl WatchState {
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, content).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))
+    }
+
+    // Manifests à télécharger : les nouveaux, ou seulement le dernier d'un serveur parcouru pour la première fois
+    pub fn new_manifests(&mut self, entries: &[CatalogueEntry], settings: &WatchSettings) -> Vec<CatalogueEntry> {
+        let followed: Vec<&CatalogueEntry> = entries.iter().filter(|entry| settings.follows(&entry.realm)).collect();
+        let mut candidates: Vec<CatalogueEntry> = Vec::new();
+        let mut latest: HashMap<String, &CatalogueEntry> = HashMap::new();
+        let already_seen = self.seen.clone();
+        for entry in followed {
+            let realm = realm_key(&entry.realm);
+            if self.baselined_realms.contains(&realm) {
+                if !self.seen.contains(&entry.manifest_url) {
+                    candidates.push(entry.clone());
+                }
+                continue;
+            }
+            self.seen.insert(entry.manifest_url.clone());
+            let current = latest.entry(realm).or_insert(entry);
+            if entry.date > current.date {
+                *current = entry;
+            }
+        }
+        let mut baseline: Vec<(String, &CatalogueEntry)> = latest.into_iter().collect();
+        baseline.sort_by(|a, b| a.0.cmp(&b.0));
+        for (realm, entry) in baseline {
+            // Dernier patch déjà traité (serveur suivi auparavant, état d'une version précédente) : rien à faire
+            if !already_seen.contains(&entry.manifest_url) {
+                candidates.push(entry.clone());
+            }
+            self.baselined_realms.insert(realm);
+        }
+        self.seen.extend(candidates.iter().map(|entry| entry.manifest_url.clone()));
+        candidates
+    }
+
+    // Oublie un téléchargement perdu ou en échec pour le relancer au prochain passage
+    pub fn retry(&mut self, task_id: &str) {
+        if let Some(index) = self.downloads.iter().position(|d| d.task_id == task_id && !d.completed) {
+            let download = self.downloads.remove(index);
+            self.seen.remove(&download.manifest);
+        }
+    }
+
+    // Patchs terminés à supprimer : au-delà des `keep_latest` plus récents de chaque serveur
+    pub fn expired(&self, keep_latest: u32) -> Vec<WatchedDownload> {
+        if keep_latest == 0 {
+            return Vec::new();
+        }
+        let mut by_realm: HashMap<String, Vec<&WatchedDownload>> = HashMap::new();
+        for download in self.downloads.iter().filter(|d| d.completed) {
+            by_realm.entry(realm_key(&download.realm)).or_default().push(download);
+        }
+        let mut expired: Vec<WatchedDownload> = by_realm
+            .into_values()
+            .flat_map(|mut downloads| {
+                downloads.sort_by(|a, b| b.date.cmp(&a.date));
+                downloads.into_iter().skip(keep_latest as usize).cloned().collect::<Vec<_>>()
+            })
+            .collect();
+        expired.sort_by(|a, b| (&a.realm, &a.date).cmp(&(&b.realm, &b.date)));
+        expired
+    }
+
+    pub fn forget(&mut self, task_id: &str) {
+        self.downloads.retain(|d| d.task_id != task_id);
+    }
+}
+
+// Clé d'un serveur indépendante de la casse et des alias ("euw" et "EUW1")
+fn realm_key(realm: &str) -> String {
+    Realm::parse(realm).id.to_uppercase()
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchReport {
+    pub checked: usize,
+    pub enqueued: Vec<String>,
+    pub removed: Vec<String>,
+    // Anciens patchs laissés sur le disque : journal de la tâche absent ou dossier partagé avec une autre tâche
+    pub kept: Vec<String>,
+    pub errors: Vec<String>,
+}
@@ -0,0 +1,336 @@
+// Notifications webhook (Discord, Slack, JSON générique ou modèle libre) pour les événements de patch et de téléchargement
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+pub const WEBHOOK_LOG_FILE_NAME: &str = "webhook_log.jsonl";
+const DISCORD_COLOR_INFO: u32 = 0x3498DB;
+const DISCORD_COLOR_SUCCESS: u32 = 0x2ECC71;
+const DISCORD_COLOR_ERROR: u32 = 0xE74C3C;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    Generic,
+    Discord,
+    Slack,
+    // Modèle JSON libre avec des champs {event}, {title}, {message}, {timestamp} et ceux de l'événement
+    Custom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WebhookEventKind {
+    NewManifest,
+    DownloadCompleted,
+    DownloadFailed,
+    Test,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WebhookTarget {
+    pub url: String,
+    pub format: WebhookFormat,
+    // Événements envoyés, vide = tous
+    pub events: Vec<WebhookEventKind>,
+    pub template: String,
+    pub enabled: bool,
+}
+
+impl Default for WebhookTarget {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            format: WebhookFormat::Generic,
+            events: Vec::new(),
+            template: String::new(),
+            enabled: true,
+        }
+    }
+}
+
+impl WebhookTarget {
+    pub fn accepts(&self, kind: WebhookEventKind) -> bool {
+        self.enabled && (kind == WebhookEventKind::Test || self.events.is_empty() || self.events.contains(&kind))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let url = self.url.trim();
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(format!("URL de webhook invalide : {}", self.url));
+        }
+        if self.format == WebhookFormat::Custom {
+            payload(self, &WebhookEvent::test())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookField {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookEvent {
+    pub kind: WebhookEventKind,
+    pub title: String,
+    pub message: String,
+    pub timestamp: String,
+    pub fields: Vec<WebhookField>,
+}
+
+impl WebhookEvent {
+    fn new(kind: WebhookEventKind, title: String, message: String, fields: &[(&str, &str)]) -> Self {
+        Self {
+            kind,
+            title,
+            message,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            fields: fields
+                .iter()
+                .map(|(name, value)| WebhookField {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn new_manifest(realm: &str, manifest: &str, date: &str, version: &str) -> Self {
+        Self::new(
+            WebhookEventKind::NewManifest,
+            format!("Nouveau patch {} sur {}", version, realm),
+            format!("Manifest publié le {}", date),
+            &[("realm", realm), ("version", version), ("date", date), ("manifest", manifest)],
+        )
+    }
+
+    pub fn download_completed(task_id: &str, manifest: &str, version: &str, output_path: &str) -> Self {
+        Self::new(
+            WebhookEventKind::DownloadCompleted,
+            format!("Téléchargement terminé : {}", version),
+            format!("Fichiers disponibles dans {}", output_path),
+            &[("taskId", task_id), ("version", version), ("manifest", manifest), ("outputPath", output_path)],
+        )
+    }
+
+    pub fn download_failed(task_id: &str, manifest: &str, error: &str) -> Self {
+        Self::new(
+            WebhookEventKind::DownloadFailed,
+            "Échec du téléchargement".to_string(),
+            error.to_string(),
+            &[("taskId", task_id), ("manifest", manifest), ("error", error)],
+        )
+    }
+
+    pub fn test() -> Self {
+        Self::new(
+            WebhookEventKind::Test,
+            "Test du webhook".to_string(),
+            "Ce message confirme que le webhook est bien configuré".to_string(),
+            &[],
+        )
+    }
+
+    fn kind_name(&self) -> String {
+        serde_json::to_value(self.kind)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+// Remplace les {champs} du modèle par les valeurs échappées pour une chaîne JSON
+fn render_template(template: &str, event: &WebhookEvent) -> Result<Value, String> {
+    let escape = |text: &str| {
+        let quoted = serde_json::to_string(text).unwrap_or_default();
+        quoted[1..quoted.len() - 1].to_string()
+    };
+    let mut rendered = template
+        .replace("{event}", &escape(&event.kind_name()))
+        .replace("{title}", &escape(&event.title))
+        .replace("{message}", &escape(&event.message))
+        .replace("{timestamp}", &escape(&event.timestamp));
+    for field in &event.fields {
+        rendered = rendered.replace(&format!("{{{}}}", field.name), &escape(&field.value));
+    }
+    serde_json::from_str(&rendered).map_err(|e| format!("Modèle de webhook invalide : {}", e))
+}
+
+// Corps JSON envoyé pour une cible
+pub fn payload(target: &WebhookTarget, event: &WebhookEvent) -> Result<Value, String> {
+    match target.format {
+        WebhookFormat::Generic => {
+            let data: serde_json::Map<String, Value> = event
+                .fields
+                .iter()
+                .map(|field| (field.name.clone(), Value::String(field.value.clone())))
+                .collect();
+            Ok(json!({
+                "event": event.kind_name(),
+                "title": event.title,
+                "message": event.message,
+                "timestamp": event.timestamp,
+                "data": data,
+            }))
+        }
+        WebhookFormat::Discord => {
+            let color = match event.kind {
+                WebhookEventKind::DownloadCompleted => DISCORD_COLOR_SUCCESS,
+                WebhookEventKind::DownloadFailed => DISCORD_COLOR_ERROR,
+                _ => DISCORD_COLOR_INFO,
+            };
+            let fields: Vec<Value> = event
+                .fields
+                .iter()
+                .map(|field| json!({ "name": field.name, "value": field.value, "inline": field.value.len() < 40 }))
+                .collect();
+            Ok(json!({
+                "embeds": [{
+                    "title": event.title,
+                    "description": event.message,
+                    "color": color,
+                    "timestamp": event.timestamp,
+                    "fields": fields,
+                }]
+            }))
+        }
+        WebhookFormat::Slack => {
+            let details: Vec<String> = event
+                .fields
+                .iter()
+                .map(|field| format!("*{}* : {}", field.name, field.value))
+                .collect();
+            let mut blocks = vec![json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": format!("*{}*\n{}", event.title, event.message) }
+            })];
+            if !details.is_empty() {
+                blocks.push(json!({
+                    "type": "section",
+                    "text": { "type": "mrkdwn", "text": details.join("\n") }
+                }));
+            }
+            Ok(json!({ "text": event.title, "blocks": blocks }))
+        }
+        WebhookFormat::Custom => render_template(&target.template, event),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DeliveryOptions {
+    pub max_attempts: u32,
+    // Doublé après chaque échec
+    pub retry_delay: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for DeliveryOptions {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            retry_delay: Duration::from_secs(2),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+// Entrée du journal des envois
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryRecord {
+    pub url: String,
+    pub event: WebhookEventKind,
+    pub timestamp: String,
+    pub attempts: u32,
+    pub status: Option<u16>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+// Envoie l'événement, avec nouvelles tentatives sur erreur réseau, 429 et 5xx
+pub async fn deliver(
+    client: &reqwest::Client,
+    target: &WebhookTarget,
+    event: &WebhookEvent,
+    options: &DeliveryOptions,
+) -> DeliveryRecord {
+    let mut record = DeliveryRecord {
+        url: target.url.clone(),
+        event: event.kind,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        attempts: 0,
+        status: None,
+        success: false,
+        error: None,
+    };
+    let body = match payload(target, event) {
+        Ok(body) => body,
+        Err(e) => {
+            record.error = Some(e);
+            return record;
+        }
+    };
+
+    let mut delay = options.retry_delay;
+    while record.attempts < options.max_attempts.max(1) {
+        if record.attempts > 0 {
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+        record.attempts += 1;
+        let response = client.post(target.url.trim()).timeout(options.timeout).json(&body).send().await;
+        let retry = match response {
+            Ok(response) => {
+                let status = response.status();
+                record.status = Some(status.as_u16());
+                if status.is_success() {
+                    record.success = true;
+                    record.error = None;
+                    break;
+                }
+                record.error = Some(format!("Erreur HTTP {}", status));
+                status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            Err(e) => {
+                record.status = None;
+                record.error = Some(format!("Erreur réseau : {}", e));
+                true
+            }
+        };
+        if !retry {
+            break;
+        }
+    }
+    record
+}
+
+pub fn append_log(path: &Path, record: &DeliveryRecord) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer {}: {}", parent.display(), e))?;
+    }
+    let line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))
+}
+
+// Derniers envois, du plus récent au plus ancien
+pub fn read_log(path: &Path, limit: usize) -> Vec<DeliveryRecord> {
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    text.lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect()
+}
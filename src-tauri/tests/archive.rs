@@ -0,0 +1,134 @@
+// Export d'un patch téléchargé en archive, complet puis repris après interruption
+use app_lib::archive::{export_archive, ArchiveFormat, ArchiveIndex, ArchiveInfo, INDEX_FILE_NAME};
+use app_lib::downloader::{self, DownloadControl, DownloadOptions};
+use app_lib::mock_cdn::{sample_data, MockCdn, MockFile, MockRelease};
+use app_lib::rman::Manifest;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+
+const FILE_COUNT: u64 = 100;
+const FILE_SIZE: usize = 40_000;
+
+async fn downloaded_patch(output: &Path) -> (Manifest, MockRelease) {
+    let cdn = MockCdn::start().await.unwrap();
+    let mut files: Vec<MockFile> = (0..FILE_COUNT)
+        .map(|i| MockFile::new(&format!("DATA/FINAL/Champions/Champion{:03}.wad.client", i), sample_data(i + 1, FILE_SIZE)))
+        .collect();
+    files.push(MockFile::new(&format!("DATA/{}deep.bin", "very_long_folder_name/".repeat(6)), sample_data(500, 1000)));
+    let release = MockRelease::new("EUW1", "2024-08-28", files);
+    let published = cdn.publish(&release).await;
+    let manifest = downloader::fetch_manifest(&published.url).await.unwrap();
+    let options = DownloadOptions {
+        cdn_url: cdn.cdn_url(),
+        ..DownloadOptions::default()
+    };
+    downloader::download_manifest(&manifest, output, &options, Arc::default())
+        .await
+        .unwrap();
+    (manifest, release)
+}
+
+fn info(manifest: &Manifest) -> ArchiveInfo {
+    ArchiveInfo {
+        version: "Patch 2024-08-28".to_string(),
+        realm: "EUW1".to_string(),
+        manifest_hash: format!("{:016X}", manifest.id),
+    }
+}
+
+fn read_archive(path: &Path, format: ArchiveFormat) -> HashMap<String, Vec<u8>> {
+    let mut entries = HashMap::new();
+    match format {
+        ArchiveFormat::TarZst => {
+            let decoder = zstd::Decoder::new(std::fs::File::open(path).unwrap()).unwrap();
+            let mut archive = tar::Archive::new(decoder);
+            for entry in archive.entries().unwrap() {
+                let mut entry = entry.unwrap();
+                let name = entry.path().unwrap().to_string_lossy().to_string();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).unwrap();
+                entries.insert(name, data);
+            }
+        }
+        ArchiveFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(std::fs::File::open(path).unwrap()).unwrap();
+            for i in 0..archive.len() {
+                let mut entry = archive.by_index(i).unwrap();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).unwrap();
+                entries.insert(entry.name().to_string(), data);
+            }
+        }
+    }
+    entries
+}
+
+fn check_archive(path: &Path, format: ArchiveFormat, manifest: &Manifest, release: &MockRelease) {
+    let entries = read_archive(path, format);
+    assert_eq!(entries.len(), release.files.len() + 1);
+    for file in &release.files {
+        assert_eq!(entries.get(&file.path), Some(&file.data), "{}", file.path);
+    }
+
+    let index: ArchiveIndex = serde_json::from_slice(&entries[INDEX_FILE_NAME]).unwrap();
+    assert_eq!(index.realm, "EUW1");
+    assert_eq!(index.manifest_hash, format!("{:016X}", manifest.id));
+    assert_eq!(index.files.len(), release.files.len());
+    let first = &index.files[0];
+    assert_eq!(first.sha256, format!("{:x}", Sha256::digest(&entries[&first.path])));
+}
+
+#[tokio::test]
+async fn export_writes_every_file_and_an_index() {
+    let output = tempfile::tempdir().unwrap();
+    let (manifest, release) = downloaded_patch(output.path()).await;
+
+    for format in [ArchiveFormat::TarZst, ArchiveFormat::Zip] {
+        let destination = output.path().with_extension(format.extension());
+        let summary = export_archive(&manifest, output.path(), &destination, format, &info(&manifest), &DownloadControl::default())
+            .unwrap();
+        assert!(!summary.interrupted);
+        assert_eq!(summary.files, release.files.len());
+        check_archive(&destination, format, &manifest, &release);
+        std::fs::remove_file(destination).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn interrupted_export_resumes_from_its_checkpoint() {
+    let output = tempfile::tempdir().unwrap();
+    let archives = tempfile::tempdir().unwrap();
+    let (manifest, release) = downloaded_patch(output.path()).await;
+
+    for format in [ArchiveFormat::TarZst, ArchiveFormat::Zip] {
+        let destination = archives.path().join(format!("patch.{}", format.extension()));
+
+        // Interruption après le premier point de reprise (64 fichiers)
+        let control = Arc::new(DownloadControl::default());
+        let watcher = control.clone();
+        let stopper = std::thread::spawn(move || {
+            while watcher.downloaded_bytes() < 70 * FILE_SIZE as u64 {
+                std::hint::spin_loop();
+            }
+            watcher.cancel();
+        });
+        let summary = export_archive(&manifest, output.path(), &destination, format, &info(&manifest), &control).unwrap();
+        stopper.join().unwrap();
+        assert!(summary.interrupted);
+        assert!(!destination.exists());
+
+        let control = DownloadControl::default();
+        let summary = export_archive(&manifest, output.path(), &destination, format, &info(&manifest), &control).unwrap();
+        assert!(!summary.interrupted);
+        assert!(summary.resumed_files >= 64);
+        assert!(control.total_bytes() < FILE_COUNT * FILE_SIZE as u64);
+        check_archive(&destination, format, &manifest, &release);
+
+        // Aucun fichier de travail ne reste à côté de l'archive
+        assert_eq!(std::fs::read_dir(archives.path()).unwrap().count(), 1);
+        std::fs::remove_file(destination).unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+// Extraction des flux WEM des banques audio .wpk/.bnk
+use app_lib::audio::{self, AudioBank, AudioContainer};
+use app_lib::hashes::HashTable;
+
+fn wem(codec: u16, samples: &[u8]) -> Vec<u8> {
+    // fmt Wwise : 24 octets, plus long que l'en-tête WAV classique
+    let mut fmt = Vec::new();
+    fmt.extend_from_slice(&codec.to_le_bytes());
+    fmt.extend_from_slice(&1u16.to_le_bytes());
+    fmt.extend_from_slice(&48_000u32.to_le_bytes());
+    fmt.extend_from_slice(&96_000u32.to_le_bytes());
+    fmt.extend_from_slice(&2u16.to_le_bytes());
+    fmt.extend_from_slice(&16u16.to_le_bytes());
+    fmt.extend_from_slice(&[6, 0, 4, 0, 0, 0, 0, 0]);
+
+    let mut body = b"WAVE".to_vec();
+    for (id, chunk) in [(b"fmt ", &fmt[..]), (b"JUNK", &[1, 2, 3][..]), (b"data", samples)] {
+        body.extend_from_slice(id);
+        body.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        body.extend_from_slice(chunk);
+        if chunk.len() % 2 == 1 {
+            body.push(0);
+        }
+    }
+    let mut data = b"RIFF".to_vec();
+    data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    data.extend_from_slice(&body);
+    data
+}
+
+fn build_wpk(files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut data = b"r3d2".to_vec();
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&(files.len() as u32).to_le_bytes());
+    let table_end = 12 + files.len() * 4;
+    let headers: Vec<usize> = files
+        .iter()
+        .scan(table_end, |position, (name, _)| {
+            let start = *position;
+            *position += 12 + name.len() * 2;
+            Some(start)
+        })
+        .collect();
+    let mut payload = headers.last().map(|h| h + 12 + files.last().unwrap().0.len() * 2).unwrap_or(table_end);
+    for header in &headers {
+        data.extend_from_slice(&(*header as u32).to_le_bytes());
+    }
+    for (name, content) in files {
+        data.extend_from_slice(&(payload as u32).to_le_bytes());
+        data.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        for unit in name.encode_utf16() {
+            data.extend_from_slice(&unit.to_le_bytes());
+        }
+        payload += content.len();
+    }
+    for (_, content) in files {
+        data.extend_from_slice(content);
+    }
+    data
+}
+
+fn build_bnk(streams: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let mut data = b"BKHD".to_vec();
+    data.extend_from_slice(&8u32.to_le_bytes());
+    data.extend_from_slice(&[0x86, 0, 0, 0, 1, 0, 0, 0]);
+    let mut index = Vec::new();
+    let mut payload = Vec::new();
+    for (id, content) in streams {
+        index.extend_from_slice(&id.to_le_bytes());
+        index.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        index.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        payload.extend_from_slice(content);
+    }
+    for (tag, section) in [(b"DIDX", index), (b"DATA", payload), (b"HIRC", vec![0u8; 4])] {
+        data.extend_from_slice(tag);
+        data.extend_from_slice(&(section.len() as u32).to_le_bytes());
+        data.extend_from_slice(&section);
+    }
+    data
+}
+
+#[test]
+fn lists_and_extracts_wpk_streams() {
+    let pcm = wem(0xFFFE, &[1, 0, 2, 0, 3, 0]);
+    let vorbis = wem(0xFFFF, &[9, 9, 9]);
+    let wpk = build_wpk(&[("1001.wem", pcm.clone()), ("1002.wem", vorbis.clone())]);
+
+    let bank = AudioBank::parse(&wpk, &HashTable::default()).unwrap();
+    assert_eq!(bank.container, AudioContainer::Wpk);
+    let ids: Vec<u32> = bank.streams.iter().map(|s| s.id).collect();
+    assert_eq!(ids, vec![1001, 1002]);
+    assert_eq!(bank.streams[0].name.as_deref(), Some("1001.wem"));
+    assert_eq!(bank.streams[0].codec, "pcm");
+    assert_eq!(bank.streams[1].codec, "vorbis");
+    assert_eq!(audio::stream_data(&wpk, &bank.streams[1]).unwrap(), &vorbis[..]);
+
+    // Extraction brute
+    let dir = tempfile::tempdir().unwrap();
+    let written = audio::extract_streams(&wpk, &bank, &[1002], dir.path(), false).unwrap();
+    assert_eq!(written, vec![dir.path().join("1002.wem")]);
+    assert_eq!(std::fs::read(&written[0]).unwrap(), vorbis);
+
+    // Avec conversion : le PCM devient un WAV standard, le Vorbis reste en WEM
+    let written = audio::extract_streams(&wpk, &bank, &[], &dir.path().join("converted"), true).unwrap();
+    assert_eq!(
+        written,
+        vec![dir.path().join("converted/1001.wav"), dir.path().join("converted/1002.wem")]
+    );
+    let wav = std::fs::read(&written[0]).unwrap();
+    assert_eq!(&wav[..4], b"RIFF");
+    assert_eq!(&wav[8..16], b"WAVEfmt ");
+    assert_eq!(u16::from_le_bytes([wav[20], wav[21]]), 1);
+    assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 48_000);
+    assert_eq!(&wav[36..40], b"data");
+    assert_eq!(&wav[44..], &[1, 0, 2, 0, 3, 0]);
+    assert!(audio::wem_to_wav(&vorbis).unwrap_err().contains("vorbis"));
+}
+
+#[test]
+fn lists_bnk_streams_with_hash_names() {
+    let bnk = build_bnk(&[(42, wem(0xFFFF, &[1])), (7, wem(0x0001, &[2, 0]))]);
+    let table = HashTable::parse("000000000000002a ahri_recall.wem\n");
+    let bank = AudioBank::parse(&bnk, &table).unwrap();
+    assert_eq!(bank.container, AudioContainer::Bnk);
+    assert_eq!(bank.streams.len(), 2);
+    assert_eq!(bank.streams[0].name.as_deref(), Some("ahri_recall.wem"));
+    assert_eq!(bank.streams[1].name, None);
+    assert_eq!(bank.streams[1].codec, "pcm");
+
+    let dir = tempfile::tempdir().unwrap();
+    let written = audio::extract_streams(&bnk, &bank, &[], dir.path(), false).unwrap();
+    assert_eq!(written, vec![dir.path().join("ahri_recall.wem"), dir.path().join("7.wem")]);
+
+    assert!(AudioBank::parse(b"RIFF0000", &table).is_err());
+}
+
+#[test]
+fn rejects_overflowing_offsets_and_rates() {
+    // Position relative à DATA qui dépasse 4 Go une fois ajoutée au début de la section
+    let mut bnk = build_bnk(&[(42, wem(0x0001, &[1, 0]))]);
+    bnk[28..32].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(AudioBank::parse(&bnk, &HashTable::default()).unwrap_err().contains("hors de la banque"));
+
+    // Fréquence absurde : le débit en octets par seconde ne tient pas sur 32 bits
+    let mut pcm = wem(0x0001, &[1, 0]);
+    pcm[24..28].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(audio::wem_to_wav(&pcm).unwrap_err().contains("invalide"));
+
+    // Nombre de flux annoncé sans rapport avec la taille du fichier
+    let mut wpk = build_wpk(&[("1001.wem", wem(0x0001, &[1, 0]))]);
+    wpk[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(AudioBank::parse(&wpk, &HashTable::default()).is_err());
+}
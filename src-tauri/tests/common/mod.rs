@@ -0,0 +1,91 @@
+// Outils partagés par les tests : construction de WAD et de fichiers .bin
+#![allow(dead_code)]
+
+use app_lib::prop_bin::{fnv1a, BinType};
+use app_lib::wad::{self, WAD_ENTRY_SIZE, WAD_HEADER_SIZE};
+use std::io::Write;
+
+pub struct RawEntry {
+    pub path: String,
+    pub kind: u8,
+    pub size: usize,
+    pub data: Vec<u8>,
+}
+
+pub fn entry(path: &str, kind: u8, size: usize, data: Vec<u8>) -> RawEntry {
+    RawEntry {
+        path: path.to_string(),
+        kind,
+        size,
+        data,
+    }
+}
+
+pub fn build_wad(entries: &[RawEntry]) -> Vec<u8> {
+    let mut out = b"RW\x03\x04".to_vec();
+    out.extend_from_slice(&[0u8; 256 + 8]);
+    out.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    let mut offset = WAD_HEADER_SIZE as usize + entries.len() * WAD_ENTRY_SIZE;
+    for entry in entries {
+        out.extend_from_slice(&wad::path_hash(&entry.path).to_le_bytes());
+        out.extend_from_slice(&(offset as u32).to_le_bytes());
+        out.extend_from_slice(&(entry.data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(entry.size as u32).to_le_bytes());
+        out.push(entry.kind);
+        out.push(0);
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out.extend_from_slice(&0u64.to_le_bytes());
+        offset += entry.data.len();
+    }
+    for entry in entries {
+        out.extend_from_slice(&entry.data);
+    }
+    out
+}
+
+pub fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+// Écriture minimale du format, suffisante pour couvrir tous les types
+pub struct Writer(pub Vec<u8>);
+
+impl Writer {
+    pub fn u8(&mut self, v: u8) -> &mut Self {
+        self.0.push(v);
+        self
+    }
+    pub fn u16(&mut self, v: u16) -> &mut Self {
+        self.0.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+    pub fn u32(&mut self, v: u32) -> &mut Self {
+        self.0.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+    pub fn u64(&mut self, v: u64) -> &mut Self {
+        self.0.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+    pub fn f32(&mut self, v: f32) -> &mut Self {
+        self.0.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+    pub fn string(&mut self, v: &str) -> &mut Self {
+        self.u16(v.len() as u16);
+        self.0.extend_from_slice(v.as_bytes());
+        self
+    }
+    pub fn field(&mut self, name: &str, kind: BinType) -> &mut Self {
+        self.u32(fnv1a(name)).u8(kind as u8)
+    }
+    pub fn sized(&mut self, body: impl FnOnce(&mut Writer)) -> &mut Self {
+        let mut inner = Writer(Vec::new());
+        body(&mut inner);
+        self.u32(inner.0.len() as u32);
+        self.0.extend_from_slice(&inner.0);
+        self
+    }
+}
@@ -0,0 +1,147 @@
+// Différences sémantiques des .bin de champions entre deux patchs
+use app_lib::game_diff::{self, PropertyChange};
+use app_lib::hashes::HashTable;
+use app_lib::prop_bin::{fnv1a, BinType};
+use common::{build_wad, entry, Writer};
+use serde_json::json;
+use std::path::Path;
+
+mod common;
+
+const BIN_PATH: &str = "data/characters/ahri/ahri.bin";
+const SPELL: &str = "Characters/Ahri/Spells/AhriQAbility/AhriQ";
+
+fn data_value(w: &mut Writer, name: &str, values: &[f32]) {
+    w.u32(fnv1a("SpellDataValue")).sized(|w| {
+        w.u16(2);
+        w.field("mName", BinType::String).string(name);
+        w.field("mValues", BinType::List).u8(BinType::F32 as u8).sized(|w| {
+            w.u32(values.len() as u32);
+            for value in values {
+                w.f32(*value);
+            }
+        });
+    });
+}
+
+// Les valeurs sont volontairement listées dans un ordre différent selon le patch
+fn ahri_bin(base_damage: f32, reversed: bool, extra_entry: bool) -> Vec<u8> {
+    let mut w = Writer(Vec::new());
+    w.0.extend_from_slice(b"PROP");
+    w.u32(3).u32(0);
+    let count = if extra_entry { 2 } else { 1 };
+    w.u32(count);
+    for _ in 0..count {
+        w.u32(fnv1a("SpellObject"));
+    }
+    w.sized(|w| {
+        w.u32(fnv1a(SPELL)).u16(2);
+        w.field("mScriptName", BinType::String).string("AhriOrbofDeception");
+        w.field("mSpell", BinType::Embed).u32(fnv1a("SpellDataResource")).sized(|w| {
+            w.u16(1);
+            w.field("mDataValues", BinType::List).u8(BinType::Embed as u8).sized(|w| {
+                w.u32(2);
+                if reversed {
+                    data_value(w, "APRatio", &[0.45]);
+                    data_value(w, "BaseDamage", &[0.0, base_damage, 65.0]);
+                } else {
+                    data_value(w, "BaseDamage", &[0.0, base_damage, 65.0]);
+                    data_value(w, "APRatio", &[0.45]);
+                }
+            });
+        });
+    });
+    if extra_entry {
+        w.sized(|w| {
+            w.u32(fnv1a("Characters/Ahri/Spells/AhriPassive")).u16(0);
+        });
+    }
+    w.0
+}
+
+fn write_patch(dir: &Path, bin: Vec<u8>) {
+    let champions = dir.join("DATA/FINAL/Champions");
+    std::fs::create_dir_all(&champions).unwrap();
+    let texture = b"DDS \x7c".to_vec();
+    let wad = build_wad(&[
+        entry(BIN_PATH, 0, bin.len(), bin.clone()),
+        entry("assets/characters/ahri/ahri.dds", 0, texture.len(), texture),
+    ]);
+    std::fs::write(champions.join("Ahri.wad.client"), wad).unwrap();
+    // Les WAD localisés sont ignorés même s'ils contiennent des .bin
+    let localized = build_wad(&[entry("data/localized.bin", 0, bin.len(), bin)]);
+    std::fs::write(champions.join("Ahri.fr_FR.wad.client"), localized).unwrap();
+}
+
+fn table() -> HashTable {
+    let names = [
+        SPELL,
+        "SpellObject",
+        "SpellDataResource",
+        "SpellDataValue",
+        "mScriptName",
+        "mSpell",
+        "mDataValues",
+        "mName",
+        "mValues",
+        "Characters/Ahri/Spells/AhriPassive",
+    ];
+    let mut list: String = names.iter().map(|n| format!("{:08x} {}\n", fnv1a(n), n)).collect();
+    list.push_str(&format!("{:016x} {}\n", app_lib::wad::path_hash(BIN_PATH), BIN_PATH));
+    HashTable::parse(&list)
+}
+
+#[test]
+fn reports_changed_properties_between_patches() {
+    let dir = tempfile::tempdir().unwrap();
+    let (old, new) = (dir.path().join("14.1"), dir.path().join("14.2"));
+    write_patch(&old, ahri_bin(40.0, false, false));
+    write_patch(&new, ahri_bin(45.0, true, true));
+
+    let scopes = vec!["DATA/FINAL/Champions/".to_string()];
+    let mut diff = game_diff::diff_game_data(&old, &new, &scopes, &table()).unwrap();
+    assert_eq!(diff.bins_compared, 1);
+    assert_eq!(diff.added, vec!["Characters/Ahri/Spells/AhriPassive"]);
+    assert!(diff.removed.is_empty());
+    assert_eq!(diff.changed.len(), 1);
+    let spell = &diff.changed[0];
+    assert_eq!(spell.entry, SPELL);
+    assert_eq!(spell.class, "SpellObject");
+    assert_eq!(spell.bin_file, BIN_PATH);
+    // Les éléments nommés sont appariés par nom : seul le montant modifié ressort
+    assert_eq!(
+        spell.changes,
+        vec![PropertyChange {
+            property: "mSpell.mDataValues[BaseDamage].mValues[1]".to_string(),
+            old: Some(json!(40.0)),
+            new: Some(json!(45.0)),
+        }]
+    );
+
+    diff.old_label = "14.1".to_string();
+    diff.new_label = "14.2".to_string();
+    let markdown = game_diff::to_markdown(&diff);
+    assert!(markdown.starts_with("# Données de jeu : 14.1 → 14.2"));
+    assert!(markdown.contains(&format!("### {}", SPELL)));
+    assert!(markdown.contains("- `mSpell.mDataValues[BaseDamage].mValues[1]` : 40 → 45"));
+    assert!(markdown.contains("- Characters/Ahri/Spells/AhriPassive"));
+}
+
+#[test]
+fn unknown_names_and_scopes() {
+    let dir = tempfile::tempdir().unwrap();
+    let (old, new) = (dir.path().join("old"), dir.path().join("new"));
+    write_patch(&old, ahri_bin(40.0, false, false));
+    write_patch(&new, ahri_bin(45.0, false, false));
+
+    // Sans table de hash, les .bin sont reconnus à leur signature
+    let diff = game_diff::diff_game_data(&old, &new, &[], &HashTable::default()).unwrap();
+    assert_eq!(diff.bins_compared, 1);
+    assert_eq!(diff.changed[0].entry, format!("{{{:08x}}}", fnv1a(SPELL)));
+    assert_eq!(diff.changed[0].bin_file, format!("{:016x}.bin", app_lib::wad::path_hash(BIN_PATH)));
+
+    let scopes = vec!["DATA/FINAL/Maps/".to_string()];
+    let diff = game_diff::diff_game_data(&old, &new, &scopes, &table()).unwrap();
+    assert_eq!(diff.bins_compared, 0);
+    assert!(diff.changed.is_empty());
+}
@@ -0,0 +1,116 @@
+// Version réelle du jeu : ressource de version de l'exécutable et métadonnées du client
+use app_lib::game_version;
+use std::collections::HashMap;
+
+fn put_u16(data: &mut [u8], offset: usize, value: u16) {
+    data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+// PE32+ minimal : une section .rsrc avec RT_VERSION -> 1 -> 0x409 -> VS_VERSIONINFO
+fn build_exe(version: [u16; 4]) -> Vec<u8> {
+    let (pe, optional, section_table, rsrc) = (0x40, 0x58, 0x148, 0x200);
+    let mut data = vec![0u8; 0x300];
+    data[..2].copy_from_slice(b"MZ");
+    put_u32(&mut data, 0x3C, pe as u32);
+    data[pe..pe + 4].copy_from_slice(b"PE\0\0");
+    put_u16(&mut data, pe + 6, 1);
+    put_u16(&mut data, pe + 20, 240);
+    put_u16(&mut data, optional, 0x20B);
+    put_u32(&mut data, optional + 112 + 2 * 8, 0x1000);
+    put_u32(&mut data, optional + 112 + 2 * 8 + 4, 0x100);
+
+    data[section_table..section_table + 5].copy_from_slice(b".rsrc");
+    put_u32(&mut data, section_table + 8, 0x100);
+    put_u32(&mut data, section_table + 12, 0x1000);
+    put_u32(&mut data, section_table + 16, 0x100);
+    put_u32(&mut data, section_table + 20, rsrc as u32);
+
+    // Trois niveaux de répertoires à une entrée chacun
+    for (directory, id, target) in [(0x00, 16, 0x8000_0018), (0x18, 1, 0x8000_0030), (0x30, 0x409, 0x48)] {
+        put_u16(&mut data, rsrc + directory + 14, 1);
+        put_u32(&mut data, rsrc + directory + 16, id);
+        put_u32(&mut data, rsrc + directory + 20, target);
+    }
+    put_u32(&mut data, rsrc + 0x48, 0x1000 + 0x58);
+    put_u32(&mut data, rsrc + 0x4C, 0x60);
+
+    let info = rsrc + 0x58;
+    put_u16(&mut data, info, 0x60);
+    put_u16(&mut data, info + 2, 52);
+    for (i, c) in "VS_VERSION_INFO".encode_utf16().enumerate() {
+        put_u16(&mut data, info + 6 + i * 2, c);
+    }
+    put_u32(&mut data, info + 40, 0xFEEF_04BD);
+    put_u32(&mut data, info + 48, ((version[0] as u32) << 16) | version[1] as u32);
+    put_u32(&mut data, info + 52, ((version[2] as u32) << 16) | version[3] as u32);
+    data
+}
+
+#[test]
+fn reads_file_version_from_executable_resources() {
+    assert_eq!(game_version::pe_file_version(&build_exe([14, 17, 612, 7771])).unwrap(), "14.17.612.7771");
+
+    let mut no_resources = build_exe([14, 17, 612, 7771]);
+    put_u32(&mut no_resources, 0x58 + 112 + 2 * 8, 0);
+    assert!(game_version::pe_file_version(&no_resources).is_err());
+    assert!(game_version::pe_file_version(b"not an executable").is_err());
+    assert!(game_version::pe_file_version(&build_exe([1, 2, 3, 4])[..0x220]).is_err());
+}
+
+#[test]
+fn treats_overflowing_sections_as_missing() {
+    // Section qui se termine au-delà de 4 Go
+    let mut exe = build_exe([14, 17, 612, 7771]);
+    put_u32(&mut exe, 0x148 + 8, 0x200);
+    put_u32(&mut exe, 0x148 + 12, 0xFFFF_FF00);
+    put_u32(&mut exe, 0x58 + 112 + 2 * 8, 0xFFFF_FF80);
+    assert!(game_version::pe_file_version(&exe).unwrap_err().contains("hors des sections"));
+
+    // Position dans le fichier qui dépasse 32 bits une fois le décalage ajouté
+    let mut exe = build_exe([14, 17, 612, 7771]);
+    put_u32(&mut exe, 0x148 + 8, 0x200);
+    put_u32(&mut exe, 0x148 + 12, 0xF00);
+    put_u32(&mut exe, 0x148 + 20, 0xFFFF_FFF0);
+    assert!(game_version::pe_file_version(&exe).unwrap_err().contains("hors des sections"));
+}
+
+#[test]
+fn detects_version_in_downloaded_patch() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(game_version::detect_game_version(dir.path()).is_err());
+
+    // Métadonnées seules, puis l'exécutable qui fait foi
+    let metadata = dir.path().join("Game").join(game_version::CONTENT_METADATA);
+    std::fs::create_dir_all(metadata.parent().unwrap()).unwrap();
+    std::fs::write(&metadata, r#"{"version": "14.16.608.2213+branch.releases-14-16.code.public"}"#).unwrap();
+    assert_eq!(game_version::detect_game_version(dir.path()).unwrap(), "14.16.608.2213");
+
+    let exe = dir.path().join("Game").join(game_version::GAME_EXECUTABLE);
+    std::fs::write(&exe, build_exe([14, 17, 612, 7771])).unwrap();
+    assert_eq!(game_version::detect_game_version(dir.path()).unwrap(), "14.17.612.7771");
+
+    // Exécutable illisible : on retombe sur les métadonnées
+    std::fs::write(&exe, b"MZ").unwrap();
+    assert_eq!(game_version::detect_game_version(dir.path()).unwrap(), "14.16.608.2213");
+
+    assert_eq!(game_version::metadata_version(r#"{"other": 1}"#), None);
+    assert_eq!(game_version::patch_number("14.17.612.7771"), "14.17");
+}
+
+#[test]
+fn saves_and_loads_detected_versions() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data").join(game_version::VERSION_CACHE_FILE_NAME);
+    assert!(game_version::load_versions(&path).is_empty());
+
+    let versions = HashMap::from([("https://cdn/ABC.manifest".to_string(), "14.17.612.7771".to_string())]);
+    game_version::save_versions(&path, &versions).unwrap();
+    assert_eq!(game_version::load_versions(&path), versions);
+
+    std::fs::write(&path, "corrompu").unwrap();
+    assert!(game_version::load_versions(&path).is_empty());
+}
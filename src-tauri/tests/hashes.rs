@@ -0,0 +1,41 @@
+// Chargement des listes de hash communautaires
+use app_lib::hashes::{self, HashTable};
+use app_lib::mock_cdn::MockCdn;
+use app_lib::wad::path_hash;
+
+#[test]
+fn parses_hash_lists_and_keeps_the_latest_name() {
+    let mut table = HashTable::parse(
+        "0123456789abcdef assets/a.png\n\
+         not-a-hash assets/b.png\n\
+         \n\
+         fedcba9876543210 data/menu/main.stringtable\n\
+         0123456789ABCDEF assets/a_renamed.png\n\
+         1111111111111111\n",
+    );
+    assert_eq!(table.len(), 2);
+    assert_eq!(table.get(0x0123456789abcdef), Some("assets/a_renamed.png"));
+    assert_eq!(table.get(0xfedcba9876543210), Some("data/menu/main.stringtable"));
+    assert_eq!(table.get(0x1111111111111111), None);
+
+    table.extend(&format!("{:016x} data/characters/ahri/ahri.bin", path_hash("DATA/Characters/Ahri/Ahri.bin")));
+    assert_eq!(table.len(), 3);
+    assert_eq!(table.get(path_hash("data/characters/ahri/ahri.bin")), Some("data/characters/ahri/ahri.bin"));
+    assert_eq!(table.get(0x0123456789abcdef), Some("assets/a_renamed.png"));
+}
+
+#[tokio::test]
+async fn fetches_hash_lists_from_urls_and_local_files() {
+    let cdn = MockCdn::start().await.unwrap();
+    cdn.set_route("/hashes.game.txt", b"0000000000000001 assets/remote.png\n".to_vec()).await;
+    let remote = hashes::fetch_hash_list(&format!("{}/hashes.game.txt", cdn.base_url())).await.unwrap();
+    assert_eq!(HashTable::parse(&remote).get(1), Some("assets/remote.png"));
+    assert!(hashes::fetch_hash_list(&format!("{}/missing.txt", cdn.base_url())).await.is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let local = dir.path().join("hashes.txt");
+    std::fs::write(&local, "0000000000000002 assets/local.png\n").unwrap();
+    let text = hashes::fetch_hash_list(&local.to_string_lossy()).await.unwrap();
+    assert_eq!(HashTable::parse(&text).get(2), Some("assets/local.png"));
+    assert_eq!(HashTable::load_file(&local).unwrap().len(), 1);
+}
@@ -0,0 +1,117 @@
+// Historique des manifests : enregistrement, recherche et patch en jeu à une date
+use app_lib::history::{HistoryQuery, ManifestHistory, ManifestRecord};
+
+fn record(realm: &str, hash: &str, date: &str, patch: Option<&str>) -> ManifestRecord {
+    ManifestRecord {
+        url: format!("https://cdn/channels/public/releases/{}.manifest", hash),
+        realm: realm.to_string(),
+        hash: hash.to_string(),
+        date: date.to_string(),
+        size_bytes: 1024,
+        version: patch.map(|p| format!("Patch {}", p)).unwrap_or_else(|| format!("Version {}", hash)),
+        patch_version: patch.map(|p| p.parse().unwrap()),
+        build_version: None,
+        first_seen: String::new(),
+        last_seen: String::new(),
+    }
+}
+
+fn catalogue() -> Vec<ManifestRecord> {
+    vec![
+        record("EUW1", "AAAA", "2024-02-21", Some("24.4")),
+        record("EUW1", "BBBB", "2024-03-06", Some("24.5")),
+        record("EUW1", "CCCC", "2024-03-12 09:30:00", Some("24.5.1")),
+        record("EUW1", "DDDD", "2024-08-28", Some("24.17")),
+        record("NA1", "EEEE", "2024-03-06", Some("24.5")),
+        record("PBE1", "FFFF", "2024-03-08", None),
+    ]
+}
+
+#[test]
+fn records_manifests_and_tracks_sightings() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data").join("history.sqlite");
+    let mut history = ManifestHistory::open(&path).unwrap();
+    assert!(history.is_empty().unwrap());
+
+    assert_eq!(history.record(&catalogue(), "2024-09-01T10:00:00Z").unwrap(), 6);
+    // Le manifest AAAA a disparu du catalogue mais reste dans l'historique
+    assert_eq!(history.record(&catalogue()[1..], "2024-09-02T10:00:00Z").unwrap(), 0);
+    history
+        .set_build_version(&catalogue()[3].url, "14.17.612.7771", Some("24.17".parse().unwrap()))
+        .unwrap();
+    drop(history);
+
+    let history = ManifestHistory::open(&path).unwrap();
+    assert_eq!(history.len().unwrap(), 6);
+    let all = history.search(&HistoryQuery::default()).unwrap();
+    let old = all.iter().find(|r| r.hash == "AAAA").unwrap();
+    assert_eq!((old.first_seen.as_str(), old.last_seen.as_str()), ("2024-09-01T10:00:00Z", "2024-09-01T10:00:00Z"));
+    let latest = &all[0];
+    assert_eq!(latest.hash, "DDDD");
+    assert_eq!(latest.last_seen, "2024-09-02T10:00:00Z");
+    assert_eq!(latest.build_version.as_deref(), Some("14.17.612.7771"));
+
+    // Une actualisation sans version détectée ne l'efface pas
+    let mut history = history;
+    history.record(&catalogue()[3..4], "2024-09-03T10:00:00Z").unwrap();
+    let search = HistoryQuery {
+        text: Some("14.17.612".to_string()),
+        ..HistoryQuery::default()
+    };
+    assert_eq!(history.search(&search).unwrap().len(), 1);
+}
+
+#[test]
+fn searches_by_realm_versions_dates_and_text() {
+    let mut history = ManifestHistory::open_in_memory().unwrap();
+    history.record(&catalogue(), "2024-09-01T10:00:00Z").unwrap();
+    let hashes = |query: HistoryQuery| -> Vec<String> { history.search(&query).unwrap().into_iter().map(|r| r.hash).collect() };
+
+    assert_eq!(
+        hashes(HistoryQuery {
+            realm: Some("euw1".to_string()),
+            ..HistoryQuery::default()
+        }),
+        vec!["DDDD", "CCCC", "BBBB", "AAAA"]
+    );
+    assert_eq!(
+        hashes(HistoryQuery {
+            from_version: Some("24.5".parse().unwrap()),
+            to_version: Some("24.9".parse().unwrap()),
+            ..HistoryQuery::default()
+        }),
+        vec!["CCCC", "BBBB", "EEEE"]
+    );
+    assert_eq!(
+        hashes(HistoryQuery {
+            from_date: Some("2024-03-06".to_string()),
+            to_date: Some("2024-03-12".to_string()),
+            realm: Some("EUW1".to_string()),
+            ..HistoryQuery::default()
+        }),
+        vec!["CCCC", "BBBB"]
+    );
+    assert_eq!(
+        hashes(HistoryQuery {
+            text: Some("pbe".to_string()),
+            ..HistoryQuery::default()
+        }),
+        vec!["FFFF"]
+    );
+    assert_eq!(
+        hashes(HistoryQuery {
+            limit: Some(2),
+            ..HistoryQuery::default()
+        })
+        .len(),
+        2
+    );
+
+    let live = history.live_at("EUW1", "2024-03-10").unwrap().unwrap();
+    assert_eq!(live.hash, "BBBB");
+    assert_eq!(live.patch_version.unwrap().to_string(), "24.5");
+    assert_eq!(history.live_at("EUW1", "2024-03-12").unwrap().unwrap().hash, "CCCC");
+    assert!(history.live_at("EUW1", "2023-12-31").unwrap().is_none());
+    assert!(history.live_at("KR", "2024-03-10").unwrap().is_none());
+}
@@ -0,0 +1,128 @@
+// Journal des fichiers créés par une tâche : suppression à l'annulation sans toucher aux fichiers existants
+use app_lib::downloader::{self, DownloadControl, DownloadOptions};
+use app_lib::journal::{self, FileJournal};
+use app_lib::mock_cdn::{sample_data, MockCdn, MockFile, MockRelease};
+use std::sync::Arc;
+
+#[test]
+fn records_only_paths_created_by_the_task() {
+    let dir = tempfile::tempdir().unwrap();
+    let journal_path = dir.path().join("journals").join("task.jsonl");
+    let existing = dir.path().join("patch");
+    std::fs::create_dir_all(&existing).unwrap();
+    std::fs::write(existing.join("notes.txt"), b"user").unwrap();
+
+    let journal = FileJournal::open(&journal_path).unwrap();
+    journal.create_dir_all(&existing.join("DATA").join("FINAL")).unwrap();
+    assert!(!journal.record_new(&existing.join("notes.txt")).unwrap());
+    assert!(journal.record_new(&existing.join("DATA/FINAL/a.bin")).unwrap());
+    std::fs::write(existing.join("DATA/FINAL/a.bin"), b"data").unwrap();
+    drop(journal);
+
+    // Reprise : le journal est complété
+    let journal = FileJournal::open(&journal_path).unwrap();
+    journal.record_new(&existing.join("DATA/b.bin")).unwrap();
+    std::fs::write(existing.join("DATA/b.bin"), b"data").unwrap();
+    assert_eq!(
+        journal::read_journal(&journal_path).unwrap(),
+        vec![
+            existing.join("DATA"),
+            existing.join("DATA").join("FINAL"),
+            existing.join("DATA/FINAL/a.bin"),
+            existing.join("DATA/b.bin"),
+        ]
+    );
+
+    let report = journal::remove_created(&journal_path).unwrap();
+    assert_eq!((report.removed_files, report.removed_dirs), (2, 2));
+    assert!(report.kept.is_empty());
+    assert_eq!(std::fs::read(existing.join("notes.txt")).unwrap(), b"user");
+    assert!(!existing.join("DATA").exists());
+    assert!(!journal_path.exists());
+    // Journal absent : rien à faire
+    assert_eq!(journal::remove_created(&journal_path).unwrap().removed_files, 0);
+}
+
+#[test]
+fn keeps_directories_holding_user_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let journal_path = dir.path().join("task.jsonl");
+    let output = dir.path().join("out");
+    let journal = FileJournal::open(&journal_path).unwrap();
+    journal.create_dir_all(&output).unwrap();
+    journal.record_new(&output.join("a.bin")).unwrap();
+    std::fs::write(output.join("a.bin"), b"data").unwrap();
+    // Fichier ajouté par l'utilisateur après le début du téléchargement
+    std::fs::write(output.join("mine.txt"), b"user").unwrap();
+
+    let report = journal::remove_created(&journal_path).unwrap();
+    assert_eq!((report.removed_files, report.removed_dirs), (1, 0));
+    assert_eq!(report.kept, vec![output.to_string_lossy().to_string()]);
+    assert!(output.join("mine.txt").exists());
+}
+
+#[tokio::test]
+async fn cancelled_download_removes_its_files_only() {
+    let cdn = MockCdn::start().await.unwrap();
+    let release = MockRelease::new(
+        "EUW1",
+        "2024-08-28",
+        vec![
+            MockFile::new("DATA/FINAL/big.wad.client", sample_data(1, 300_000)),
+            MockFile::new("Game/League of Legends.exe", sample_data(2, 5_000)),
+        ],
+    );
+    let published = cdn.publish(&release).await;
+    let manifest = downloader::fetch_manifest(&published.url).await.unwrap();
+
+    // Dossier existant avec un fichier de l'utilisateur et une ancienne copie invalide d'un fichier du patch
+    let root = tempfile::tempdir().unwrap();
+    let output = root.path().join("patch");
+    std::fs::create_dir_all(output.join("Game")).unwrap();
+    std::fs::write(output.join("Game").join("settings.cfg"), b"user").unwrap();
+    std::fs::write(output.join("Game").join("League of Legends.exe"), b"old").unwrap();
+
+    let journal_path = root.path().join("task.jsonl");
+    let options = DownloadOptions {
+        cdn_url: cdn.cdn_url(),
+        speed_limit: 128 * 1024,
+        workers: 1,
+        journal: Some(Arc::new(FileJournal::open(&journal_path).unwrap())),
+        ..DownloadOptions::default()
+    };
+    let control = Arc::new(DownloadControl::default());
+    let stopper = control.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(400)).await;
+        stopper.cancel();
+    });
+    let summary = downloader::download_manifest(&manifest, &output, &options, control).await.unwrap();
+    assert!(summary.interrupted);
+    assert!(downloader::partial_path(&output.join("DATA/FINAL/big.wad.client")).exists());
+
+    journal::remove_created(&journal_path).unwrap();
+    assert!(!output.join("DATA").exists());
+    assert!(!downloader::partial_path(&output.join("Game/League of Legends.exe")).exists());
+    assert_eq!(std::fs::read(output.join("Game").join("settings.cfg")).unwrap(), b"user");
+    assert!(output.join("Game").join("League of Legends.exe").exists());
+}
+
+#[test]
+fn sweeps_recorded_partial_files_outside_the_download_root() {
+    let dir = tempfile::tempdir().unwrap();
+    let journal_path = dir.path().join("task.jsonl");
+    let custom = dir.path().join("elsewhere");
+    let journal = FileJournal::open(&journal_path).unwrap();
+    journal.create_dir_all(&custom).unwrap();
+    let partial = downloader::partial_path(&custom.join("a.bin"));
+    journal.record_new(&custom.join("a.bin")).unwrap();
+    journal.record_new(&partial).unwrap();
+    std::fs::write(&partial, b"half").unwrap();
+    std::fs::write(custom.join("b.bin"), b"done").unwrap();
+
+    assert_eq!(journal::remove_recorded_partials(&journal_path).unwrap(), 1);
+    assert!(!partial.exists());
+    assert!(custom.join("b.bin").exists());
+    // Le journal reste utilisable pour une annulation avec suppression
+    assert_eq!(journal::remove_recorded_partials(&journal_path).unwrap(), 0);
+}
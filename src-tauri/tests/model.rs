@@ -0,0 +1,205 @@
+// Lecture des .skn/.skl et export glTF binaire
+use app_lib::model::{self, Skeleton, SkinnedMesh};
+
+fn f32s(out: &mut Vec<u8>, values: &[f32]) {
+    for value in values {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+// Triangle en deux sous-maillages, sommets de 56 octets (avec couleur)
+fn build_skn() -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&0x0011_2233u32.to_le_bytes());
+    data.extend_from_slice(&4u16.to_le_bytes());
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&2u32.to_le_bytes());
+    for (name, start_index, count) in [("Body", 0u32, 3u32), ("Tail", 3, 3)] {
+        let mut raw = [0u8; 64];
+        raw[..name.len()].copy_from_slice(name.as_bytes());
+        data.extend_from_slice(&raw);
+        for value in [0, 4, start_index, count] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&6u32.to_le_bytes());
+    data.extend_from_slice(&4u32.to_le_bytes());
+    data.extend_from_slice(&56u32.to_le_bytes());
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&[0u8; 40]);
+    for index in [0u16, 1, 2, 1, 3, 2] {
+        data.extend_from_slice(&index.to_le_bytes());
+    }
+    for (i, position) in [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 2.0, 0.0], [1.0, 2.0, -1.0]].iter().enumerate() {
+        f32s(&mut data, position);
+        data.extend_from_slice(&[0, 1, 0, 0]);
+        f32s(&mut data, &[3.0, 1.0, 0.0, 0.0]);
+        f32s(&mut data, &[0.0, 0.0, 1.0]);
+        f32s(&mut data, &[i as f32 * 0.25, 0.5]);
+        data.extend_from_slice(&[255, 255, 255, 255]);
+    }
+    data
+}
+
+fn build_skl() -> Vec<u8> {
+    let joints = [("Root", -1i16, [0.0f32, 0.0, 0.0]), ("Spine", 0, [0.0, 1.0, 0.0])];
+    let joints_offset = 64usize;
+    let influences_offset = joints_offset + joints.len() * 100;
+    let names_offset = influences_offset + 4;
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&0x22FD_4FC3u32.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&(joints.len() as u16).to_le_bytes());
+    data.extend_from_slice(&2u32.to_le_bytes());
+    for offset in [joints_offset, 0, influences_offset, 0, 0, 0, 0, 0, 0, 0, 0] {
+        data.extend_from_slice(&(offset as i32).to_le_bytes());
+    }
+    assert_eq!(data.len(), joints_offset);
+
+    let mut name_position = names_offset;
+    for (i, (name, parent, translation)) in joints.iter().enumerate() {
+        let start = data.len();
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&(i as i16).to_le_bytes());
+        data.extend_from_slice(&parent.to_le_bytes());
+        data.extend_from_slice(&[0u8; 10]);
+        f32s(&mut data, translation);
+        f32s(&mut data, &[1.0, 1.0, 1.0]);
+        f32s(&mut data, &[0.0, 0.0, 0.0, 1.0]);
+        f32s(&mut data, &translation.map(|v| -v));
+        f32s(&mut data, &[1.0, 1.0, 1.0]);
+        f32s(&mut data, &[0.0, 0.0, 0.0, 1.0]);
+        let field = data.len();
+        data.extend_from_slice(&((name_position - field) as i32).to_le_bytes());
+        assert_eq!(data.len() - start, 100);
+        name_position += name.len() + 1;
+    }
+    // Os 0 du .skn -> Spine, os 1 -> Root
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&0u16.to_le_bytes());
+    for (name, _, _) in joints {
+        data.extend_from_slice(name.as_bytes());
+        data.push(0);
+    }
+    data
+}
+
+fn glb_json(glb: &[u8]) -> (serde_json::Value, &[u8]) {
+    assert_eq!(&glb[..4], b"glTF");
+    assert_eq!(u32::from_le_bytes(glb[4..8].try_into().unwrap()), 2);
+    assert_eq!(u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize, glb.len());
+    let json_length = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+    assert_eq!(json_length % 4, 0);
+    let json = serde_json::from_slice(&glb[20..20 + json_length]).unwrap();
+    let bin = &glb[20 + json_length + 8..];
+    (json, bin)
+}
+
+#[test]
+fn parses_skn_and_skl_files() {
+    let mesh = SkinnedMesh::parse(&build_skn()).unwrap();
+    assert_eq!(mesh.version, (4, 1));
+    assert_eq!(mesh.submeshes.len(), 2);
+    assert_eq!(mesh.submeshes[1].name, "Tail");
+    assert_eq!(mesh.vertices.len(), 4);
+    assert_eq!(mesh.vertices[3].position, [1.0, 2.0, -1.0]);
+    assert_eq!(mesh.vertices[2].uv, [0.5, 0.5]);
+
+    let skeleton = Skeleton::parse(&build_skl()).unwrap();
+    assert_eq!(skeleton.joints.len(), 2);
+    assert_eq!(skeleton.joints[1].name, "Spine");
+    assert_eq!(skeleton.joints[1].parent, 0);
+    assert_eq!(skeleton.influences, vec![1, 0]);
+
+    assert!(SkinnedMesh::parse(&build_skn()[..100]).is_err());
+    assert!(Skeleton::parse(b"r3d2sklt\x00\x00\x00\x00").is_err());
+}
+
+#[test]
+fn exports_a_skinned_mesh_to_glb() {
+    let mesh = SkinnedMesh::parse(&build_skn()).unwrap();
+    let skeleton = Skeleton::parse(&build_skl()).unwrap();
+    let png = b"\x89PNG\r\n\x1a\nfake".to_vec();
+    let glb = model::to_glb(&mesh, Some(&skeleton), Some(&png)).unwrap();
+    let (json, bin) = glb_json(&glb);
+
+    assert_eq!(json["asset"]["version"], "2.0");
+    assert_eq!(json["buffers"][0]["byteLength"].as_u64().unwrap() as usize, bin.len());
+    let primitives = json["meshes"][0]["primitives"].as_array().unwrap();
+    assert_eq!(primitives.len(), 2);
+    let position = &json["accessors"][primitives[0]["attributes"]["POSITION"].as_u64().unwrap() as usize];
+    assert_eq!(position["count"], 4);
+    assert_eq!(position["max"], serde_json::json!([1.0, 2.0, 0.0]));
+    assert_eq!(position["min"], serde_json::json!([0.0, 0.0, -1.0]));
+
+    // Les index d'os passent par la table d'influences et les poids sont normalisés
+    let read_view = |accessor: &serde_json::Value| -> &[u8] {
+        let view = &json["bufferViews"][accessor["bufferView"].as_u64().unwrap() as usize];
+        let offset = view["byteOffset"].as_u64().unwrap() as usize;
+        &bin[offset..offset + view["byteLength"].as_u64().unwrap() as usize]
+    };
+    let joints = read_view(&json["accessors"][primitives[0]["attributes"]["JOINTS_0"].as_u64().unwrap() as usize]);
+    assert_eq!(&joints[..8], &[1, 0, 0, 0, 1, 0, 1, 0]);
+    let weights = read_view(&json["accessors"][primitives[0]["attributes"]["WEIGHTS_0"].as_u64().unwrap() as usize]);
+    assert_eq!(f32::from_le_bytes(weights[..4].try_into().unwrap()), 0.75);
+    let tail = read_view(&json["accessors"][primitives[1]["indices"].as_u64().unwrap() as usize]);
+    assert_eq!(tail, &[1, 0, 3, 0, 2, 0]);
+
+    assert_eq!(json["skins"][0]["joints"], serde_json::json!([0, 1]));
+    assert_eq!(json["nodes"][0]["children"], serde_json::json!([1]));
+    assert_eq!(json["nodes"][2]["skin"], 0);
+    assert_eq!(json["scenes"][0]["nodes"], serde_json::json!([0, 2]));
+    let ibm = read_view(&json["accessors"][json["skins"][0]["inverseBindMatrices"].as_u64().unwrap() as usize]);
+    assert_eq!(ibm.len(), 2 * 64);
+    assert_eq!(f32::from_le_bytes(ibm[64 + 52..64 + 56].try_into().unwrap()), -1.0);
+
+    assert_eq!(json["images"][0]["mimeType"], "image/png");
+    assert_eq!(read_view(&json["images"][0]), &png[..]);
+    assert_eq!(json["materials"][0]["pbrMetallicRoughness"]["baseColorTexture"]["index"], 0);
+
+    // Sans squelette : maillage statique
+    let (json, _) = glb_json(&model::to_glb(&mesh, None, None).unwrap());
+    assert!(json.get("skins").is_none());
+    assert!(json["meshes"][0]["primitives"][0]["attributes"].get("JOINTS_0").is_none());
+}
+
+#[test]
+fn finds_matching_skeleton_and_texture() {
+    let mesh = "assets/characters/ahri/skins/base/Ahri.skn";
+    assert_eq!(model::skeleton_path(mesh).as_deref(), Some("assets/characters/ahri/skins/base/Ahri.skl"));
+    assert_eq!(model::skeleton_path("ahri.dds"), None);
+    let names = [
+        "assets/characters/ahri/skins/base/ahri_base_tx_cm.dds",
+        "assets/characters/ahri/skins/base/ahri_base_tx_cm.tex",
+        "assets/characters/ahri/skins/base/particles/ahri_tx_cm.dds",
+        "assets/characters/ahri/skins/skin01/ahri_skin01_tx_cm.dds",
+        "assets/characters/ahri/skins/base/ahri_base_normal.dds",
+    ];
+    assert_eq!(
+        model::matching_texture(mesh, names.into_iter()),
+        Some("assets/characters/ahri/skins/base/ahri_base_tx_cm.dds")
+    );
+    assert_eq!(model::matching_texture("other/ahri.skn", names.into_iter()), None);
+}
+
+#[test]
+fn rejects_counts_larger_than_the_file() {
+    // Sous-maillages puis sommets annoncés par milliards
+    for offset in [8, 180] {
+        let mut skn = build_skn();
+        skn[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(SkinnedMesh::parse(&skn).unwrap_err().contains("tronqué"), "octet {}", offset);
+    }
+
+    // Influences annoncées par milliards, puis table des articulations à une position négative
+    let mut skl = build_skl();
+    skl[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(Skeleton::parse(&skl).unwrap_err().contains("tronqué"));
+    let mut skl = build_skl();
+    skl[20..24].copy_from_slice(&(-1i32).to_le_bytes());
+    assert!(Skeleton::parse(&skl).is_err());
+}
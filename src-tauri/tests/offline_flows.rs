@@ -0,0 +1,276 @@
+// Téléchargement, vérification et comparaison de bout en bout contre le CDN local
+use app_lib::catalogue;
+use app_lib::diff::diff_manifests;
+use app_lib::disk_space;
+use app_lib::error::DownloadError;
+use app_lib::downloader::{self, DownloadControl, DownloadOptions};
+use app_lib::mock_cdn::{sample_data, MockCdn, MockFile, MockRelease};
+use app_lib::rman::HASH_TYPE_RITO_HKDF;
+use std::sync::Arc;
+
+fn release_v1() -> MockRelease {
+    MockRelease::new(
+        "EUW1",
+        "2024-08-28",
+        vec![
+            MockFile::new("DATA/FINAL/Champions/Ahri.wad.client", sample_data(1, 70_000)),
+            MockFile::new("DATA/FINAL/Maps/Map11.wad.client", sample_data(2, 40_000)),
+            MockFile::new("DATA/FINAL/Champions/Ahri.fr_FR.wad.client", sample_data(3, 20_000)).with_languages(&["fr_FR"]),
+            MockFile::new("League of Legends.exe", sample_data(4, 5_000)),
+        ],
+    )
+}
+
+fn options(cdn: &MockCdn, language: &str) -> DownloadOptions {
+    DownloadOptions {
+        cdn_url: cdn.cdn_url(),
+        language: language.to_string(),
+        ..DownloadOptions::default()
+    }
+}
+
+#[tokio::test]
+async fn catalogue_lists_published_manifests() {
+    let cdn = MockCdn::start().await.unwrap();
+    let published = cdn.publish(&release_v1()).await;
+    cdn.publish(&MockRelease::new("PBE1", "2024-08-30", vec![MockFile::new("a.bin", sample_data(5, 10))]))
+        .await;
+
+    let entries = catalogue::fetch_catalogue(&cdn.catalogue_url()).await.unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].realm, "EUW1");
+    assert_eq!(entries[0].manifest_url, published.url);
+    assert_eq!(entries[0].size_bytes, published.size_bytes);
+    assert_eq!(entries[0].manifest_hash(), format!("{:016X}", published.id));
+    assert_eq!(entries[1].realm, "PBE1");
+}
+
+#[tokio::test]
+async fn download_writes_selected_files() {
+    let cdn = MockCdn::start().await.unwrap();
+    let release = release_v1();
+    let published = cdn.publish(&release).await;
+    let output = tempfile::tempdir().unwrap();
+
+    let manifest = downloader::fetch_manifest(&published.url).await.unwrap();
+    assert_eq!(manifest.id, published.id);
+    assert_eq!(manifest.files.len(), 4);
+
+    let control = Arc::new(DownloadControl::default());
+    let summary = downloader::download_manifest(&manifest, output.path(), &options(&cdn, "en_us"), control.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(summary.files, 3);
+    assert!(!summary.interrupted);
+    assert_eq!(control.downloaded_bytes(), control.total_bytes());
+    for file in &release.files {
+        let path = output.path().join(&file.path);
+        if file.languages.is_empty() {
+            assert_eq!(std::fs::read(&path).unwrap(), file.data, "{}", file.path);
+        } else {
+            assert!(!path.exists(), "{} ne devrait pas être téléchargé", file.path);
+        }
+    }
+}
+
+#[tokio::test]
+async fn download_supports_hkdf_chunk_ids() {
+    let cdn = MockCdn::start_with(8 * 1024, HASH_TYPE_RITO_HKDF).await.unwrap();
+    let release = release_v1();
+    let published = cdn.publish(&release).await;
+    let output = tempfile::tempdir().unwrap();
+
+    let manifest = downloader::fetch_manifest(&published.url).await.unwrap();
+    downloader::download_manifest(&manifest, output.path(), &options(&cdn, "fr_FR"), Arc::default())
+        .await
+        .unwrap();
+
+    for file in &release.files {
+        assert_eq!(std::fs::read(output.path().join(&file.path)).unwrap(), file.data);
+    }
+}
+
+#[tokio::test]
+async fn verify_detects_missing_and_corrupted_files_and_resume_refetches_them() {
+    let cdn = MockCdn::start().await.unwrap();
+    let published = cdn.publish(&release_v1()).await;
+    let output = tempfile::tempdir().unwrap();
+    let options = options(&cdn, "en_us");
+
+    let manifest = downloader::fetch_manifest(&published.url).await.unwrap();
+    downloader::download_manifest(&manifest, output.path(), &options, Arc::default())
+        .await
+        .unwrap();
+    assert!(downloader::verify_files(&manifest, output.path(), &options).unwrap().is_ok());
+
+    let corrupted = output.path().join("DATA/FINAL/Maps/Map11.wad.client");
+    let mut data = std::fs::read(&corrupted).unwrap();
+    data[100] ^= 0xFF;
+    std::fs::write(&corrupted, data).unwrap();
+    std::fs::remove_file(output.path().join("League of Legends.exe")).unwrap();
+
+    let report = downloader::verify_files(&manifest, output.path(), &options).unwrap();
+    assert_eq!(report.checked, 3);
+    assert_eq!(report.missing, vec!["League of Legends.exe".to_string()]);
+    assert_eq!(report.corrupted, vec!["DATA/FINAL/Maps/Map11.wad.client".to_string()]);
+
+    cdn.clear_requests().await;
+    let summary = downloader::download_manifest(&manifest, output.path(), &options, Arc::default())
+        .await
+        .unwrap();
+    assert_eq!(summary.skipped_files, 1);
+    assert!(cdn.requests().await.iter().all(|path| path.contains("/bundles/")));
+    assert!(downloader::verify_files(&manifest, output.path(), &options).unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn download_respects_the_speed_limit() {
+    let cdn = MockCdn::start().await.unwrap();
+    let published = cdn
+        .publish(&MockRelease::new("EUW1", "2024-08-28", vec![MockFile::new("big.bin", sample_data(7, 200_000))]))
+        .await;
+    let output = tempfile::tempdir().unwrap();
+
+    let manifest = downloader::fetch_manifest(&published.url).await.unwrap();
+    let options = DownloadOptions {
+        speed_limit: 256 * 1024,
+        ..options(&cdn, "en_us")
+    };
+    let started = std::time::Instant::now();
+    downloader::download_manifest(&manifest, output.path(), &options, Arc::default())
+        .await
+        .unwrap();
+
+    // Les données compressées font plus de 100 Ko, soit au moins ~0,4 s à 256 Ko/s
+    assert!(started.elapsed() >= std::time::Duration::from_millis(350));
+}
+
+#[tokio::test]
+async fn disk_space_is_checked_before_and_during_download() {
+    let cdn = MockCdn::start().await.unwrap();
+    let published = cdn.publish(&release_v1()).await;
+    let output = tempfile::tempdir().unwrap();
+    let manifest = downloader::fetch_manifest(&published.url).await.unwrap();
+
+    let required = disk_space::required_space(&manifest, output.path(), &options(&cdn, "en_us")).unwrap();
+    assert_eq!(required, 70_000 + 40_000 + 5_000);
+    assert!(disk_space::check_disk_space(output.path(), required, 0).is_ok());
+    match disk_space::check_disk_space(output.path(), u64::MAX / 2, 0) {
+        Err(DownloadError::InsufficientDiskSpace { required, .. }) => assert_eq!(required, u64::MAX / 2),
+        other => panic!("erreur inattendue: {:?}", other),
+    }
+
+    // Le moteur s'arrête proprement quand l'espace libre passe sous la réserve
+    let options = DownloadOptions {
+        min_free_space: u64::MAX,
+        ..options(&cdn, "en_us")
+    };
+    let summary = downloader::download_manifest(&manifest, output.path(), &options, Arc::default())
+        .await
+        .unwrap();
+    assert!(summary.disk_full);
+    assert!(summary.interrupted);
+    assert!(cdn.requests().await.iter().all(|path| !path.contains("/bundles/")));
+}
+
+#[tokio::test]
+async fn required_space_counts_files_that_will_be_rewritten() {
+    let cdn = MockCdn::start().await.unwrap();
+    let published = cdn.publish(&release_v1()).await;
+    let output = tempfile::tempdir().unwrap();
+    let options = options(&cdn, "en_us");
+    let manifest = downloader::fetch_manifest(&published.url).await.unwrap();
+    downloader::download_manifest(&manifest, output.path(), &options, Arc::default())
+        .await
+        .unwrap();
+    assert_eq!(disk_space::required_space(&manifest, output.path(), &options).unwrap(), 0);
+
+    // Un fichier corrompu de la bonne taille est réécrit en entier dans un fichier temporaire
+    let corrupted = output.path().join("DATA/FINAL/Maps/Map11.wad.client");
+    let mut data = std::fs::read(&corrupted).unwrap();
+    data[100] ^= 0xFF;
+    std::fs::write(&corrupted, data).unwrap();
+    assert_eq!(disk_space::required_space(&manifest, output.path(), &options).unwrap(), 40_000);
+
+    // La place déjà réservée par le fichier temporaire est déduite
+    std::fs::write(downloader::partial_path(&corrupted), vec![0; 10_000]).unwrap();
+    assert_eq!(disk_space::required_space(&manifest, output.path(), &options).unwrap(), 30_000);
+}
+
+#[tokio::test]
+async fn download_fails_when_a_bundle_is_missing() {
+    let cdn = MockCdn::start().await.unwrap();
+    let published = cdn.publish(&release_v1()).await;
+    let output = tempfile::tempdir().unwrap();
+
+    let manifest = downloader::fetch_manifest(&published.url).await.unwrap();
+    let bundle_id = manifest.file("League of Legends.exe").unwrap().chunk_ids[0];
+    let bundle_id = manifest.chunks[&bundle_id].bundle_id;
+    cdn.remove_route(&format!("/channels/public/bundles/{:016X}.bundle", bundle_id)).await;
+
+    let result = downloader::download_manifest(&manifest, output.path(), &options(&cdn, "en_us"), Arc::default()).await;
+    assert!(result.unwrap_err().contains("404"));
+}
+
+#[tokio::test]
+async fn diff_reports_added_removed_and_changed_files() {
+    let cdn = MockCdn::start().await.unwrap();
+    let old = cdn.publish(&release_v1()).await;
+
+    let mut files = release_v1().files;
+    files.retain(|f| f.path != "DATA/FINAL/Maps/Map11.wad.client");
+    files[0].data[10] = b'!';
+    files.push(MockFile::new("DATA/FINAL/Champions/Aurora.wad.client", sample_data(6, 30_000)));
+    let new = cdn.publish(&MockRelease::new("EUW1", "2024-09-11", files)).await;
+
+    let old = downloader::fetch_manifest(&old.url).await.unwrap();
+    let new = downloader::fetch_manifest(&new.url).await.unwrap();
+    let diff = diff_manifests(&old, &new);
+
+    let paths = |entries: &[app_lib::diff::DiffEntry]| entries.iter().map(|e| e.path.clone()).collect::<Vec<_>>();
+    assert_eq!(paths(&diff.added), vec!["DATA/FINAL/Champions/Aurora.wad.client"]);
+    assert_eq!(paths(&diff.removed), vec!["DATA/FINAL/Maps/Map11.wad.client"]);
+    assert_eq!(paths(&diff.changed), vec!["DATA/FINAL/Champions/Ahri.wad.client"]);
+    assert_eq!(diff.unchanged, 2);
+}
+
+#[tokio::test]
+async fn interrupted_download_never_leaves_truncated_files() {
+    let cdn = MockCdn::start().await.unwrap();
+    let release = MockRelease::new("EUW1", "2024-08-28", vec![MockFile::new("big.bin", sample_data(8, 300_000))]);
+    let published = cdn.publish(&release).await;
+    let output = tempfile::tempdir().unwrap();
+    let manifest = downloader::fetch_manifest(&published.url).await.unwrap();
+    let final_path = output.path().join("big.bin");
+    let partial = downloader::partial_path(&final_path);
+
+    // Interruption en cours de route : seul le fichier temporaire existe
+    let slow = DownloadOptions {
+        speed_limit: 128 * 1024,
+        workers: 1,
+        ..options(&cdn, "en_us")
+    };
+    let control = Arc::new(DownloadControl::default());
+    let stopper = control.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(400)).await;
+        stopper.cancel();
+    });
+    let summary = downloader::download_manifest(&manifest, output.path(), &slow, control.clone())
+        .await
+        .unwrap();
+    assert!(summary.interrupted);
+    assert!(summary.bytes_written > 0 && summary.bytes_written < 300_000);
+    assert!(!final_path.exists());
+    assert_eq!(std::fs::metadata(&partial).unwrap().len(), 300_000);
+
+    // La reprise réutilise les chunks déjà écrits puis renomme le fichier
+    let control = Arc::new(DownloadControl::default());
+    downloader::download_manifest(&manifest, output.path(), &options(&cdn, "en_us"), control.clone())
+        .await
+        .unwrap();
+    assert!(control.total_bytes() < 300_000);
+    assert_eq!(std::fs::read(&final_path).unwrap(), release.files[0].data);
+    assert!(!partial.exists());
+}
@@ -0,0 +1,81 @@
+// Modèle de dossier de sortie et détection des collisions entre tâches
+use app_lib::output_template::{expand_template, find_collision, TemplateValues, DEFAULT_OUTPUT_TEMPLATE};
+use app_lib::settings::AppSettings;
+use std::path::{Path, PathBuf};
+
+fn values() -> TemplateValues {
+    TemplateValues {
+        root: PathBuf::from("/data/patches"),
+        product: "lol".to_string(),
+        channel: "live".to_string(),
+        realm: "EUW1".to_string(),
+        version: "Patch 2024-08-28".to_string(),
+        manifest_hash: "93A211A9D0F05050".to_string(),
+        date: "2024-08-28".to_string(),
+        language: "fr_FR".to_string(),
+        content: String::new(),
+    }
+}
+
+#[test]
+fn default_template_separates_patches() {
+    let path = expand_template(DEFAULT_OUTPUT_TEMPLATE, &values()).unwrap();
+    assert_eq!(path, Path::new("/data/patches/EUW1/Patch 2024-08-28/93A211A9D0F05050"));
+
+    let other = TemplateValues {
+        manifest_hash: "0123456789ABCDEF".to_string(),
+        ..values()
+    };
+    assert_ne!(expand_template(DEFAULT_OUTPUT_TEMPLATE, &other).unwrap(), path);
+
+    let by_product = expand_template("{root}/{product}/{channel}/{realm}", &values()).unwrap();
+    assert_eq!(by_product, Path::new("/data/patches/lol/live/EUW1"));
+}
+
+#[test]
+fn template_values_cannot_escape_the_root() {
+    let hostile = TemplateValues {
+        realm: "../..".to_string(),
+        version: "14.17/../x:y".to_string(),
+        ..values()
+    };
+    let path = expand_template("{root}/{realm}/{version}_{language}/{content}", &hostile).unwrap();
+    assert_eq!(path, Path::new("/data/patches/.._/14.17_.._x_y_fr_FR/unknown"));
+    assert!(path.starts_with("/data/patches"));
+}
+
+#[test]
+fn invalid_templates_are_rejected() {
+    assert!(expand_template("{root}/{patch}", &values()).unwrap_err().contains("{patch}"));
+    assert!(expand_template("{root}/{realm", &values()).is_err());
+    assert!(expand_template("{realm}/{root}", &values()).is_err());
+    assert!(expand_template("  ", &values()).is_err());
+
+    // Le modèle doit partir du dossier de téléchargement
+    for template in ["/data/{realm}", "C:\\patches\\{realm}", "{realm}/{version}", "patches/{root}/{realm}"] {
+        assert!(expand_template(template, &values()).unwrap_err().contains("{root}"), "{}", template);
+    }
+    assert!(expand_template("{root}/{realm}/{root}", &values()).is_err());
+    let path = expand_template(" {root}\\{realm}\\ ", &values()).unwrap();
+    assert_eq!(path, Path::new("/data/patches/EUW1"));
+
+    let settings = AppSettings {
+        output_template: "{root}/{unknown}".to_string(),
+        ..AppSettings::default()
+    };
+    assert!(settings.validate().is_err());
+    assert!(AppSettings::default().validate().is_ok());
+}
+
+#[test]
+fn collisions_are_detected_between_active_tasks() {
+    let a = PathBuf::from("/data/patches/EUW1/Patch 2024-08-28/93A211A9D0F05050");
+    let b = PathBuf::from("/data/patches/EUW1/Patch 2024-09-11/0123456789ABCDEF");
+    let active = [("task_1", a.as_path()), ("task_2", b.as_path())];
+
+    assert_eq!(find_collision(active, Path::new("/data/patches/EUW1/./Patch 2024-08-28/93A211A9D0F05050")), Some("task_1"));
+    // Un dossier parent ou enfant d'une tâche active se chevauche aussi
+    assert_eq!(find_collision(active, Path::new("/data/patches/EUW1")), Some("task_1"));
+    assert_eq!(find_collision(active, &b.join("DATA")), Some("task_2"));
+    assert_eq!(find_collision(active, Path::new("/data/patches/NA1/Patch 2024-08-28/93A211A9D0F05050")), None);
+}
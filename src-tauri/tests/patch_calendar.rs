@@ -0,0 +1,94 @@
+// Calendrier des patchs : numérotation, micro-patchs et versions des builds
+use app_lib::patch_calendar::{self, PatchCalendar, PatchVersion};
+
+fn version(text: &str) -> PatchVersion {
+    text.parse().unwrap()
+}
+
+#[test]
+fn parses_orders_and_formats_versions() {
+    let season = version("25.S1.3");
+    assert_eq!(
+        season,
+        PatchVersion {
+            year: 25,
+            season: Some(1),
+            patch: 3,
+            hotfix: 0
+        }
+    );
+    assert_eq!(season.to_string(), "25.S1.3");
+    assert_eq!(version("24.17.2").to_string(), "24.17.2");
+    assert!(version("24.17.2").is_hotfix());
+    assert_eq!(version("24.17.2").base(), version("24.17"));
+    for invalid in ["", "24", "24.x", "25.S1", "24.17.1.1"] {
+        assert!(invalid.parse::<PatchVersion>().is_err(), "{}", invalid);
+    }
+
+    let mut versions: Vec<PatchVersion> = ["25.4", "24.24", "25.S1.3", "24.17.1", "24.17", "25.S1.1"]
+        .iter()
+        .map(|v| version(v))
+        .collect();
+    versions.sort();
+    let sorted: Vec<String> = versions.iter().map(ToString::to_string).collect();
+    assert_eq!(sorted, vec!["24.17", "24.17.1", "24.24", "25.S1.1", "25.S1.3", "25.4"]);
+
+    assert_eq!(serde_json::to_string(&season).unwrap(), "\"25.S1.3\"");
+    assert_eq!(serde_json::from_str::<PatchVersion>("\"24.17.1\"").unwrap(), version("24.17.1"));
+}
+
+#[test]
+fn labels_releases_and_hotfixes_from_dates() {
+    let calendar = PatchCalendar::default();
+    let date = |text| patch_calendar::parse_date(text).unwrap();
+    assert_eq!(calendar.version_for_date(date("2024-08-27")), Some(version("24.17")));
+    assert_eq!(calendar.version_for_date(date("2025-02-06 10:12:00")), Some(version("25.S1.3")));
+    assert_eq!(calendar.version_for_date(date("2024-09-04")), Some(version("24.17.1")));
+    assert_eq!(calendar.version_for_date(date("2021-06-01")), None);
+    // Après le dernier patch connu (25.24) : cadence de deux semaines, numérotation reprise chaque année
+    assert_eq!(calendar.version_for_date(date("2025-12-24")), Some(version("25.24.1")));
+    assert_eq!(calendar.version_for_date(date("2026-01-08")), Some(version("26.1")));
+    assert_eq!(calendar.version_for_date(date("2026-10-14")), Some(version("26.21")));
+    assert_eq!(calendar.version_for_date(date("2026-10-19")), Some(version("26.21.1")));
+    // Trop loin du dernier patch connu : calendrier à compléter
+    assert_eq!(calendar.version_for_date(date("2030-01-01")), None);
+
+    let labels = calendar.label_versions(&["2024-09-06", "2024-08-28", "2024-09-03", "pas une date", "2024-09-03"]);
+    let labels: Vec<Option<String>> = labels.iter().map(|v| v.map(|v| v.to_string())).collect();
+    assert_eq!(
+        labels,
+        vec![
+            Some("24.17.2".to_string()),
+            Some("24.17".to_string()),
+            Some("24.17.1".to_string()),
+            None,
+            Some("24.17.1".to_string())
+        ]
+    );
+}
+
+#[test]
+fn maps_builds_and_loads_edited_calendar() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(patch_calendar::CALENDAR_FILE_NAME);
+    std::fs::write(
+        &path,
+        r#"{"windowDays": 1, "patches": [
+            {"version": "26.S2.1", "date": "2026-05-06"},
+            {"version": "26.S1.1", "date": "2026-01-08"}
+        ]}"#,
+    )
+    .unwrap();
+    let calendar = PatchCalendar::load(&path).unwrap();
+    assert_eq!(calendar.patches[0].version, version("26.S1.1"));
+    assert_eq!(calendar.version_for_date(patch_calendar::parse_date("2026-01-10").unwrap()), Some(version("26.S1.1.1")));
+
+    let builtin = PatchCalendar::default();
+    assert_eq!(builtin.from_build("14.17.612.7771"), Some(version("24.17")));
+    assert_eq!(builtin.from_build("15.3.650.1234"), Some(version("25.S1.3")));
+    assert_eq!(builtin.from_build("16.2.1.1"), Some(version("26.2")));
+    assert_eq!(builtin.from_build("inconnue"), None);
+
+    std::fs::write(&path, r#"{"windowDays": 1, "patches": [{"version": "26", "date": "2026-01-08"}]}"#).unwrap();
+    assert!(PatchCalendar::load(&path).is_err());
+}
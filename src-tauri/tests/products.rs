@@ -0,0 +1,54 @@
+// Catalogue multi-produits : jeu, client LoL, TFT et Riot Client
+use app_lib::catalogue;
+use app_lib::mock_cdn::{sample_data, MockCdn, MockFile, MockRelease};
+use app_lib::products::{Channel, Product, PRODUCTS};
+
+#[test]
+fn parses_products_and_channels_from_catalogue() {
+    let csv = "product,realm,manifest,date,unused,size\n\
+               lol,EUW1,https://cdn/A.manifest,2024-08-28,,10\n\
+               lcu,PBE1,https://cdn/B.manifest,2024-08-29,,20\n\
+               riot-client,KeystoneFoundationLiveWin,https://cdn/C.manifest,2024-08-30,,30\n\
+               TFT,LIVESTAGING,https://cdn/D.manifest,2024-08-30,,40\n\
+               valorant,live,https://cdn/E.manifest,2024-08-30,,50\n";
+    let entries = catalogue::parse_catalogue(csv);
+    let products: Vec<(Product, Channel)> = entries.iter().map(|e| (e.product, e.channel)).collect();
+    assert_eq!(
+        products,
+        vec![
+            (Product::Lol, Channel::Live),
+            (Product::LolClient, Channel::Pbe),
+            (Product::RiotClient, Channel::Live),
+            (Product::Tft, Channel::Staging),
+        ]
+    );
+    assert_eq!(Channel::from_realm("LOLTMNT02"), Channel::Tournament);
+
+    for product in PRODUCTS {
+        assert_eq!(product.id().parse::<Product>().unwrap(), *product);
+        assert!(product.cdn_url().ends_with("/channels/public"));
+    }
+    assert_eq!(Product::LolClient.cdn_url(), Product::Lol.cdn_url());
+    assert_eq!(Product::RiotClient.default_language(), Some("none"));
+    assert!(!Product::RiotClient.uses_patch_calendar());
+    assert_eq!(serde_json::to_string(&Product::RiotClient).unwrap(), "\"riot_client\"");
+    assert!("valorant".parse::<Product>().is_err());
+}
+
+#[tokio::test]
+async fn mock_catalogue_publishes_other_products() {
+    let cdn = MockCdn::start().await.unwrap();
+    cdn.publish(&MockRelease::new("EUW1", "2024-08-28", vec![MockFile::new("a.bin", sample_data(1, 10))]))
+        .await;
+    let client = cdn
+        .publish(
+            &MockRelease::new("KeystoneFoundationLiveWin", "2024-08-29", vec![MockFile::new("RiotClientServices.exe", sample_data(2, 10))])
+                .with_product(Product::RiotClient),
+        )
+        .await;
+
+    let entries = catalogue::fetch_catalogue(&cdn.catalogue_url()).await.unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].product, Product::RiotClient);
+    assert_eq!(entries[1].manifest_url, client.url);
+}
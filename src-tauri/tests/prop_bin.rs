@@ -0,0 +1,207 @@
+// Conversion des fichiers de propriétés .bin en JSON
+use app_lib::hashes::HashTable;
+use app_lib::prop_bin::{fnv1a, BinType, BinValue, PropBin, MAX_VALUE_DEPTH};
+use common::Writer;
+use serde_json::json;
+
+mod common;
+
+fn champion_entry(w: &mut Writer) {
+    w.u32(fnv1a("Characters/Ahri/CharacterRecords/Root")).u16(22);
+    w.field("mCharacterName", BinType::String).string("Ahri");
+    w.field("isEnabled", BinType::Bool).u8(1);
+    w.field("i8", BinType::I8).u8(0xFF);
+    w.field("u8", BinType::U8).u8(200);
+    w.field("i16", BinType::I16).u16(0xFFFE);
+    w.field("u16", BinType::U16).u16(60000);
+    w.field("baseHP", BinType::I32).u32(-5i32 as u32);
+    w.field("u32", BinType::U32).u32(4_000_000_000);
+    w.field("i64", BinType::I64).u64(-7i64 as u64);
+    w.field("u64", BinType::U64).u64(1 << 40);
+    w.field("attackRange", BinType::F32).f32(550.0);
+    w.field("v2", BinType::Vec2).f32(1.0).f32(2.0);
+    w.field("v3", BinType::Vec3).f32(1.0).f32(2.0).f32(3.0);
+    w.field("v4", BinType::Vec4).f32(1.0).f32(2.0).f32(3.0).f32(4.0);
+    w.field("transform", BinType::Mtx44);
+    for i in 0..16 {
+        w.f32(i as f32);
+    }
+    w.field("color", BinType::Rgba).u8(255).u8(128).u8(0).u8(255);
+    w.field("spellName", BinType::Hash).u32(fnv1a("AhriOrbofDeception"));
+    w.field("icon", BinType::File).u64(0x0123_4567_89AB_CDEF);
+    w.field("spells", BinType::List).u8(BinType::Link as u8).sized(|w| {
+        w.u32(2).u32(fnv1a("Characters/Ahri/Spells/AhriQ")).u32(0xDEAD_BEEF);
+    });
+    w.field("stats", BinType::Pointer).u32(fnv1a("CharacterStats")).sized(|w| {
+        w.u16(2);
+        w.field("armor", BinType::F32).f32(21.0);
+        w.field("tags", BinType::List2).u8(BinType::String as u8).sized(|w| {
+            w.u32(2).string("mage").string("assassin");
+        });
+    });
+    w.field("empty", BinType::Embed).u32(0);
+    w.field("maps", BinType::Map).u8(BinType::U32 as u8).u8(BinType::Option as u8).sized(|w| {
+        w.u32(2);
+        w.u32(1).u8(BinType::String as u8).u8(1).string("one");
+        w.u32(2).u8(BinType::String as u8).u8(0);
+    });
+}
+
+fn prop_file(patch: bool) -> Vec<u8> {
+    let mut w = Writer(Vec::new());
+    if patch {
+        w.0.extend_from_slice(b"PTCH");
+        w.u64(1);
+    }
+    w.0.extend_from_slice(b"PROP");
+    w.u32(3).u32(1).string("DATA/Characters/Ahri/Ahri.bin");
+    w.u32(1).u32(fnv1a("CharacterRecord"));
+    w.sized(champion_entry);
+    if patch {
+        w.u32(1).u32(fnv1a("Characters/Ahri/CharacterRecords/Root")).sized(|w| {
+            w.u8(BinType::F32 as u8).string("stats.armor").f32(25.0);
+        });
+    }
+    w.0
+}
+
+#[test]
+fn parses_every_field_type() {
+    let bin = PropBin::parse(&prop_file(false)).unwrap();
+    assert!(!bin.is_patch);
+    assert_eq!(bin.version, 3);
+    assert_eq!(bin.linked, vec!["DATA/Characters/Ahri/Ahri.bin"]);
+    let entry = &bin.entries[0];
+    assert_eq!(entry.class, fnv1a("CharacterRecord"));
+    assert_eq!(entry.fields.len(), 22);
+    assert_eq!(entry.fields[2].value, BinValue::I8(-1));
+    assert_eq!(entry.fields[8].value, BinValue::I64(-7));
+
+    let json = bin.to_json(&HashTable::default());
+    let root = &json["entries"][format!("{{{:08x}}}", fnv1a("Characters/Ahri/CharacterRecords/Root"))];
+    assert_eq!(root["__type"], format!("{{{:08x}}}", fnv1a("CharacterRecord")));
+    assert_eq!(root[format!("{{{:08x}}}", fnv1a("baseHP"))], json!(-5));
+    assert_eq!(root[format!("{{{:08x}}}", fnv1a("icon"))], json!("{0123456789abcdef}"));
+}
+
+#[test]
+fn resolves_names_through_the_hash_table() {
+    let names = [
+        "Characters/Ahri/CharacterRecords/Root",
+        "CharacterRecord",
+        "CharacterStats",
+        "mCharacterName",
+        "attackRange",
+        "v3",
+        "transform",
+        "color",
+        "spellName",
+        "AhriOrbofDeception",
+        "spells",
+        "Characters/Ahri/Spells/AhriQ",
+        "stats",
+        "armor",
+        "tags",
+        "empty",
+        "maps",
+        "isEnabled",
+        "icon",
+    ];
+    let mut list: String = names.iter().map(|n| format!("{:08x} {}\n", fnv1a(n), n)).collect();
+    list.push_str("0123456789abcdef assets/characters/ahri/hud/ahri_square.dds\n");
+    let table = HashTable::parse(&list);
+
+    let json = PropBin::parse(&prop_file(false)).unwrap().to_json(&table);
+    let root = &json["entries"]["Characters/Ahri/CharacterRecords/Root"];
+    assert_eq!(root["__type"], "CharacterRecord");
+    assert_eq!(root["mCharacterName"], "Ahri");
+    assert_eq!(root["isEnabled"], true);
+    assert_eq!(root["attackRange"], 550.0);
+    assert_eq!(root["v3"], json!([1.0, 2.0, 3.0]));
+    assert_eq!(root["transform"].as_array().unwrap().len(), 16);
+    assert_eq!(root["color"], json!([255, 128, 0, 255]));
+    assert_eq!(root["spellName"], "AhriOrbofDeception");
+    assert_eq!(root["icon"], "assets/characters/ahri/hud/ahri_square.dds");
+    assert_eq!(root["spells"], json!(["Characters/Ahri/Spells/AhriQ", "{deadbeef}"]));
+    assert_eq!(root["stats"], json!({"__type": "CharacterStats", "armor": 21.0, "tags": ["mage", "assassin"]}));
+    assert_eq!(root["empty"], serde_json::Value::Null);
+    assert_eq!(root["maps"], json!({"1": "one", "2": null}));
+}
+
+#[test]
+fn parses_patch_files() {
+    let bin = PropBin::parse(&prop_file(true)).unwrap();
+    assert!(bin.is_patch);
+    assert_eq!(bin.patches.len(), 1);
+    assert_eq!(bin.patches[0].path, "stats.armor");
+    let json = bin.to_json(&HashTable::default());
+    assert_eq!(json["type"], "PTCH");
+    assert_eq!(json["patches"][0]["value"], 25.0);
+}
+
+#[test]
+fn rejects_truncated_and_foreign_files() {
+    let data = prop_file(false);
+    assert!(PropBin::parse(&data[..data.len() - 3]).unwrap_err().contains("tronqué"));
+    assert!(PropBin::parse(b"RW\x03\x04").is_err());
+}
+
+#[test]
+fn rejects_oversized_counts_and_deep_nesting() {
+    // Des milliards d'entrées annoncées dans un fichier de quelques octets
+    let mut w = Writer(Vec::new());
+    w.0.extend_from_slice(b"PROP");
+    w.u32(1).u32(u32::MAX).u32(fnv1a("CharacterRecord"));
+    assert!(PropBin::parse(&w.0).unwrap_err().contains("tronqué"));
+
+    let mut w = Writer(Vec::new());
+    w.0.extend_from_slice(b"PROP");
+    w.u32(1).u32(1).u32(fnv1a("CharacterRecord"));
+    w.sized(|w| {
+        w.u32(fnv1a("Root")).u16(1);
+        w.field("tags", BinType::List).u8(BinType::String as u8).u32(0).u32(u32::MAX);
+    });
+    assert!(PropBin::parse(&w.0).unwrap_err().contains("tronqué"));
+
+    // Options imbriquées au-delà de la limite
+    let mut w = Writer(Vec::new());
+    w.0.extend_from_slice(b"PROP");
+    w.u32(1).u32(1).u32(fnv1a("CharacterRecord"));
+    w.sized(|w| {
+        w.u32(fnv1a("Root")).u16(1);
+        w.field("nested", BinType::Option);
+        for _ in 0..MAX_VALUE_DEPTH + 1 {
+            w.u8(BinType::Option as u8).u8(1);
+        }
+        w.u8(BinType::Option as u8).u8(0);
+    });
+    assert!(PropBin::parse(&w.0).unwrap_err().contains("imbrication"));
+}
+
+#[test]
+fn cli_converts_a_bin_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("ahri.bin");
+    let output = dir.path().join("ahri.json");
+    let hashes = dir.path().join("hashes.binfields.txt");
+    std::fs::write(&input, prop_file(false)).unwrap();
+    std::fs::write(&hashes, format!("{:08x} mCharacterName\n", fnv1a("mCharacterName"))).unwrap();
+
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_bin2json"))
+        .arg(&input)
+        .arg(&output)
+        .arg("--hashes")
+        .arg(&hashes)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+    let root = json["entries"].as_object().unwrap().values().next().unwrap();
+    assert_eq!(root["mCharacterName"], "Ahri");
+
+    let failed = std::process::Command::new(env!("CARGO_BIN_EXE_bin2json"))
+        .arg(dir.path().join("missing.bin"))
+        .output()
+        .unwrap();
+    assert!(!failed.status.success());
+}
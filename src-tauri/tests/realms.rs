@@ -0,0 +1,70 @@
+// Registre des serveurs : alias, types de serveur, régions et serveur préféré
+use app_lib::catalogue;
+use app_lib::products::Channel;
+use app_lib::realms::{self, PlatformRegion, Realm};
+
+#[test]
+fn parses_known_realms_aliases_and_special_families() {
+    let euw = Realm::parse(" euw ");
+    assert_eq!((euw.id.as_str(), euw.kind, euw.region, euw.known), ("EUW1", Channel::Live, PlatformRegion::Europe, true));
+    assert_eq!(Realm::parse("NA").id, "NA1");
+    assert_eq!(Realm::parse("pbe").id, "PBE1");
+    assert_eq!(Realm::parse("PBE1").kind, Channel::Pbe);
+
+    let staging = Realm::parse("LIVESTAGING");
+    assert_eq!((staging.kind, staging.region, staging.known), (Channel::Staging, PlatformRegion::Unknown, false));
+    assert_eq!(Realm::parse("LOLTMNT02").kind, Channel::Tournament);
+    assert_eq!(Realm::parse("PBE2").kind, Channel::Pbe);
+    // Serveur inconnu : nom du catalogue conservé tel quel
+    let other = Realm::parse("KeystoneFoundationLiveWin");
+    assert_eq!((other.id.as_str(), other.kind), ("KeystoneFoundationLiveWin", Channel::Live));
+
+    assert!(realms::same_realm("EUW", "euw1"));
+    assert!(realms::same_realm("loltmnt02", "LOLTMNT02"));
+    assert!(!realms::same_realm("EUW1", "EUN1"));
+}
+
+#[test]
+fn catalogue_uses_canonical_realm_ids() {
+    let csv = "product,realm,manifest,date,unused,size\n\
+               lol,EUW,https://cdn/A.manifest,2024-08-28,,10\n\
+               lol,PBE,https://cdn/B.manifest,2024-08-29,,20\n";
+    let realms: Vec<(String, Channel)> = catalogue::parse_catalogue(csv)
+        .into_iter()
+        .map(|e| (e.realm, e.channel))
+        .collect();
+    assert_eq!(realms, vec![("EUW1".to_string(), Channel::Live), ("PBE1".to_string(), Channel::Pbe)]);
+}
+
+#[test]
+fn groups_realms_by_region_live_first() {
+    let available: Vec<Realm> = ["PBE1", "NA1", "BR1", "EUW", "EUW1", "KR", "LOLTMNT02"]
+        .iter()
+        .map(|r| Realm::parse(r))
+        .collect();
+    let groups = realms::group_by_region(&available);
+    let ids = |region: PlatformRegion| -> Vec<String> { groups[&region].iter().map(|r| r.id.clone()).collect() };
+    assert_eq!(
+        groups.keys().copied().collect::<Vec<_>>(),
+        vec![PlatformRegion::Americas, PlatformRegion::Europe, PlatformRegion::Asia, PlatformRegion::Unknown]
+    );
+    assert_eq!(ids(PlatformRegion::Americas), vec!["BR1", "NA1", "PBE1"]);
+    assert_eq!(ids(PlatformRegion::Europe), vec!["EUW1"]);
+    assert_eq!(ids(PlatformRegion::Unknown), vec!["LOLTMNT02"]);
+
+    assert_eq!(realms::registry().iter().filter(|r| !r.is_live()).count(), 1);
+}
+
+#[test]
+fn picks_preferred_realm_with_fallbacks() {
+    let parse = |ids: &[&str]| -> Vec<Realm> { ids.iter().map(|r| Realm::parse(r)).collect() };
+    let pick = |ids: &[&str], preferred: &str| realms::pick_preferred(&parse(ids), preferred).map(|r| r.id);
+
+    assert_eq!(pick(&["NA1", "EUW1", "PBE1"], "euw"), Some("EUW1".to_string()));
+    // Même région à défaut du serveur demandé
+    assert_eq!(pick(&["NA1", "EUN1", "PBE1"], "EUW1"), Some("EUN1".to_string()));
+    // Sinon le premier serveur live, puis n'importe lequel
+    assert_eq!(pick(&["PBE1", "KR"], "EUW1"), Some("KR".to_string()));
+    assert_eq!(pick(&["PBE1"], "EUW1"), Some("PBE1".to_string()));
+    assert_eq!(pick(&[], "EUW1"), None);
+}
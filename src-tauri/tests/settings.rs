@@ -0,0 +1,55 @@
+// Persistance et validation des paramètres
+use app_lib::settings::{AppSettings, SettingsStore, SETTINGS_VERSION};
+
+#[tokio::test]
+async fn settings_are_saved_with_their_version_and_reloaded() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config").join("settings.json");
+
+    let store = SettingsStore::load(path.clone());
+    assert_eq!(store.get().await, AppSettings::default());
+
+    let settings = AppSettings {
+        language: "fr_fr".to_string(),
+        max_concurrent_downloads: 2,
+        download_speed: 512,
+        ..AppSettings::default()
+    };
+    store.save(settings.clone()).await.unwrap();
+
+    let file: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(file["version"], SETTINGS_VERSION);
+    assert_eq!(file["settings"]["maxConcurrentDownloads"], 2);
+    assert_eq!(SettingsStore::load(path).get().await, settings);
+}
+
+#[tokio::test]
+async fn invalid_settings_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SettingsStore::load(dir.path().join("settings.json"));
+
+    for settings in [
+        AppSettings { max_concurrent_downloads: 0, ..AppSettings::default() },
+        AppSettings { language: "français".to_string(), ..AppSettings::default() },
+        AppSettings { theme: "blue".to_string(), ..AppSettings::default() },
+    ] {
+        assert!(store.save(settings).await.is_err());
+    }
+    assert!(!dir.path().join("settings.json").exists());
+}
+
+#[tokio::test]
+async fn unversioned_and_corrupted_files_are_handled() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("settings.json");
+
+    std::fs::write(&path, r#"{ "language": "ko_kr", "autoStart": true }"#).unwrap();
+    let settings = SettingsStore::load(path.clone()).get().await;
+    assert_eq!(settings.language, "ko_kr");
+    assert!(settings.auto_start);
+    assert_eq!(settings.max_concurrent_downloads, AppSettings::default().max_concurrent_downloads);
+
+    std::fs::write(&path, "{ pas du json").unwrap();
+    assert_eq!(SettingsStore::load(path.clone()).get().await, AppSettings::default());
+    assert!(path.with_extension("json.bak").exists());
+}
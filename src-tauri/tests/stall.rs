@@ -0,0 +1,128 @@
+// Détection des blocages : fenêtre glissante, seuils et compteur d'octets reçus
+use app_lib::downloader::{self, DownloadControl, DownloadOptions};
+use app_lib::mock_cdn::{sample_data, MockCdn, MockFile, MockRelease};
+use app_lib::stall::{StallDetector, StallSettings};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn settings(window_seconds: u64, min_bytes: u64) -> StallSettings {
+    StallSettings {
+        window_seconds,
+        min_bytes,
+        ..StallSettings::default()
+    }
+}
+
+#[test]
+fn detects_stalls_over_a_sliding_window() {
+    let start = Instant::now();
+    let at = |seconds: u64| start + Duration::from_secs(seconds);
+    let mut detector = StallDetector::new(&settings(10, 1000), 0);
+
+    // Fenêtre pas encore couverte : jamais bloqué
+    assert!(!detector.observe(at(0), 0));
+    assert!(!detector.observe(at(5), 0));
+    // 10 s sans rien recevoir
+    assert!(detector.observe(at(10), 0));
+
+    detector.reset();
+    let mut received = 0;
+    for second in 0..=30 {
+        // Débit régulier de 200 octets/s : 2000 octets par fenêtre
+        received += 200;
+        assert!(!detector.observe(at(second), received), "seconde {}", second);
+    }
+    // Le débit tombe à 10 octets/s : bloqué dès que les 10 dernières secondes passent sous le seuil
+    for second in 31..=35 {
+        assert!(!detector.observe(at(second), received + (second - 30) * 10), "seconde {}", second);
+    }
+    assert!(detector.observe(at(36), received + 60));
+}
+
+#[test]
+fn thresholds_follow_settings() {
+    let start = Instant::now();
+    let disabled = StallSettings {
+        enabled: false,
+        ..settings(10, 1000)
+    };
+    let mut detector = StallDetector::new(&disabled, 0);
+    assert!(!detector.observe(start, 0));
+    assert!(!detector.observe(start + Duration::from_secs(60), 0));
+
+    // Limite de 50 octets/s : 250 octets par fenêtre de 10 s ne sont pas un blocage
+    let mut detector = StallDetector::new(&settings(10, 1000), 50);
+    assert!(!detector.observe(start, 0));
+    assert!(!detector.observe(start + Duration::from_secs(10), 250));
+    assert!(detector.observe(start + Duration::from_secs(20), 260));
+
+    assert!(StallSettings::default().validate().is_ok());
+    assert!(settings(5, 1000).validate().is_err());
+    assert!(StallSettings {
+        max_restarts: 100,
+        ..StallSettings::default()
+    }
+    .validate()
+    .is_err());
+}
+
+#[tokio::test]
+async fn counts_bytes_received_across_attempts() {
+    let cdn = MockCdn::start().await.unwrap();
+    let release = MockRelease::new("EUW1", "2024-08-28", vec![MockFile::new("a.bin", sample_data(1, 50_000))]);
+    let published = cdn.publish(&release).await;
+    let manifest = downloader::fetch_manifest(&published.url).await.unwrap();
+    let options = DownloadOptions {
+        cdn_url: cdn.cdn_url(),
+        ..DownloadOptions::default()
+    };
+
+    let output = tempfile::tempdir().unwrap();
+    let control = Arc::new(DownloadControl::default());
+    downloader::download_manifest(&manifest, output.path(), &options, control.clone())
+        .await
+        .unwrap();
+    let received = control.received_bytes();
+    assert!(received > 0);
+    assert!(!control.is_preparing());
+
+    // Deuxième tentative sur un autre dossier : le compteur continue au lieu de repartir de zéro
+    let other = tempfile::tempdir().unwrap();
+    downloader::download_manifest(&manifest, other.path(), &options, control.clone())
+        .await
+        .unwrap();
+    assert_eq!(control.received_bytes(), received * 2);
+}
+
+#[tokio::test]
+async fn resumed_download_skips_verified_files_before_transferring() {
+    let cdn = MockCdn::start().await.unwrap();
+    let release = MockRelease::new(
+        "EUW1",
+        "2024-08-28",
+        vec![
+            MockFile::new("a.bin", sample_data(1, 200_000)),
+            MockFile::new("b.bin", sample_data(2, 20_000)),
+        ],
+    );
+    let published = cdn.publish(&release).await;
+    let manifest = downloader::fetch_manifest(&published.url).await.unwrap();
+    let options = DownloadOptions {
+        cdn_url: cdn.cdn_url(),
+        ..DownloadOptions::default()
+    };
+    let output = tempfile::tempdir().unwrap();
+    downloader::download_manifest(&manifest, output.path(), &options, Arc::new(DownloadControl::default()))
+        .await
+        .unwrap();
+    std::fs::remove_file(output.path().join("b.bin")).unwrap();
+
+    // Reprise : la vérification de a.bin ne reçoit rien, seul b.bin est transféré
+    let control = Arc::new(DownloadControl::default());
+    let summary = downloader::download_manifest(&manifest, output.path(), &options, control.clone())
+        .await
+        .unwrap();
+    assert_eq!((summary.files, summary.skipped_files), (2, 1));
+    assert_eq!(control.total_bytes(), 20_000);
+    assert!(!control.is_preparing());
+}
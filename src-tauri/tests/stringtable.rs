@@ -0,0 +1,140 @@
+// Textes localisés : lecture des stringtables et différences par langue
+use app_lib::hashes::{self, HashTable};
+use app_lib::stringtable::{self, RewordedString, StringEntry};
+use app_lib::wad;
+use common::{build_wad, entry};
+use std::path::Path;
+
+mod common;
+
+fn build_rst(version: u8, strings: &[(u64, &str)]) -> Vec<u8> {
+    let bits = match version {
+        2 | 3 => 40,
+        4 => 39,
+        _ => 38,
+    };
+    let mut data = b"RST".to_vec();
+    data.push(version);
+    if version == 2 {
+        data.push(1);
+        data.extend_from_slice(&4u32.to_le_bytes());
+        data.extend_from_slice(b"font");
+    }
+    data.extend_from_slice(&(strings.len() as u32).to_le_bytes());
+    let mut text = Vec::new();
+    for (hash, value) in strings {
+        data.extend_from_slice(&(((text.len() as u64) << bits) | hash).to_le_bytes());
+        text.extend_from_slice(value.as_bytes());
+        text.push(0);
+    }
+    if version < 5 {
+        data.push(0);
+    }
+    data.extend_from_slice(&text);
+    data
+}
+
+fn write_patch(dir: &Path, language: &str, strings: &[(u64, &str)]) {
+    let localized = dir.join("DATA/FINAL/Localized");
+    std::fs::create_dir_all(&localized).unwrap();
+    let rst = build_rst(5, strings);
+    let wad = build_wad(&[
+        entry(&format!("data/menu/{}/main.stringtable", language.to_lowercase()), 0, rst.len(), rst),
+        entry("assets/sounds/vo.wpk", 0, 4, b"r3d2".to_vec()),
+    ]);
+    std::fs::write(localized.join(format!("Global.{}.wad.client", language)), wad).unwrap();
+}
+
+#[test]
+fn parses_every_rst_version_and_fontconfig() {
+    let table = HashTable::parse("00000000aa game_champion_name_ahri\n");
+    for version in [2, 3, 4, 5] {
+        let strings = stringtable::parse_rst(&build_rst(version, &[(0xAA, "Ahri"), (0xBB, "Renard à neuf queues")]), &table)
+            .unwrap();
+        assert_eq!(strings["game_champion_name_ahri"], "Ahri");
+        assert_eq!(strings["{00000000bb}"], "Renard à neuf queues");
+    }
+    assert!(stringtable::parse_rst(b"RST\x09\x00\x00\x00\x00", &table).is_err());
+
+    let fontconfig = "[FontConfig \"English\"]\ntr \"game_champion_name_ahri\" = \"Ahri\"\nbroken line\n";
+    let strings = stringtable::parse_fontconfig(fontconfig);
+    assert_eq!(strings.len(), 1);
+    assert_eq!(strings["game_champion_name_ahri"], "Ahri");
+
+    assert!(stringtable::is_string_table_name("data/menu/fr_fr/main.stringtable"));
+    assert!(stringtable::is_string_table_name("DATA/Menu/fontconfig_fr_FR.txt"));
+    assert!(!stringtable::is_string_table_name("data/menu/readme.txt"));
+    assert_eq!(wad::wad_locale("DATA/FINAL/Localized/Global.fr_FR.wad.client"), Some("fr_FR"));
+    assert_eq!(wad::wad_locale("DATA/FINAL/Champions/Ahri.wad.client"), None);
+}
+
+#[tokio::test]
+async fn rst_keys_resolve_through_the_community_key_lists() {
+    // Les listes donnent le hash complet : seule la clé est reprise, puis tronquée pour chaque version
+    let dir = tempfile::tempdir().unwrap();
+    let list = dir.path().join("hashes.rst.xxh3.txt");
+    std::fs::write(&list, "1f2e3d4c5b6a7980 game_champion_name_ahri\nbroken\n").unwrap();
+    assert!(hashes::is_rst_hash_list(&list.to_string_lossy()));
+    assert!(!hashes::is_rst_hash_list("https://raw.communitydragon.org/data/hashes/lol/hashes.game.txt.0"));
+    let table = HashTable::parse(&hashes::fetch_hash_list(&list.to_string_lossy()).await.unwrap());
+
+    let key = "game_champion_name_ahri";
+    let xxh64 = xxhash_rust::xxh64::xxh64(key.as_bytes(), 0);
+    let xxh3 = xxhash_rust::xxh3::xxh3_64(key.as_bytes());
+    for (version, hash) in [(2, xxh64 & 0xFF_FFFF_FFFF), (3, xxh64 & 0xFF_FFFF_FFFF), (4, xxh64 & 0x7F_FFFF_FFFF), (5, xxh3 & 0x3F_FFFF_FFFF)] {
+        assert_eq!(stringtable::rst_key_hash("Game_Champion_Name_Ahri", version).unwrap(), hash);
+        let strings = stringtable::parse_rst(&build_rst(version, &[(hash, "Ahri")]), &table).unwrap();
+        assert_eq!(strings[key], "Ahri", "version {}", version);
+    }
+}
+
+#[test]
+fn extracts_and_diffs_languages_between_patches() {
+    let dir = tempfile::tempdir().unwrap();
+    let (old, new) = (dir.path().join("14.1"), dir.path().join("14.2"));
+    write_patch(&old, "fr_FR", &[(1, "Ahri"), (2, "Orbe d'illusion"), (3, "Ancien objet")]);
+    write_patch(&old, "en_US", &[(1, "Ahri")]);
+    write_patch(&new, "fr_FR", &[(1, "Ahri"), (2, "Orbe de tromperie"), (4, "Nouvel objet")]);
+    write_patch(&new, "de_DE", &[(1, "Ahri")]);
+
+    // Sans table de hash : toutes les entrées sont lues et les tables reconnues à leur signature
+    let table = HashTable::default();
+    let scopes = vec!["DATA/FINAL/Localized/".to_string()];
+    let languages = stringtable::load_languages(&old, &scopes, &[], &table).unwrap();
+    assert_eq!(languages.keys().collect::<Vec<_>>(), vec!["en_US", "fr_FR"]);
+    assert_eq!(languages["fr_FR"]["{0000000002}"], "Orbe d'illusion");
+
+    let only_french = stringtable::load_languages(&old, &scopes, &["FR_fr".to_string()], &table).unwrap();
+    assert_eq!(only_french.len(), 1);
+
+    let output = dir.path().join("strings");
+    let summaries = stringtable::write_languages(&languages, &output).unwrap();
+    assert_eq!(summaries[1].language, "fr_FR");
+    assert_eq!(summaries[1].strings, 3);
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(output.join("fr_FR.json")).unwrap()).unwrap();
+    assert_eq!(json["{0000000001}"], "Ahri");
+
+    let new_languages = stringtable::load_languages(&new, &scopes, &[], &table).unwrap();
+    let diffs = stringtable::diff_languages(&languages, &new_languages);
+    assert_eq!(diffs.iter().map(|d| d.language.as_str()).collect::<Vec<_>>(), vec!["de_DE", "en_US", "fr_FR"]);
+    assert_eq!(diffs[0].added.len(), 1);
+    assert_eq!(diffs[1].removed.len(), 1);
+    let french = &diffs[2];
+    assert_eq!(french.unchanged, 1);
+    assert_eq!(
+        french.added,
+        vec![StringEntry {
+            key: "{0000000004}".to_string(),
+            text: "Nouvel objet".to_string()
+        }]
+    );
+    assert_eq!(french.removed[0].text, "Ancien objet");
+    assert_eq!(
+        french.reworded,
+        vec![RewordedString {
+            key: "{0000000002}".to_string(),
+            old: "Orbe d'illusion".to_string(),
+            new: "Orbe de tromperie".to_string()
+        }]
+    );
+}
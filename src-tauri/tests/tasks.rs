@@ -0,0 +1,163 @@
+// Tâches : identifiants uniques, cycle de vie, filtres et pagination de list_downloads
+use app_lib::tasks::{self, DownloadTask, TaskQuery, TaskStatus};
+use std::collections::HashSet;
+
+fn task(id: &str, status: TaskStatus, realm: Option<&str>, version: &str, start_time: &str) -> DownloadTask {
+    let mut task = DownloadTask::new(
+        id.to_string(),
+        format!("https://cdn/{}.manifest", id),
+        version.to_string(),
+        None,
+        realm.map(str::to_string),
+    );
+    task.status = status;
+    task.start_time = start_time.to_string();
+    task
+}
+
+fn sample() -> Vec<DownloadTask> {
+    vec![
+        task("a", TaskStatus::Completed, Some("EUW1"), "Patch 24.17", "2024-09-01T10:00:00+00:00"),
+        task("b", TaskStatus::Failed, Some("NA1"), "Patch 24.17", "2024-09-01T11:00:00+00:00"),
+        task("c", TaskStatus::Downloading, Some("EUW1"), "Patch 24.18", "2024-09-01T12:00:00+00:00"),
+        task("d", TaskStatus::Completed, Some("PBE1"), "Version ABCD", "2024-09-01T13:00:00+00:00"),
+        task("e", TaskStatus::Paused, None, "Unknown", "2024-09-01T14:00:00+00:00"),
+    ]
+}
+
+fn ids(query: TaskQuery) -> Vec<String> {
+    query.page(&sample()).tasks.into_iter().map(|t| t.id).collect()
+}
+
+#[test]
+fn generates_unique_sortable_ids() {
+    let generated: Vec<String> = (0..1000).map(|_| tasks::new_task_id("task")).collect();
+    assert_eq!(generated.iter().collect::<HashSet<_>>().len(), generated.len());
+    assert!(generated.iter().all(|id| id.starts_with("task_") && id.len() == "task_".len() + 26));
+    assert!(tasks::new_task_id("export").starts_with("export_"));
+}
+
+#[test]
+fn filters_by_status_realm_and_version() {
+    assert_eq!(ids(TaskQuery::default()), vec!["e", "d", "c", "b", "a"]);
+    assert_eq!(
+        ids(TaskQuery {
+            status: vec![TaskStatus::Completed, TaskStatus::Failed],
+            ..TaskQuery::default()
+        }),
+        vec!["d", "b", "a"]
+    );
+    // Les alias de serveur sont reconnus, les tâches sans serveur sont exclues
+    assert_eq!(
+        ids(TaskQuery {
+            realm: Some("euw".to_string()),
+            ..TaskQuery::default()
+        }),
+        vec!["c", "a"]
+    );
+    assert_eq!(
+        ids(TaskQuery {
+            version: Some("24.17".to_string()),
+            realm: Some("EUW1".to_string()),
+            ..TaskQuery::default()
+        }),
+        vec!["a"]
+    );
+    let active: Vec<bool> = sample().iter().map(|t| t.is_active()).collect();
+    assert_eq!(active, vec![false, false, true, false, true]);
+}
+
+#[test]
+fn paginates_matching_tasks() {
+    let query = TaskQuery {
+        offset: 1,
+        limit: Some(2),
+        ..TaskQuery::default()
+    };
+    let page = query.page(&sample());
+    assert_eq!(page.total, 5);
+    assert_eq!(page.offset, 1);
+    assert_eq!(page.tasks.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), vec!["d", "c"]);
+
+    let past_end = TaskQuery {
+        offset: 10,
+        ..TaskQuery::default()
+    };
+    let page = past_end.page(&sample());
+    assert_eq!((page.total, page.tasks.len()), (5, 0));
+}
+
+#[test]
+fn validates_transitions_and_records_history() {
+    let mut task = DownloadTask::new("task_1".to_string(), "https://cdn/A.manifest".to_string(), "Patch 24.17".to_string(), None, None);
+    assert_eq!(task.status, TaskStatus::Queued);
+    assert!(task.transition(TaskStatus::Completed, None).is_err());
+
+    task.transition(TaskStatus::Downloading, None).unwrap();
+    task.speed = "12.0 MB/s".to_string();
+    task.transition(TaskStatus::Paused, Some("Espace disque insuffisant".to_string())).unwrap();
+    assert_eq!((task.speed.as_str(), task.eta.as_str()), ("0 MB/s", "--"));
+    // Rester dans le même statut n'ajoute rien à l'historique
+    task.transition(TaskStatus::Paused, None).unwrap();
+    assert!(task.transition(TaskStatus::Downloading, None).is_err());
+    task.transition(TaskStatus::Queued, None).unwrap();
+    task.transition(TaskStatus::Downloading, None).unwrap();
+    task.transition(TaskStatus::Completed, None).unwrap();
+    assert!(task.end_time.is_some());
+    task.transition(TaskStatus::Verifying, None).unwrap();
+    assert!(task.end_time.is_none());
+    task.transition(TaskStatus::Failed, Some("1 fichier(s) manquant(s), 0 corrompu(s)".to_string())).unwrap();
+
+    let statuses: Vec<TaskStatus> = task.history.iter().map(|change| change.status).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            TaskStatus::Queued,
+            TaskStatus::Downloading,
+            TaskStatus::Paused,
+            TaskStatus::Queued,
+            TaskStatus::Downloading,
+            TaskStatus::Completed,
+            TaskStatus::Verifying,
+            TaskStatus::Failed,
+        ]
+    );
+    assert_eq!(task.history[2].reason.as_deref(), Some("Espace disque insuffisant"));
+    assert!(task.history.windows(2).all(|pair| pair[0].at <= pair[1].at));
+}
+
+#[test]
+fn cancelled_tasks_are_final() {
+    let mut task = DownloadTask::new("task_2".to_string(), String::new(), String::new(), None, None);
+    task.transition(TaskStatus::Cancelled, None).unwrap();
+    assert!(!task.is_active());
+    for next in [TaskStatus::Queued, TaskStatus::Downloading, TaskStatus::Verifying, TaskStatus::Failed] {
+        assert!(task.transition(next, None).is_err());
+    }
+    assert_eq!(task.history.len(), 2);
+}
+
+#[test]
+fn reads_legacy_status_names() {
+    let status: TaskStatus = serde_json::from_str("\"pending\"").unwrap();
+    assert_eq!(status, TaskStatus::Queued);
+    let status: TaskStatus = serde_json::from_str("\"error\"").unwrap();
+    assert_eq!(status, TaskStatus::Failed);
+    assert_eq!(serde_json::to_string(&TaskStatus::Cancelled).unwrap(), "\"cancelled\"");
+}
+
+#[test]
+fn tasks_saved_without_language_still_load() {
+    let json = r#"{"id":"task_3","manifest":"m","version":"v","status":"completed","progress":100.0,"speed":"0 MB/s","eta":"--","error":null,"start_time":"2024-08-28T00:00:00Z","end_time":null,"output_path":null}"#;
+    let task: DownloadTask = serde_json::from_str(json).unwrap();
+    assert_eq!((task.language.as_str(), task.content_filter.as_str()), ("", ""));
+
+    let task = DownloadTask {
+        language: "fr_fr".to_string(),
+        content_filter: "DATA/FINAL/Champions".to_string(),
+        ..task
+    };
+    let value = serde_json::to_value(&task).unwrap();
+    assert_eq!(value["language"], "fr_fr");
+    assert_eq!(value["content_filter"], "DATA/FINAL/Champions");
+}
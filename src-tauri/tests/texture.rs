@@ -0,0 +1,192 @@
+// Décodage des textures .tex/.dds et conversion en PNG
+use app_lib::texture::{self, Texture, TextureFormat};
+
+struct BitWriter {
+    bytes: [u8; 16],
+    position: usize,
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter {
+            bytes: [0; 16],
+            position: 0,
+        }
+    }
+
+    fn write(&mut self, value: u32, count: usize) -> &mut Self {
+        for i in 0..count {
+            if value >> i & 1 != 0 {
+                self.bytes[self.position / 8] |= 1 << (self.position % 8);
+            }
+            self.position += 1;
+        }
+        self
+    }
+}
+
+fn dds_header(width: u32, height: u32, mips: u32, flags: u32, fourcc: &[u8; 4], bits: u32, masks: [u32; 4]) -> Vec<u8> {
+    let mut header = vec![0u8; 128];
+    header[..4].copy_from_slice(b"DDS ");
+    header[4..8].copy_from_slice(&124u32.to_le_bytes());
+    header[12..16].copy_from_slice(&height.to_le_bytes());
+    header[16..20].copy_from_slice(&width.to_le_bytes());
+    header[28..32].copy_from_slice(&mips.to_le_bytes());
+    header[76..80].copy_from_slice(&32u32.to_le_bytes());
+    header[80..84].copy_from_slice(&flags.to_le_bytes());
+    header[84..88].copy_from_slice(fourcc);
+    header[88..92].copy_from_slice(&bits.to_le_bytes());
+    for (i, mask) in masks.iter().enumerate() {
+        header[92 + i * 4..96 + i * 4].copy_from_slice(&mask.to_le_bytes());
+    }
+    header
+}
+
+fn dx10(width: u32, height: u32, dxgi: u32, blocks: &[u8]) -> Vec<u8> {
+    let mut data = dds_header(width, height, 1, 0x4, b"DX10", 0, [0; 4]);
+    data.extend_from_slice(&dxgi.to_le_bytes());
+    data.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]);
+    data.extend_from_slice(blocks);
+    data
+}
+
+fn pixel(image: &texture::Image, x: u32, y: u32) -> [u8; 4] {
+    let offset = ((y * image.width + x) * 4) as usize;
+    image.rgba[offset..offset + 4].try_into().unwrap()
+}
+
+#[test]
+fn decodes_bc1_and_bc3_blocks() {
+    // Rouge pur partout sauf la dernière ligne en bleu
+    let mut bc1 = vec![0x00, 0xF8, 0x1F, 0x00];
+    bc1.extend_from_slice(&0x5500_0000u32.to_le_bytes());
+    let image = Texture::parse(&dds_header(4, 4, 1, 0x4, b"DXT1", 0, [0; 4]).into_iter().chain(bc1.clone()).collect::<Vec<_>>())
+        .unwrap()
+        .decode(0)
+        .unwrap();
+    assert_eq!(pixel(&image, 0, 0), [255, 0, 0, 255]);
+    assert_eq!(pixel(&image, 3, 3), [0, 0, 255, 255]);
+
+    // c0 <= c1 : l'index 3 est transparent en BC1
+    let transparent = [0x1F, 0x00, 0x00, 0xF8, 0xFF, 0xFF, 0xFF, 0xFF];
+    let image = Texture::parse(&dx10(4, 4, 71, &transparent)).unwrap().decode(0).unwrap();
+    assert_eq!(pixel(&image, 2, 1), [0, 0, 0, 0]);
+
+    // BC3 : alpha interpolé sur 8 niveaux, index 1 = a1 sur le premier pixel, 0 ailleurs
+    let mut bc3 = vec![200, 100, 1, 0, 0, 0, 0, 0];
+    bc3.extend_from_slice(&bc1);
+    let image = Texture::parse(&dx10(4, 4, 77, &bc3)).unwrap().decode(0).unwrap();
+    assert_eq!(pixel(&image, 0, 0), [255, 0, 0, 100]);
+    assert_eq!(pixel(&image, 1, 0), [255, 0, 0, 200]);
+}
+
+#[test]
+fn decodes_bc7_single_and_partitioned_blocks() {
+    // Mode 6 : un sous-ensemble, extrémités 7 bits + p-bit, index 4 bits
+    let mut mode6 = BitWriter::new();
+    mode6.write(1 << 6, 7);
+    for (e0, e1) in [(127, 0), (0, 127), (0, 0), (127, 127)] {
+        mode6.write(e0, 7).write(e1, 7);
+    }
+    mode6.write(1, 1).write(1, 1).write(0, 3);
+    for pixel in 1..16 {
+        mode6.write(if pixel == 15 { 15 } else { 0 }, 4);
+    }
+    let image = Texture::parse(&dx10(4, 4, 98, &mode6.bytes)).unwrap().decode(0).unwrap();
+    assert_eq!(pixel(&image, 0, 0), [255, 1, 1, 255]);
+    assert_eq!(pixel(&image, 3, 3), [1, 255, 1, 255]);
+
+    // Mode 1, partition 13 : moitié haute rouge, moitié basse bleue
+    let mut mode1 = BitWriter::new();
+    mode1.write(0b10, 2).write(13, 6);
+    for channel in 0..3 {
+        for subset in 0..2 {
+            let value = match (channel, subset) {
+                (0, 0) | (2, 1) => 63,
+                _ => 0,
+            };
+            mode1.write(value, 6).write(value, 6);
+        }
+    }
+    mode1.write(0, 1).write(0, 1);
+    let image = Texture::parse(&dx10(4, 4, 99, &mode1.bytes)).unwrap().decode(0).unwrap();
+    assert_eq!(pixel(&image, 1, 1), [253, 0, 0, 255]);
+    assert_eq!(pixel(&image, 2, 2), [0, 0, 253, 255]);
+
+    // Un bloc sans mode valide est noir transparent
+    let image = Texture::parse(&dx10(4, 4, 98, &[0; 16])).unwrap().decode(0).unwrap();
+    assert!(image.rgba.iter().all(|v| *v == 0));
+}
+
+#[test]
+fn selects_mipmaps_in_tex_and_dds_files() {
+    // TEX BGRA8 4x4 avec mipmaps, stockés du plus petit au plus grand
+    let mut tex = b"TEX\0".to_vec();
+    tex.extend_from_slice(&4u16.to_le_bytes());
+    tex.extend_from_slice(&4u16.to_le_bytes());
+    tex.extend_from_slice(&[1, 20, 0, 1]);
+    tex.extend_from_slice(&[0, 0, 255, 255]);
+    tex.extend(std::iter::repeat([0, 255, 0, 255]).take(4).flatten());
+    tex.extend(std::iter::repeat([255, 0, 0, 128]).take(16).flatten());
+    let texture = Texture::parse(&tex).unwrap();
+    assert_eq!((texture.format, texture.mip_count), (TextureFormat::Bgra8, 3));
+    let full = texture.decode(0).unwrap();
+    assert_eq!((full.width, full.height), (4, 4));
+    assert_eq!(pixel(&full, 3, 3), [0, 0, 255, 128]);
+    let smallest = texture.decode(2).unwrap();
+    assert_eq!((smallest.width, smallest.height, smallest.rgba.clone()), (1, 1, vec![255, 0, 0, 255]));
+    assert!(texture.decode(3).is_err());
+
+    // DDS 24 bits à masques, 2 niveaux du plus grand au plus petit
+    let mut dds = dds_header(2, 2, 2, 0x40, &[0; 4], 24, [0xFF0000, 0x00FF00, 0x0000FF, 0]);
+    dds.extend(std::iter::repeat([10, 20, 30]).take(4).flatten());
+    dds.extend_from_slice(&[1, 2, 3]);
+    let texture = Texture::parse(&dds).unwrap();
+    assert_eq!(pixel(&texture.decode(0).unwrap(), 1, 1), [30, 20, 10, 255]);
+    assert_eq!(texture.decode(1).unwrap().rgba, vec![3, 2, 1, 255]);
+}
+
+#[test]
+fn rejects_corrupted_dds_headers_without_panicking() {
+    // Nombre de niveaux ramené à celui permis par les dimensions
+    let dds = dds_header(4, 4, 1000, 0x4, b"DXT1", 0, [0; 4]);
+    let texture = Texture::parse(&dds).unwrap();
+    assert_eq!(texture.mip_count, 3);
+    assert_eq!(texture.level_dimensions(40), (1, 1));
+
+    // Dimensions dont la taille déborde : erreur au lieu d'un dépassement arithmétique
+    let huge = dds_header(u32::MAX, u32::MAX, u32::MAX, 0x40, &[0; 4], 32, [0xFF, 0xFF00, 0xFF0000, 0]);
+    let texture = Texture::parse(&huge).unwrap();
+    assert_eq!(texture.mip_count, 32);
+    assert!(texture.decode(0).is_err());
+    assert!(texture.decode(31).is_err());
+}
+
+#[test]
+fn converts_files_and_subtrees_to_png() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("assets/characters/ahri");
+    std::fs::create_dir_all(&source).unwrap();
+    // Image 5x3 : les blocs débordent de l'image
+    let mut bc1 = dds_header(5, 3, 1, 0x4, b"DXT1", 0, [0; 4]);
+    for _ in 0..2 {
+        bc1.extend_from_slice(&[0x00, 0xF8, 0x00, 0xF8, 0, 0, 0, 0]);
+    }
+    std::fs::write(source.join("ahri.dds"), &bc1).unwrap();
+    std::fs::write(source.join("broken.tex"), b"TEX\0\x04").unwrap();
+    std::fs::write(source.join("notes.txt"), b"ignored").unwrap();
+
+    let output = dir.path().join("png");
+    let report = texture::convert_tree(&dir.path().join("assets"), &output, 0).unwrap();
+    assert_eq!(report.converted.len(), 1);
+    assert_eq!(report.failed.len(), 1);
+    assert!(report.failed[0].path.ends_with("broken.tex"));
+
+    let png = std::fs::File::open(output.join("characters/ahri/ahri.png")).unwrap();
+    let mut reader = png::Decoder::new(png).read_info().unwrap();
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).unwrap();
+    assert_eq!((info.width, info.height, info.color_type), (5, 3, png::ColorType::Rgba));
+    assert_eq!(&buffer[..4], &[255, 0, 0, 255]);
+    assert!(texture::convert_file(&source.join("notes.txt"), &output.join("notes.png"), 0).is_err());
+}
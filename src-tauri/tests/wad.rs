@@ -0,0 +1,183 @@
+// Lecture d'un WAD v3 construit localement avec tous les types de compression
+use app_lib::hashes::{self, HashTable};
+use app_lib::mock_cdn::sample_data;
+use app_lib::wad::{self, Wad, WadCompression};
+use common::{build_wad, entry, gzip, RawEntry};
+use std::path::Path;
+
+mod common;
+
+const WAD_PATH: &str = "DATA/FINAL/Champions/Ahri.wad.client";
+
+fn write_wad(dir: &Path, entries: &[RawEntry]) -> std::path::PathBuf {
+    let path = dir.join("Ahri.wad.client");
+    std::fs::write(&path, build_wad(entries)).unwrap();
+    path
+}
+
+#[test]
+fn reads_every_compression_type() {
+    let dir = tempfile::tempdir().unwrap();
+    let raw = sample_data(1, 3000);
+    let gz = sample_data(2, 5000);
+    let zst = sample_data(3, 7000);
+    let part_a = sample_data(4, 4000);
+    let part_b = sample_data(5, 6000);
+    let chunked: Vec<u8> = [part_a.clone(), part_b.clone()].concat();
+
+    // Premier sous-chunk stocké brut, le second compressé
+    let compressed_b = zstd::bulk::compress(&part_b, 3).unwrap();
+    let mut toc = Vec::new();
+    for (compressed, size) in [(part_a.len(), part_a.len()), (compressed_b.len(), part_b.len())] {
+        toc.extend_from_slice(&(compressed as u32).to_le_bytes());
+        toc.extend_from_slice(&(size as u32).to_le_bytes());
+        toc.extend_from_slice(&0u64.to_le_bytes());
+    }
+    let entries = vec![
+        entry("assets/raw.bin", 0, raw.len(), raw.clone()),
+        entry("assets/gzip.bin", 1, gz.len(), gzip(&gz)),
+        entry("assets/zstd.bin", 3, zst.len(), zstd::bulk::compress(&zst, 3).unwrap()),
+        entry("assets/chunked.bin", 4 | (2 << 4), chunked.len(), [part_a.clone(), compressed_b].concat()),
+        entry(&wad::subchunk_toc_path(WAD_PATH), 0, toc.len(), toc),
+    ];
+    let path = write_wad(dir.path(), &entries);
+
+    let mut wad = Wad::open(&path).unwrap();
+    assert_eq!(wad.version, (3, 4));
+    assert_eq!(wad.entries.len(), 5);
+    assert!(wad.load_subchunk_toc(WAD_PATH).unwrap());
+
+    for (name, expected, compression) in [
+        ("assets/raw.bin", &raw, WadCompression::None),
+        ("ASSETS/Gzip.bin", &gz, WadCompression::Gzip),
+        ("assets/zstd.bin", &zst, WadCompression::Zstd),
+        ("assets/chunked.bin", &chunked, WadCompression::ZstdChunked),
+    ] {
+        let entry = wad.entry(wad::path_hash(name)).unwrap().clone();
+        assert_eq!(entry.compression, compression);
+        assert_eq!(&wad.read(&entry).unwrap(), expected, "{}", name);
+    }
+}
+
+#[test]
+fn chunked_entries_without_toc_are_decoded_frame_by_frame() {
+    let dir = tempfile::tempdir().unwrap();
+    let head = sample_data(6, 1000);
+    let tail = sample_data(7, 9000);
+    let data = [
+        head.clone(),
+        zstd::bulk::compress(&tail[..4500], 3).unwrap(),
+        zstd::bulk::compress(&tail[4500..], 3).unwrap(),
+    ]
+    .concat();
+    let path = write_wad(dir.path(), &[entry("assets/chunked.bin", 4 | (3 << 4), 10_000, data)]);
+
+    let mut wad = Wad::open(&path).unwrap();
+    assert!(!wad.load_subchunk_toc(WAD_PATH).unwrap());
+    let entry = wad.entries[0].clone();
+    assert_eq!(wad.read(&entry).unwrap(), [head, tail].concat());
+}
+
+#[test]
+fn extracts_entries_with_guessed_extensions() {
+    let dir = tempfile::tempdir().unwrap();
+    let bin = [b"PROP".to_vec(), sample_data(8, 100)].concat();
+    let dds = [b"DDS ".to_vec(), sample_data(9, 100)].concat();
+    let path = write_wad(
+        dir.path(),
+        &[
+            entry("data/characters/ahri/ahri.bin", 0, bin.len(), bin.clone()),
+            entry("assets/characters/ahri/ahri.dds", 3, dds.len(), zstd::bulk::compress(&dds, 3).unwrap()),
+        ],
+    );
+    let wad = Wad::open(&path).unwrap();
+    let output = dir.path().join("out");
+
+    let bin_hash = wad::path_hash("data/characters/ahri/ahri.bin");
+    let written = wad::extract_entries(&wad, &[bin_hash], &output, &HashTable::default()).unwrap();
+    assert_eq!(written, vec![output.join(format!("{:016x}.bin", bin_hash))]);
+    assert_eq!(std::fs::read(&written[0]).unwrap(), bin);
+
+    let written = wad::extract_entries(&wad, &[], &output, &HashTable::default()).unwrap();
+    assert_eq!(written.len(), 2);
+    assert!(written[1].to_string_lossy().ends_with(".dds"));
+    assert_eq!(wad::parse_hash(&format!("{:016x}", bin_hash)).unwrap(), bin_hash);
+}
+
+#[test]
+fn rejects_invalid_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bad.wad.client");
+
+    std::fs::write(&path, b"not a wad").unwrap();
+    assert!(Wad::open(&path).is_err());
+
+    let mut data = build_wad(&[entry("a", 0, 4, b"abcd".to_vec())]);
+    data[2] = 2;
+    std::fs::write(&path, &data).unwrap();
+    assert!(Wad::open(&path).unwrap_err().contains("2."));
+
+    let mut data = build_wad(&[entry("a", 0, 4, b"abcd".to_vec())]);
+    data.truncate(data.len() - 2);
+    std::fs::write(&path, &data).unwrap();
+    assert!(Wad::open(&path).unwrap_err().contains("hors du fichier"));
+}
+
+#[test]
+fn rejects_entries_whose_decoded_size_does_not_match() {
+    let dir = tempfile::tempdir().unwrap();
+    let data = sample_data(10, 8000);
+    let zst = zstd::bulk::compress(&data, 3).unwrap();
+    // Taille annoncée trop petite ou démesurée : erreur sans réserver la taille annoncée
+    let path = write_wad(
+        dir.path(),
+        &[
+            entry("assets/small.bin", 3, 100, zst.clone()),
+            entry("assets/huge.bin", 1, u32::MAX as usize, gzip(&data)),
+            entry("assets/chunked.bin", 4, 100, zst),
+        ],
+    );
+    let wad = Wad::open(&path).unwrap();
+    for name in ["assets/small.bin", "assets/huge.bin", "assets/chunked.bin"] {
+        let entry = wad.entry(wad::path_hash(name)).unwrap().clone();
+        assert!(wad.read(&entry).unwrap_err().contains("Taille inattendue"), "{}", name);
+    }
+}
+
+#[test]
+fn hash_table_names_entries_and_reports_unknown_hashes() {
+    let dir = tempfile::tempdir().unwrap();
+    let champions = dir.path().join("DATA/FINAL/Champions");
+    std::fs::create_dir_all(&champions).unwrap();
+    let skin = sample_data(10, 200);
+    let wad_file = write_wad(
+        &champions,
+        &[
+            entry("data/characters/ahri/skins/skin0.bin", 0, skin.len(), skin.clone()),
+            entry("assets/characters/ahri/secret.bin", 0, 4, b"abcd".to_vec()),
+        ],
+    );
+
+    let known = wad::path_hash("data/characters/ahri/skins/skin0.bin");
+    let unknown = wad::path_hash("assets/characters/ahri/secret.bin");
+    let table = HashTable::parse(&format!("{:016x} data/characters/ahri/skins/skin0.bin\n", known));
+
+    let wad = Wad::open(&wad_file).unwrap();
+    let named = wad.named_entries(&table);
+    assert_eq!(named[0].path.as_deref(), Some("data/characters/ahri/skins/skin0.bin"));
+    assert_eq!(named[1].path, None);
+    let json = serde_json::to_value(&named[0]).unwrap();
+    assert_eq!(json["pathHash"], format!("{:016x}", known));
+
+    let output = dir.path().join("out");
+    let written = wad::extract_entries(&wad, &[], &output, &table).unwrap();
+    assert_eq!(written[0], output.join("data/characters/ahri/skins/skin0.bin"));
+    assert_eq!(std::fs::read(&written[0]).unwrap(), skin);
+    assert_eq!(written[1], output.join(format!("{:016x}.bin", unknown)));
+
+    let report = hashes::unknown_hashes(dir.path(), &table).unwrap();
+    assert_eq!(report.entries, 2);
+    assert_eq!(report.unknown, 1);
+    assert_eq!(report.wads[0].wad_path, "DATA/FINAL/Champions/Ahri.wad.client");
+    assert_eq!(report.wads[0].unknown, vec![format!("{:016x}", unknown)]);
+}
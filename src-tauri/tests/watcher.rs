@@ -0,0 +1,130 @@
+// Surveillance du catalogue : nouveaux manifests, relances et rétention
+use app_lib::catalogue::CatalogueEntry;
+use app_lib::products::{Channel, Product};
+use app_lib::settings::AppSettings;
+use app_lib::watcher::{WatchSettings, WatchState, WatchedDownload};
+
+fn entry(realm: &str, hash: &str, date: &str) -> CatalogueEntry {
+    CatalogueEntry {
+        product: Product::Lol,
+        channel: Channel::from_realm(realm),
+        realm: realm.to_string(),
+        manifest_url: format!("https://cdn/{}.manifest", hash),
+        date: date.to_string(),
+        size_bytes: 0,
+    }
+}
+
+fn watched(realm: &str, hash: &str, date: &str, completed: bool) -> WatchedDownload {
+    WatchedDownload {
+        manifest: format!("https://cdn/{}.manifest", hash),
+        realm: realm.to_string(),
+        date: date.to_string(),
+        task_id: format!("task_{}", hash),
+        output_path: Some(format!("/patchs/{}", hash)),
+        completed,
+    }
+}
+
+fn settings() -> WatchSettings {
+    WatchSettings {
+        enabled: true,
+        realms: vec!["euw1".to_string(), "PBE1".to_string()],
+        ..WatchSettings::default()
+    }
+}
+
+#[test]
+fn enqueues_latest_then_only_new_manifests() {
+    let mut state = WatchState::default();
+    let catalogue = vec![
+        entry("EUW1", "A", "2024-08-14"),
+        entry("EUW1", "B", "2024-08-28"),
+        entry("NA1", "C", "2024-08-28"),
+        entry("PBE1", "D", "2024-08-20"),
+    ];
+
+    // Premier passage : uniquement le dernier patch de chaque serveur suivi
+    let first: Vec<String> = state.new_manifests(&catalogue, &settings()).into_iter().map(|e| e.manifest_url).collect();
+    assert_eq!(first, vec!["https://cdn/B.manifest", "https://cdn/D.manifest"]);
+    assert!(state.seen.contains("https://cdn/A.manifest"));
+    assert!(state.new_manifests(&catalogue, &settings()).is_empty());
+
+    let mut updated = catalogue.clone();
+    updated.push(entry("EUW1", "E", "2024-09-11"));
+    updated.push(entry("KR", "F", "2024-09-11"));
+    let second = state.new_manifests(&updated, &settings());
+    assert_eq!(second.len(), 1);
+    assert_eq!(second[0].manifest_url, "https://cdn/E.manifest");
+
+    // Un téléchargement perdu est relancé au passage suivant
+    state.downloads.push(watched("EUW1", "E", "2024-09-11", false));
+    state.retry("task_E");
+    assert!(state.downloads.is_empty());
+    assert_eq!(state.new_manifests(&updated, &settings()).len(), 1);
+}
+
+#[test]
+fn baselines_realms_added_after_the_first_pass() {
+    let mut state = WatchState::default();
+    let catalogue = vec![
+        entry("EUW1", "A", "2024-08-14"),
+        entry("NA1", "B", "2024-08-14"),
+        entry("NA1", "C", "2024-08-28"),
+        entry("NA1", "D", "2024-08-20"),
+    ];
+    assert_eq!(state.new_manifests(&catalogue, &settings()).len(), 1);
+
+    // NA ajouté ensuite : seul son dernier patch est téléchargé, pas tout son historique
+    let mut with_na = settings();
+    with_na.realms.push("na".to_string());
+    let added: Vec<String> = state.new_manifests(&catalogue, &with_na).into_iter().map(|e| e.manifest_url).collect();
+    assert_eq!(added, vec!["https://cdn/C.manifest"]);
+    assert!(state.baselined_realms.contains("NA1"));
+    assert!(state.new_manifests(&catalogue, &with_na).is_empty());
+
+    let mut updated = catalogue.clone();
+    updated.push(entry("NA1", "E", "2024-09-11"));
+    assert_eq!(state.new_manifests(&updated, &with_na).len(), 1);
+}
+
+#[test]
+fn expires_old_patches_per_realm_and_persists_state() {
+    let mut state = WatchState {
+        downloads: vec![
+            watched("EUW1", "A", "2024-08-14", true),
+            watched("EUW1", "B", "2024-08-28", true),
+            watched("EUW1", "C", "2024-09-11", true),
+            watched("EUW1", "D", "2024-09-25", false),
+            watched("PBE1", "E", "2024-09-01", true),
+        ],
+        ..WatchState::default()
+    };
+    assert!(state.expired(0).is_empty());
+    let expired: Vec<String> = state.expired(2).into_iter().map(|d| d.task_id).collect();
+    assert_eq!(expired, vec!["task_A"]);
+    assert_eq!(state.expired(1).len(), 2);
+    state.forget("task_A");
+    assert_eq!(state.downloads.len(), 4);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data").join("watch_state.json");
+    assert_eq!(WatchState::load(&path), WatchState::default());
+    state.save(&path).unwrap();
+    assert_eq!(WatchState::load(&path), state);
+}
+
+#[test]
+fn watch_settings_are_validated() {
+    assert!(AppSettings::default().validate().is_ok());
+    for watch in [
+        WatchSettings { interval_minutes: 1, ..settings() },
+        WatchSettings { realms: vec![" ".to_string()], ..settings() },
+        WatchSettings { language: "français".to_string(), ..settings() },
+    ] {
+        let settings = AppSettings { watch, ..AppSettings::default() };
+        assert!(settings.validate().is_err());
+    }
+    assert!(settings().follows("EUW1"));
+    assert!(!settings().follows("NA1"));
+}
@@ -0,0 +1,134 @@
+// Webhooks : formats des messages, nouvelles tentatives et journal, avec un récepteur HTTP local
+use app_lib::webhooks::{self, DeliveryOptions, WebhookEvent, WebhookEventKind, WebhookFormat, WebhookTarget};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+// Répond avec les statuts donnés dans l'ordre (puis 200) et garde les corps reçus
+async fn receiver(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let bodies = received.clone();
+    tokio::spawn(async move {
+        let mut statuses = statuses.into_iter();
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            let body = loop {
+                let read = socket.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((headers, body)) = text.split_once("\r\n\r\n") {
+                    let length = headers
+                        .lines()
+                        .find_map(|line| line.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        break body.to_string();
+                    }
+                }
+            };
+            bodies.lock().unwrap().push(serde_json::from_str(&body).unwrap());
+            let status = statuses.next().unwrap_or(200);
+            let response = format!("HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    (url, received)
+}
+
+fn target(url: &str, format: WebhookFormat) -> WebhookTarget {
+    WebhookTarget {
+        url: url.to_string(),
+        format,
+        ..WebhookTarget::default()
+    }
+}
+
+fn fast() -> DeliveryOptions {
+    DeliveryOptions {
+        max_attempts: 3,
+        retry_delay: Duration::from_millis(10),
+        timeout: Duration::from_secs(5),
+    }
+}
+
+#[test]
+fn builds_payloads_for_each_format() {
+    let event = WebhookEvent::new_manifest("EUW1", "https://cdn/A.manifest", "2024-08-28", "Patch 24.17");
+
+    let generic = webhooks::payload(&target("http://x", WebhookFormat::Generic), &event).unwrap();
+    assert_eq!(generic["event"], "newManifest");
+    assert_eq!(generic["data"]["realm"], "EUW1");
+
+    let discord = webhooks::payload(&target("http://x", WebhookFormat::Discord), &event).unwrap();
+    assert_eq!(discord["embeds"][0]["title"], "Nouveau patch Patch 24.17 sur EUW1");
+    assert_eq!(discord["embeds"][0]["fields"][0]["name"], "realm");
+
+    let slack = webhooks::payload(&target("http://x", WebhookFormat::Slack), &event).unwrap();
+    assert_eq!(slack["text"], "Nouveau patch Patch 24.17 sur EUW1");
+    assert_eq!(slack["blocks"].as_array().unwrap().len(), 2);
+
+    let custom = WebhookTarget {
+        template: r#"{"msg": "{title}", "realm": "{realm}", "kind": "{event}"}"#.to_string(),
+        ..target("http://x", WebhookFormat::Custom)
+    };
+    let failed = WebhookEvent::download_failed("task_1", "https://cdn/A.manifest", "Erreur \"réseau\"");
+    let rendered = webhooks::payload(&custom, &failed).unwrap();
+    assert_eq!(rendered["kind"], "downloadFailed");
+    assert_eq!(rendered["realm"], "{realm}");
+    assert!(custom.validate().is_ok());
+
+    let broken = WebhookTarget {
+        template: "{ pas du json".to_string(),
+        ..custom.clone()
+    };
+    assert!(broken.validate().is_err());
+    assert!(target("ftp://x", WebhookFormat::Generic).validate().is_err());
+
+    let only_failures = WebhookTarget {
+        events: vec![WebhookEventKind::DownloadFailed],
+        ..target("http://x", WebhookFormat::Generic)
+    };
+    assert!(only_failures.accepts(WebhookEventKind::DownloadFailed));
+    assert!(only_failures.accepts(WebhookEventKind::Test));
+    assert!(!only_failures.accepts(WebhookEventKind::NewManifest));
+}
+
+#[tokio::test]
+async fn retries_server_errors_and_logs_deliveries() {
+    let (url, received) = receiver(vec![500, 429]).await;
+    let client = reqwest::Client::new();
+    let event = WebhookEvent::download_completed("task_1", "https://cdn/A.manifest", "14.17.612.7771", "/patchs/A");
+
+    let record = webhooks::deliver(&client, &target(&url, WebhookFormat::Generic), &event, &fast()).await;
+    assert!(record.success, "{:?}", record);
+    assert_eq!(record.attempts, 3);
+    assert_eq!(record.status, Some(200));
+    assert_eq!(received.lock().unwrap().len(), 3);
+    assert_eq!(received.lock().unwrap()[2]["data"]["outputPath"], "/patchs/A");
+
+    // Erreur client : pas de nouvelle tentative
+    let (url, received) = receiver(vec![404]).await;
+    let rejected = webhooks::deliver(&client, &target(&url, WebhookFormat::Discord), &event, &fast()).await;
+    assert!(!rejected.success);
+    assert_eq!(rejected.attempts, 1);
+    assert_eq!(rejected.status, Some(404));
+    assert_eq!(received.lock().unwrap().len(), 1);
+
+    // Récepteur injoignable : toutes les tentatives échouent
+    let unreachable = webhooks::deliver(&client, &target("http://127.0.0.1:9/hook", WebhookFormat::Slack), &event, &fast()).await;
+    assert_eq!(unreachable.attempts, 3);
+    assert!(unreachable.error.is_some());
+
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("data").join(webhooks::WEBHOOK_LOG_FILE_NAME);
+    assert!(webhooks::read_log(&log, 10).is_empty());
+    for delivery in [&record, &rejected, &unreachable] {
+        webhooks::append_log(&log, delivery).unwrap();
+    }
+    let entries = webhooks::read_log(&log, 2);
+    assert_eq!(entries, vec![unreachable.clone(), rejected.clone()]);
+}
@@ -2,7 +2,7 @@
 
 import { useState, useEffect } from 'react';
 import Image from 'next/image';
-import { useManifests } from '@/hooks/useManifests';
+import { isSpecialVersion, useManifests } from '@/hooks/useManifests';
 import { useDownloads } from '@/hooks/useDownloads';
 import { PatchManifest } from '@/types';
 
@@ -93,9 +93,10 @@ export default function Home() {
   const getStatusColor = (status: string) => {
     switch (status) {
       case 'completed': return 'bg-green-600';
-      case 'error': return 'bg-red-600';
+      case 'failed': return 'bg-red-600';
       case 'paused': return 'bg-yellow-600';
       case 'downloading': return 'bg-blue-600';
+      case 'verifying': return 'bg-indigo-600';
       default: return 'bg-gray-600';
     }
   };
@@ -103,10 +104,12 @@ export default function Home() {
   const getStatusText = (status: string) => {
     switch (status) {
       case 'completed': return 'Terminé';
-      case 'error': return 'Erreur';
+      case 'failed': return 'Erreur';
+      case 'cancelled': return 'Annulé';
       case 'paused': return 'En pause';
       case 'downloading': return 'Téléchargement';
-      case 'pending': return 'En attente';
+      case 'verifying': return 'Vérification';
+      case 'queued': return 'En attente';
       default: return status;
     }
   };
@@ -399,7 +402,7 @@ export default function Home() {
                           <span className={`text-xs px-3 py-1 rounded-full font-medium ${
                             manifest.region === preferredServer 
                               ? 'bg-green-600/80 backdrop-blur-sm' 
-                              : manifest.region.includes('PBE') || manifest.region.includes('LIVESTAGING') || manifest.region.includes('LOLTMNT')
+                              : isSpecialVersion(manifest)
                               ? 'bg-purple-600/80 backdrop-blur-sm'
                               : 'bg-blue-600/80 backdrop-blur-sm'
                           }`}>
@@ -608,12 +611,17 @@ export default function Home() {
                           ✅ Terminé
                         </span>
                       )}
-                      {download.status === 'error' && (
+                      {download.status === 'failed' && (
                         <span className="bg-red-600/80 backdrop-blur-sm px-3 py-2 rounded-lg text-xs font-medium">
                           ❌ Erreur
                         </span>
                       )}
-                      {download.status !== 'completed' && download.status !== 'error' && (
+                      {download.status === 'cancelled' && (
+                        <span className="bg-gray-600/80 backdrop-blur-sm px-3 py-2 rounded-lg text-xs font-medium">
+                          🚫 Annulé
+                        </span>
+                      )}
+                      {download.status !== 'completed' && download.status !== 'failed' && download.status !== 'cancelled' && (
                         <button
                           onClick={() => {
                             cancelDownload(download.id);
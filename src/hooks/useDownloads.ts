@@ -1,6 +1,6 @@
 import { useState, useEffect } from 'react';
 import { safeInvoke } from '@/utils/tauri';
-import { DownloadTask } from '@/types';
+import { CancelMode, DownloadTask } from '@/types';
 
 export function useDownloads() {
   const [downloads, setDownloads] = useState<DownloadTask[]>([]);
@@ -60,7 +60,7 @@ export function useDownloads() {
         id: taskId,
         manifest,
         version: '', // Sera mis à jour par le backend
-        status: 'pending',
+        status: 'queued',
         progress: 0,
         speed: '0 MB/s',
         eta: '--',
@@ -101,7 +101,7 @@ export function useDownloads() {
       setDownloads(prev => 
         prev.map(task => 
           task.id === taskId 
-            ? { ...task, status: 'downloading' as const }
+            ? { ...task, status: 'queued' as const }
             : task
         )
       );
@@ -111,13 +111,13 @@ export function useDownloads() {
   };
 
   // Annuler un téléchargement
-  const cancelDownload = async (taskId: string) => {
+  const cancelDownload = async (taskId: string, mode: CancelMode = 'keep') => {
     try {
-      await safeInvoke('cancel_download', { taskId });
+      await safeInvoke('cancel_download', { taskId, mode });
       setDownloads(prev => 
         prev.map(task => 
           task.id === taskId 
-            ? { ...task, status: 'error' as const, error: 'Téléchargement annulé' }
+            ? { ...task, status: 'cancelled' as const }
             : task
         )
       );
@@ -144,7 +144,7 @@ export function useDownloads() {
   const cleanupCompleted = () => {
     setDownloads(prev => 
       prev.filter(task => 
-        task.status !== 'completed' && task.status !== 'error'
+        task.status !== 'completed' && task.status !== 'failed' && task.status !== 'cancelled'
       )
     );
   };
@@ -153,7 +153,7 @@ export function useDownloads() {
   useEffect(() => {
     const interval = setInterval(() => {
       downloads.forEach(task => {
-        if (task.status === 'downloading' || task.status === 'pending') {
+        if (task.status === 'downloading' || task.status === 'queued' || task.status === 'verifying') {
           updateProgress(task.id);
         }
       });
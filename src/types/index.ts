@@ -9,6 +9,10 @@ export interface PatchManifest {
   manifest: string;
   languages: string[];
   region: string;
+  build_version?: string; // Version complète lue dans les fichiers téléchargés (ex: "14.17.612.7771")
+  patch_version?: string; // Version d'après le calendrier des patchs (ex: "24.17", "25.S1.3", "24.17.1" pour un micro-patch)
+  product?: 'lol' | 'lol_client' | 'tft' | 'riot_client';
+  channel?: 'live' | 'pbe' | 'staging' | 'tournament';
 }
 
 // Types pour les téléchargements
@@ -16,7 +20,7 @@ export interface DownloadTask {
   id: string;
   manifest: string;
   version: string;
-  status: 'pending' | 'downloading' | 'completed' | 'error' | 'paused';
+  status: TaskStatus;
   progress: number;
   speed: string;
   eta: string;
@@ -26,18 +30,96 @@ export interface DownloadTask {
   filePath?: string; // Chemin du fichier téléchargé
   downloaded?: string; // Quantité téléchargée (ex: "15.3 MB")
   outputPath?: string; // Dossier de destination
+  realm?: string; // Serveur du manifest dans le catalogue
+  history?: StatusChange[]; // Statuts successifs horodatés
+  stall_restarts?: number; // Redémarrages automatiques après un blocage
+  language?: string; // Langue retenue au lancement
+  content_filter?: string; // Filtre de contenu retenu au lancement
+}
+
+// Cycle de vie : queued → downloading → paused / verifying → completed / failed / cancelled
+export type TaskStatus = 'queued' | 'downloading' | 'paused' | 'verifying' | 'completed' | 'failed' | 'cancelled';
+
+// Détection des téléchargements bloqués
+export interface StallSettings {
+  enabled: boolean;
+  windowSeconds: number; // Fenêtre glissante, 10 s minimum
+  minBytes: number; // Octets reçus en dessous desquels le téléchargement est bloqué
+  maxRestarts: number; // Redémarrages depuis le point de reprise avant l'échec
+}
+
+// Annulation : conserver les fichiers pour une reprise, ou supprimer ceux créés par la tâche
+export type CancelMode = 'keep' | 'delete';
+
+export interface StatusChange {
+  status: TaskStatus;
+  at: string; // Date RFC 3339
+  reason?: string;
+}
+
+// Recherche paginée des tâches (list_downloads)
+export interface TaskQuery {
+  status?: DownloadTask['status'][]; // Vide = tous
+  realm?: string;
+  version?: string;
+  offset?: number;
+  limit?: number; // 50 par défaut
+}
+
+export interface TaskPage {
+  tasks: DownloadTask[];
+  total: number;
+  offset: number;
 }
 
 // Types pour les paramètres
 export interface AppSettings {
   downloadPath: string;
+  outputTemplate: string;
   language: string;
   contentFilter: string;
+  preferredRealm: string; // Serveur affiché par défaut, ex: "EUW1"
   maxConcurrentDownloads: number;
   downloadSpeed: number;
+  stall: StallSettings;
   theme: 'light' | 'dark';
   autoStart: boolean;
   notifications: boolean;
+  hashListUrls: string[];
+  watch: WatchSettings;
+  webhooks: WebhookTarget[];
+}
+
+export type WebhookEventKind = 'newManifest' | 'downloadCompleted' | 'downloadFailed' | 'test';
+
+export interface WebhookTarget {
+  url: string;
+  format: 'generic' | 'discord' | 'slack' | 'custom';
+  events: WebhookEventKind[]; // Vide = tous les événements
+  template: string; // Modèle JSON du format "custom" ({event}, {title}, {message}, {timestamp}, {realm}...)
+  enabled: boolean;
+}
+
+// Entrée du journal des envois
+export interface WebhookDelivery {
+  url: string;
+  event: WebhookEventKind;
+  timestamp: string;
+  attempts: number;
+  status?: number;
+  success: boolean;
+  error?: string;
+}
+
+// Téléchargement automatique des nouveaux patchs
+export interface WatchSettings {
+  enabled: boolean;
+  intervalMinutes: number;
+  realms: string[]; // Serveurs suivis (ex: "EUW1", "PBE1")
+  catalogueUrl: string; // Catalogue des manifests (liste et surveillance), vide = variable d'environnement
+  language: string; // Vide = langue des paramètres
+  contentFilter: string;
+  keepLatest: number; // Patchs conservés par serveur, 0 = tout garder
 }
 
 // Types pour les filtres
@@ -71,9 +153,49 @@ export interface TauriCommands {
   startDownload: (manifest: string, language: string, content: string) => Promise<string>;
   pauseDownload: (taskId: string) => Promise<void>;
   resumeDownload: (taskId: string) => Promise<void>;
-  cancelDownload: (taskId: string) => Promise<void>;
+  cancelDownload: (taskId: string, mode?: CancelMode) => Promise<void>;
   getDownloadProgress: (taskId: string) => Promise<DownloadTask>;
   getSettings: () => Promise<AppSettings>;
   saveSettings: (settings: AppSettings) => Promise<void>;
   showNotification: (title: string, message: string) => Promise<void>;
 }
+
+// Historique des manifests vus dans le catalogue
+export interface ManifestRecord {
+  url: string;
+  realm: string;
+  hash: string;
+  date: string;
+  sizeBytes: number;
+  version: string;
+  patchVersion?: string;
+  buildVersion?: string;
+  firstSeen: string;
+  lastSeen: string;
+}
+
+export interface HistoryQuery {
+  realm?: string;
+  fromVersion?: string; // Bornes incluses, ex: "24.1" à "24.24"
+  toVersion?: string;
+  fromDate?: string; // AAAA-MM-JJ
+  toDate?: string;
+  text?: string;
+  limit?: number;
+}
+
+// Registre des serveurs
+export type PlatformRegion = 'americas' | 'europe' | 'asia' | 'sea' | 'unknown';
+
+export interface Realm {
+  id: string; // Identifiant canonique, ex: "EUW1"
+  name: string;
+  kind: 'live' | 'pbe' | 'staging' | 'tournament';
+  region: PlatformRegion;
+  known: boolean;
+}
+
+export interface RealmOverview {
+  preferred?: Realm;
+  groups: Partial<Record<PlatformRegion, Realm[]>>;
+}